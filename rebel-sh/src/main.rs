@@ -3,11 +3,14 @@
 use anyhow::Result;
 use clap::{ArgGroup, Parser};
 use colored::*;
-use rebel::core::{Module, VmValue};
+use rebel::core::{Breakpoint, Exec, Module, Stop, VmValue};
 use rebel::fs::fs_package;
+use rebel::mem::Growable;
 use rebel::ssh::ssh_package;
+use rebel::value::Value;
 use rustyline::{error::ReadlineError, DefaultEditor};
-use std::io::{self, Read};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read};
 
 /// RebelDB interactive shell
 #[derive(Parser)]
@@ -31,6 +34,14 @@ struct Args {
     #[arg(short, long)]
     stdin: bool,
 
+    /// Maximum heap size in words; the heap starts small and grows up to this limit
+    #[arg(long, value_name = "WORDS", default_value_t = 0x100_0000)]
+    max_heap: usize,
+
+    /// Start from a heap image written by `:save-image`
+    #[arg(long, value_name = "FILE")]
+    image: Option<String>,
+
     /// Legacy mode: treat all arguments as code to execute
     #[arg(trailing_var_arg = true)]
     code: Vec<String>,
@@ -43,12 +54,16 @@ fn main() -> Result<()> {
         "https://hulylabs.com".underline()
     );
 
-    let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+    // Parse command line arguments
+    let args = Args::parse();
+
+    let mut module = Module::init(Growable::new(0x10000, args.max_heap))?;
     fs_package(&mut module)?;
     ssh_package(&mut module)?;
 
-    // Parse command line arguments
-    let args = Args::parse();
+    if let Some(path) = &args.image {
+        load_image(&mut module, path)?;
+    }
 
     // Handle --execute option
     if let Some(code) = args.execute {
@@ -83,6 +98,7 @@ fn main() -> Result<()> {
     println!("Type {} or press Ctrl+D to exit\n", ":quit".red().bold());
 
     let mut rl = DefaultEditor::new()?;
+    let mut breakpoints: Vec<String> = Vec::new();
 
     // let history_path = PathBuf::from(".history");
     // if rl.load_history(&history_path).is_err() {
@@ -90,16 +106,38 @@ fn main() -> Result<()> {
     // }
 
     loop {
-        let readline = rl.readline(&"RebelDB™ ❯ ");
+        let readline = rl.readline("RebelDB™ ❯ ");
 
         match readline {
             Ok(line) => {
                 rl.add_history_entry(line.as_str())?;
-                if line.trim() == ":quit" {
+                let line = line.trim();
+                if line == ":quit" {
                     break;
                 }
 
-                execute_command(&mut module, &line)?;
+                // commands are whole words, `:breakfoo` is code rather than `:break foo`
+                let (command, arg) = line
+                    .split_once(char::is_whitespace)
+                    .map_or((line, ""), |(command, arg)| (command, arg.trim()));
+                match command {
+                    ":save-image" => {
+                        if let Err(e) = save_image(&mut module, arg) {
+                            eprintln!("{} {}", "ERROR:".red().bold(), e);
+                        }
+                    }
+                    ":load-image" => {
+                        if let Err(e) = load_image(&mut module, arg) {
+                            eprintln!("{} {}", "ERROR:".red().bold(), e);
+                        }
+                    }
+                    ":break" => toggle_breakpoint(&mut breakpoints, arg),
+                    ":step" => debug_command(&mut module, &mut rl, arg, &breakpoints, true)?,
+                    _ if !breakpoints.is_empty() => {
+                        debug_command(&mut module, &mut rl, line, &breakpoints, false)?
+                    }
+                    _ => execute_command(&mut module, line)?,
+                }
             }
             Err(ReadlineError::Interrupted) => {
                 println!("Ctrl-C");
@@ -122,7 +160,7 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn execute_command(module: &mut Module<Box<[u32]>>, command: &str) -> Result<()> {
+fn execute_command(module: &mut Module<Growable>, command: &str) -> Result<()> {
     let result = module
         .parse(command)
         .and_then(|block| module.eval(block))
@@ -135,3 +173,175 @@ fn execute_command(module: &mut Module<Box<[u32]>>, command: &str) -> Result<()>
 
     Ok(())
 }
+
+fn save_image(module: &mut Module<Growable>, path: &str) -> Result<()> {
+    module.collect()?;
+    let file = File::create(path)
+        .map_err(|e| anyhow::anyhow!("Failed to create image '{}': {}", path, e))?;
+    module.save_image(BufWriter::new(file))?;
+    println!("Saved image to {}", path.bold());
+    Ok(())
+}
+
+fn load_image(module: &mut Module<Growable>, path: &str) -> Result<()> {
+    let file =
+        File::open(path).map_err(|e| anyhow::anyhow!("Failed to open image '{}': {}", path, e))?;
+    module.load_image(BufReader::new(file))?;
+    println!("Loaded image from {}", path.bold());
+    Ok(())
+}
+
+fn toggle_breakpoint(breakpoints: &mut Vec<String>, word: &str) {
+    if word.is_empty() {
+        println!("Breakpoints: {}", breakpoints.join(" "));
+    } else if let Some(index) = breakpoints.iter().position(|bp| bp == word) {
+        breakpoints.remove(index);
+        println!("Removed breakpoint on {}", word.bold());
+    } else {
+        breakpoints.push(word.to_string());
+        println!("Breakpoint on {}", word.bold());
+    }
+}
+
+/// Run `command` under the debugger, stopping at the first value if `step` is set, and at
+/// `breakpoints` otherwise. While stopped, debugger commands are read from the editor.
+fn debug_command(
+    module: &mut Module<Growable>,
+    rl: &mut DefaultEditor,
+    command: &str,
+    breakpoints: &[String],
+    step: bool,
+) -> Result<()> {
+    let block = match module.parse(command) {
+        Ok(block) => block,
+        Err(e) => {
+            eprintln!("{} {}", "ERROR:".red().bold(), e);
+            return Ok(());
+        }
+    };
+    let mut symbols = Vec::with_capacity(breakpoints.len());
+    for word in breakpoints {
+        symbols.push(module.get_or_insert_symbol(word)?);
+    }
+
+    let mut process = module.new_process(block)?;
+    for symbol in symbols {
+        process.add_breakpoint(Breakpoint::Word(symbol));
+    }
+    println!(
+        "Debugging: {} step, {} step over, {} continue, {} {} {} inspect, {} stop",
+        ":step".bold(),
+        ":over".bold(),
+        ":continue".bold(),
+        ":where".bold(),
+        ":stack".bold(),
+        ":env".bold(),
+        ":abort".bold()
+    );
+
+    let mut stop = if step {
+        process.step()
+    } else {
+        process.resume()
+    };
+    loop {
+        match stop {
+            Ok(Stop::Paused) => print_position(&process),
+            Ok(Stop::Done(result)) => {
+                match process.to_value(result) {
+                    Ok(value) => println!("{} {}", "OK:".green(), value),
+                    Err(e) => eprintln!("{} {}", "ERROR:".red().bold(), e),
+                }
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("{} {}", "ERROR:".red().bold(), e);
+                return Ok(());
+            }
+        }
+
+        stop = loop {
+            let line = match rl.readline("debug ❯ ") {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => return Ok(()),
+                Err(err) => return Err(err.into()),
+            };
+            match line.trim() {
+                "" | ":step" => break process.step(),
+                ":over" => break process.step_over(),
+                ":continue" => break process.resume(),
+                ":where" => print_position(&process),
+                ":stack" => print_stack(&process),
+                ":env" => print_env(&process),
+                ":abort" => return Ok(()),
+                other => eprintln!(
+                    "{} unknown debugger command {}",
+                    "ERROR:".red().bold(),
+                    other
+                ),
+            }
+        };
+    }
+}
+
+fn print_position(process: &Exec<Growable>) {
+    match process.position() {
+        Ok((Value::Block(items), index)) => {
+            let mut line = Vec::with_capacity(items.len() + 1);
+            for (i, item) in items.iter().enumerate() {
+                if i == index {
+                    line.push("»".yellow().bold().to_string());
+                }
+                line.push(show(item));
+            }
+            if index >= items.len() {
+                line.push("»".yellow().bold().to_string());
+            }
+            println!("[{}]", line.join(" "));
+        }
+        Ok((other, _)) => println!("{}", other),
+        Err(e) => eprintln!("{} {}", "ERROR:".red().bold(), e),
+    }
+}
+
+fn print_stack(process: &Exec<Growable>) {
+    match process.stack_values() {
+        Ok(values) => {
+            for (i, value) in values.iter().enumerate().rev() {
+                println!("{:>4}: {}", i, value);
+            }
+        }
+        Err(e) => eprintln!("{} {}", "ERROR:".red().bold(), e),
+    }
+    match process.frames() {
+        Ok(frames) => {
+            for frame in frames.iter().rev() {
+                println!("{} {}", "frame:".dimmed(), frame);
+            }
+        }
+        Err(e) => eprintln!("{} {}", "ERROR:".red().bold(), e),
+    }
+}
+
+fn print_env(process: &Exec<Growable>) {
+    match process.env_chain() {
+        Ok(contexts) => {
+            for (depth, context) in contexts.iter().enumerate().rev() {
+                println!("{:>4}: {}", depth, context);
+            }
+        }
+        Err(e) => eprintln!("{} {}", "ERROR:".red().bold(), e),
+    }
+}
+
+/// Like `form`, but keeps the brackets of nested blocks so the code reads as written.
+fn show(value: &Value) -> String {
+    match value {
+        Value::Block(items) => {
+            let items: Vec<String> = items.iter().map(show).collect();
+            format!("[{}]", items.join(" "))
+        }
+        Value::String(s) => format!("{:?}", s.as_str()),
+        other => other.form(),
+    }
+}
@@ -1,9 +1,18 @@
 // RebelDB™ © 2025 Huly Labs • https://hulylabs.com • SPDX-License-Identifier: MIT
 
-use crate::core::CoreError;
-use crate::module::{BlobStore, Hash};
+//! Content-addressed storage for large binaries, which the heap refers to by hash.
+
+use crate::mem::MemoryError;
 use std::collections::HashMap;
 
+/// Blake3 hash of a blob.
+pub type Hash = [u8; 32];
+
+pub trait BlobStore {
+    fn get(&self, hash: &Hash) -> Result<&[u8], MemoryError>;
+    fn put(&mut self, data: &[u8]) -> Result<Hash, MemoryError>;
+}
+
 /// An in-memory implementation of BlobStore for testing and development
 pub struct MemoryBlobStore {
     blobs: HashMap<Hash, Box<[u8]>>,
@@ -35,15 +44,15 @@ impl MemoryBlobStore {
 
 impl BlobStore for MemoryBlobStore {
     /// Get blob data for a given hash
-    fn get(&self, hash: &Hash) -> Result<&[u8], CoreError> {
+    fn get(&self, hash: &Hash) -> Result<&[u8], MemoryError> {
         self.blobs
             .get(hash)
             .map(|v| v.as_ref())
-            .ok_or(CoreError::BlobNotFound)
+            .ok_or(MemoryError::BlobNotFound)
     }
 
     /// Store blob data and return its hash
-    fn put(&mut self, data: &[u8]) -> Result<Hash, CoreError> {
+    fn put(&mut self, data: &[u8]) -> Result<Hash, MemoryError> {
         let hash = blake3::hash(data);
         let result = *hash.as_bytes();
         self.blobs
@@ -1,186 +1,615 @@
 // RebelDB™ © 2025 Huly Labs • https://hulylabs.com • SPDX-License-Identifier: MIT
 
-use crate::core::{CoreError, Exec, Module, Op, VmValue};
-use crate::mem::{MemoryError, Offset, Word};
+use crate::core::{CoreError, Exec, MemValue, Module, Op, VmValue};
+use crate::date::date_package;
+use crate::file::file_package;
+use crate::mem::{Growable, MemoryError, Offset, Storage, SymbolId, Word};
+use crate::native;
+use crate::native::{Block, Name, Number, Object, Series};
+use crate::series::series_package;
+use crate::value::Value;
 
-fn add<T>(module: &mut Exec<T>) -> Result<(), CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
-    match module.pop()? {
-        [VmValue::TAG_INT, a, VmValue::TAG_INT, b] => {
-            let result = (a as i32) + (b as i32);
-            module
-                .push([VmValue::TAG_INT, result as Word])
-                .map_err(Into::into)
+/// Integers give an integer, 64-bit if it doesn't fit in a word, checked for overflow.
+/// A decimal operand makes the result a decimal, which overflows when it is not finite.
+fn arithmetic(
+    a: Number,
+    b: Number,
+    int: fn(i64, i64) -> Option<i64>,
+    decimal: fn(f64, f64) -> f64,
+) -> Result<Number, CoreError> {
+    match (a, b) {
+        (Number::Int(a), Number::Int(b)) => int(a, b).map(Number::Int),
+        (a, b) => Some(decimal(a.to_f64(), b.to_f64()))
+            .filter(|result| result.is_finite())
+            .map(Number::Decimal),
+    }
+    .ok_or(CoreError::MathOverflow)
+}
+
+#[native]
+fn add(a: Number, b: Number) -> Result<Number, CoreError> {
+    arithmetic(a, b, i64::checked_add, |a, b| a + b)
+}
+
+#[native]
+fn subtract(a: Number, b: Number) -> Result<Number, CoreError> {
+    arithmetic(a, b, i64::checked_sub, |a, b| a - b)
+}
+
+#[native]
+fn multiply(a: Number, b: Number) -> Result<Number, CoreError> {
+    arithmetic(a, b, i64::checked_mul, |a, b| a * b)
+}
+
+/// Integer division truncates, use a decimal operand for a fraction.
+#[native]
+fn divide(a: Number, b: Number) -> Result<Number, CoreError> {
+    if b.to_f64() == 0.0 {
+        return Err(CoreError::ZeroDivide);
+    }
+    arithmetic(a, b, i64::checked_div, |a, b| a / b)
+}
+
+#[native]
+fn remainder(a: Number, b: Number) -> Result<Number, CoreError> {
+    if b.to_f64() == 0.0 {
+        return Err(CoreError::ZeroDivide);
+    }
+    arithmetic(a, b, i64::checked_rem, |a, b| a % b)
+}
+
+#[native]
+fn negate(value: Number) -> Result<Number, CoreError> {
+    match value {
+        Number::Int(value) => value.checked_neg().map(Number::Int),
+        Number::Decimal(value) => Some(Number::Decimal(-value)),
+    }
+    .ok_or(CoreError::MathOverflow)
+}
+
+#[native]
+fn abs(value: Number) -> Result<Number, CoreError> {
+    match value {
+        Number::Int(value) => value.checked_abs().map(Number::Int),
+        Number::Decimal(value) => Some(Number::Decimal(value.abs())),
+    }
+    .ok_or(CoreError::MathOverflow)
+}
+
+#[native]
+fn min(a: Number, b: Number) -> Number {
+    if b < a {
+        b
+    } else {
+        a
+    }
+}
+
+#[native]
+fn max(a: Number, b: Number) -> Number {
+    if b > a {
+        b
+    } else {
+        a
+    }
+}
+
+#[native]
+fn lt(a: Number, b: Number) -> bool {
+    a < b
+}
+
+#[native(name = "lesser?")]
+fn lesser(a: Number, b: Number) -> bool {
+    a < b
+}
+
+#[native(name = "greater?")]
+fn greater(a: Number, b: Number) -> bool {
+    a > b
+}
+
+#[native(name = "lesser-or-equal?")]
+fn lesser_or_equal(a: Number, b: Number) -> bool {
+    a <= b
+}
+
+#[native(name = "greater-or-equal?")]
+fn greater_or_equal(a: Number, b: Number) -> bool {
+    a >= b
+}
+
+#[native]
+fn not(value: VmValue) -> bool {
+    !value.is_truthy()
+}
+
+#[native(name = "and~")]
+fn and(a: VmValue, b: VmValue) -> bool {
+    a.is_truthy() && b.is_truthy()
+}
+
+#[native(name = "or~")]
+fn or(a: VmValue, b: VmValue) -> bool {
+    a.is_truthy() || b.is_truthy()
+}
+
+/// Compare values structurally: series and contexts by their contents, numbers by their
+/// value, anything else by identity.
+pub(crate) fn equal_values<T: Storage>(
+    module: &Exec<T>,
+    a: VmValue,
+    b: VmValue,
+) -> Result<bool, CoreError> {
+    match (a, b) {
+        (VmValue::String(_), VmValue::String(_))
+        | (VmValue::Binary(_), VmValue::Binary(_))
+        | (VmValue::File(_), VmValue::File(_))
+        | (VmValue::Url(_), VmValue::Url(_))
+        | (VmValue::Date(_), VmValue::Date(_))
+        | (VmValue::Time(_), VmValue::Time(_))
+        | (VmValue::Block(_), VmValue::Block(_))
+        | (VmValue::Path(_), VmValue::Path(_))
+        | (VmValue::SetPath(_), VmValue::SetPath(_))
+        | (VmValue::GetPath(_), VmValue::GetPath(_))
+        | (VmValue::Context(_), VmValue::Context(_))
+        | (VmValue::Error(_), VmValue::Error(_))
+        | (VmValue::Int64(_) | VmValue::Decimal(_), VmValue::Int64(_) | VmValue::Decimal(_))
+        | (VmValue::Decimal(_), VmValue::Int(_))
+        | (VmValue::Int(_), VmValue::Decimal(_)) => {
+            Ok(a == b || module.to_value(a)?.equals(&module.to_value(b)?))
         }
-        _ => Err(CoreError::BadArguments),
+        _ => Ok(a == b),
     }
 }
 
-fn lt<T>(module: &mut Exec<T>) -> Result<(), CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
-    match module.pop()? {
-        [VmValue::TAG_INT, a, VmValue::TAG_INT, b] => {
-            let result = if (a as i32) < (b as i32) { 1 } else { 0 };
-            module.push([VmValue::TAG_BOOL, result]).map_err(Into::into)
+#[native(name = "equal?")]
+fn equal<T: Storage>(module: &mut Exec<T>, a: VmValue, b: VmValue) -> Result<bool, CoreError> {
+    equal_values(module, a, b)
+}
+
+#[native(name = "not-equal?")]
+fn not_equal<T: Storage>(module: &mut Exec<T>, a: VmValue, b: VmValue) -> Result<bool, CoreError> {
+    equal_values(module, a, b).map(|equal| !equal)
+}
+
+/// Evaluate a block, or a string loaded into one.
+#[native(name = "do")]
+fn func_do<T: Storage>(module: &mut Exec<T>, code: Series) -> Result<(), CoreError> {
+    match code {
+        Series::Block(block) => module.jmp(block),
+        Series::String(string) => match module.to_value(VmValue::String(string))? {
+            Value::String(source) => match module.parse(&source)? {
+                VmValue::Block(block) => module.jmp(block),
+                _ => Err(CoreError::InternalError),
+            },
+            _ => Err(CoreError::InternalError),
+        },
+        Series::Binary(_) => Err(CoreError::BadArguments),
+    }
+}
+
+/// Block of the values in `source`.
+#[native]
+fn load<T: Storage>(module: &mut Exec<T>, source: String) -> Result<VmValue, CoreError> {
+    module.parse(&source)
+}
+
+#[native]
+fn context<T: Storage>(module: &mut Exec<T>, block: Block) -> Result<(), CoreError> {
+    let ctx = module.alloc_context(64)?;
+    module.push_op(Op::CONTEXT, ctx, 2)?;
+    module.jmp_in(block.0, ctx)
+}
+
+/// Copy of `proto` extended by evaluating `spec` in it, like `context`. Functions that
+/// captured the prototype capture the copy instead, so they see its words.
+#[native]
+fn make<T: Storage>(module: &mut Exec<T>, proto: Object, spec: Block) -> Result<(), CoreError> {
+    let entries: Vec<_> = module.get_context(proto.0)?.iter().collect();
+    let ctx = module.alloc_context(entries.len() as u32 + 64)?;
+    for (symbol, mut value) in entries {
+        if value[0] == VmValue::TAG_FUNC {
+            let mut func: [Word; 10] = module.get_block(value[1], 0)?;
+            let [_, scope_ctx, tag, parent] = module.get_block(func[7], 0)?;
+            if scope_ctx == proto.0 {
+                func[7] = module.alloc_block(&[VmValue::TAG_CONTEXT, ctx, tag, parent])?;
+                value[1] = module.alloc_block(&func)?;
+            }
         }
-        _ => Err(CoreError::BadArguments),
+        module.get_context(ctx)?.put(symbol, value)?;
     }
+    module.push_op(Op::CONTEXT, ctx, 2)?;
+    module.jmp_in(spec.0, ctx)
 }
 
-fn func_do<T>(module: &mut Exec<T>) -> Result<(), CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
-    match module.pop()? {
-        [VmValue::TAG_BLOCK, block] => module.jmp(block),
-        _ => Err(CoreError::BadArguments),
+#[native(name = "words-of")]
+fn words_of<T: Storage>(module: &mut Exec<T>, ctx: Object) -> Result<Block, CoreError> {
+    let words: Vec<Word> = module
+        .get_context(ctx.0)?
+        .iter()
+        .flat_map(|(symbol, _)| [VmValue::TAG_WORD, symbol])
+        .collect();
+    module.alloc_block(&words).map(Block).map_err(Into::into)
+}
+
+/// Values of a context, in the order of `words-of`.
+#[native(name = "values-of")]
+fn values_of<T: Storage>(module: &mut Exec<T>, ctx: Object) -> Result<Block, CoreError> {
+    let values: Vec<Word> = module
+        .get_context(ctx.0)?
+        .iter()
+        .flat_map(|(_, value)| value)
+        .collect();
+    module.alloc_block(&values).map(Block).map_err(Into::into)
+}
+
+fn has_word<T: Storage>(
+    module: &mut Exec<T>,
+    ctx: Offset,
+    symbol: SymbolId,
+) -> Result<bool, CoreError> {
+    match module.get_context(ctx)?.get(symbol) {
+        Ok(_) => Ok(true),
+        Err(MemoryError::WordNotFound) => Ok(false),
+        Err(err) => Err(err.into()),
     }
 }
 
-fn context<T>(module: &mut Exec<T>) -> Result<(), CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
-    match module.pop()? {
-        [VmValue::TAG_BLOCK, block] => {
-            let ctx = module.alloc_context(64)?;
-            module.push_context(ctx)?;
-            module.push_op(Op::CONTEXT, 0, 2)?;
-            module.jmp(block)
+/// A word bound to a context is the path from the context to the word, `none` if the
+/// context has no such word.
+#[native(name = "in")]
+fn in_context<T: Storage>(
+    module: &mut Exec<T>,
+    ctx: Object,
+    word: Name,
+) -> Result<Option<VmValue>, CoreError> {
+    if !has_word(module, ctx.0, word.0)? {
+        return Ok(None);
+    }
+    let path = module.alloc_block(&[VmValue::TAG_CONTEXT, ctx.0, VmValue::TAG_WORD, word.0])?;
+    Ok(Some(VmValue::Path(path)))
+}
+
+/// Value of a word, or of a word bound with `in`.
+#[native]
+fn get<T: Storage>(module: &mut Exec<T>, word: VmValue) -> Result<MemValue, CoreError> {
+    module.get(word)
+}
+
+#[native]
+fn set<T: Storage>(
+    module: &mut Exec<T>,
+    word: VmValue,
+    value: MemValue,
+) -> Result<MemValue, CoreError> {
+    module.set(word, value)?;
+    Ok(value)
+}
+
+/// Bind the words of `block` and of the blocks in it to `ctx`, the ones `ctx` has: the
+/// blocks are evaluated with `ctx` in front of their scope, wherever they are evaluated.
+#[native]
+fn bind<T: Storage>(module: &mut Exec<T>, block: Block, ctx: Object) -> Result<Block, CoreError> {
+    bind_block(module, block.0, ctx.0, &mut Vec::new())?;
+    Ok(block)
+}
+
+fn bind_block<T: Storage>(
+    module: &mut Exec<T>,
+    block: Offset,
+    ctx: Offset,
+    seen: &mut Vec<Offset>,
+) -> Result<(), CoreError> {
+    // blocks can contain themselves
+    if seen.contains(&block) {
+        return Ok(());
+    }
+    seen.push(block);
+
+    module.bind(block, ctx)?;
+    let values = module.get_block_data(block)?.to_vec();
+    for value in values.chunks_exact(2) {
+        if let [VmValue::TAG_BLOCK, inner] = *value {
+            bind_block(module, inner, ctx, seen)?;
         }
-        _ => Err(CoreError::BadArguments),
     }
+    Ok(())
 }
 
-fn reduce<T>(module: &mut Exec<T>) -> Result<(), CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
-    let [tag, block] = module.peek::<2>().ok_or(MemoryError::StackUnderflow)?;
-    if tag == VmValue::TAG_BLOCK {
-        module.pop::<2>()?;
-        module.jmp_op(block, Op::REDUCE)
+#[native]
+fn reduce<T: Storage>(module: &mut Exec<T>, value: VmValue) -> Result<(), CoreError> {
+    match value {
+        VmValue::Block(block) => module.jmp_op(block, Op::REDUCE),
+        value => module.push_vm_value(value).map_err(Into::into),
+    }
+}
+
+#[native]
+fn foreach<T: Storage>(
+    module: &mut Exec<T>,
+    #[literal] word: Name,
+    data: Block,
+    body: Block,
+) -> Result<(), CoreError> {
+    if let Ok(value) = module.get_block::<2>(data.0, 0) {
+        let ctx_offset = module.alloc_context(1)?;
+        let mut ctx = module.get_context(ctx_offset)?;
+        ctx.put(word.0, value)?;
+        ctx.seal()?;
+
+        module.push([
+            VmValue::TAG_WORD,
+            word.0,
+            VmValue::TAG_BLOCK,
+            data.0,
+            VmValue::TAG_BLOCK,
+            body.0,
+        ])?;
+        module.push([VmValue::TAG_INT, 0])?;
+        module.jmp_op_in(body.0, Op::FOREACH, ctx_offset)
     } else {
-        Ok(())
+        module.push_vm_value(VmValue::None).map_err(Into::into)
     }
 }
 
-fn foreach<T>(module: &mut Exec<T>) -> Result<(), CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
-    let args = module.pop::<6>()?;
-    println!("args: {:?}", args);
-    match args {
-        [VmValue::TAG_WORD, word, VmValue::TAG_BLOCK, data, VmValue::TAG_BLOCK, body] => {
-            if let Ok(value) = module.get_block::<2>(data, 0) {
-                let ctx_offset = module.alloc_context(1)?;
-                let mut ctx = module.get_context(ctx_offset)?;
-                ctx.put(word, value)?;
-                ctx.seal()?;
-
-                println!("ctx: {:?}", ctx);
-
-                module.push_context(ctx_offset)?;
-                module.push(args)?;
-                module.push([VmValue::TAG_INT, 0])?;
-                module.jmp_op(body, Op::FOREACH)
-            } else {
-                Ok(())
-            }
-        }
-        _ => Err(CoreError::BadArguments),
+#[native]
+fn repeat<T: Storage>(
+    module: &mut Exec<T>,
+    #[literal] word: Name,
+    count: i32,
+    body: Block,
+) -> Result<(), CoreError> {
+    if count > 0 {
+        let ctx_offset = module.alloc_context(1)?;
+        let mut ctx = module.get_context(ctx_offset)?;
+        ctx.put(word.0, [VmValue::TAG_INT, 1])?;
+        ctx.seal()?;
+
+        module.push([
+            VmValue::TAG_WORD,
+            word.0,
+            VmValue::TAG_INT,
+            count as Word,
+            VmValue::TAG_BLOCK,
+            body.0,
+            VmValue::TAG_INT,
+            1,
+        ])?;
+        module.jmp_op_in(body.0, Op::REPEAT, ctx_offset)
+    } else {
+        module.push_vm_value(VmValue::None).map_err(Into::into)
     }
 }
 
-fn func<T>(module: &mut Exec<T>) -> Result<(), CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
-    let [tag1, params, tag2, body] = module.pop::<4>()?;
-    if tag1 != VmValue::TAG_BLOCK || tag2 != VmValue::TAG_BLOCK {
-        return Err(CoreError::BadArguments);
+#[native(name = "loop")]
+fn loop_block<T: Storage>(module: &mut Exec<T>, count: i32, body: Block) -> Result<(), CoreError> {
+    if count > 0 {
+        module.push([VmValue::TAG_BLOCK, body.0, VmValue::TAG_INT, count as Word])?;
+        module.jmp_op(body.0, Op::LOOP)
+    } else {
+        module.push_vm_value(VmValue::None).map_err(Into::into)
     }
-    let arity = module.get_block_len(params)? as Offset;
-    let func = module.alloc_block(&[
-        VmValue::TAG_INT,
-        arity,
+}
+
+#[native(name = "while")]
+fn while_block<T: Storage>(
+    module: &mut Exec<T>,
+    condition: Block,
+    body: Block,
+) -> Result<(), CoreError> {
+    // the condition is evaluated first, then the body while it holds
+    module.push([
         VmValue::TAG_BLOCK,
-        params,
+        condition.0,
         VmValue::TAG_BLOCK,
-        body,
+        body.0,
+        VmValue::TAG_NONE,
+        0,
+        VmValue::TAG_INT,
+        0,
     ])?;
-    module.push([VmValue::TAG_FUNC, func]).map_err(Into::into)
+    module.jmp_op(condition.0, Op::WHILE)
 }
 
-fn either<T>(module: &mut Exec<T>) -> Result<(), CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
-    match module.pop()? {
-        [VmValue::TAG_BOOL, cond, VmValue::TAG_BLOCK, if_true, VmValue::TAG_BLOCK, if_false] => {
-            let block = if cond != 0 { if_true } else { if_false };
-            module.jmp(block)
-        }
-        _ => Err(CoreError::BadArguments),
-    }
+#[native(name = "break")]
+fn break_loop<T: Storage>(module: &mut Exec<T>) -> Result<(), CoreError> {
+    module.break_loop()
 }
 
-fn print<T>(module: &mut Exec<T>) -> Result<(), CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
-    module.pop_to_value().map(|value| print!("{}", value))
+#[native(name = "continue")]
+fn continue_loop<T: Storage>(module: &mut Exec<T>) -> Result<(), CoreError> {
+    module.continue_loop()
 }
 
-fn is_block<T>(module: &mut Exec<T>) -> Result<(), CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
-    let value = module.pop::<2>()?;
-    if value[0] == VmValue::TAG_BLOCK {
-        module.push([VmValue::TAG_BOOL, 1]).map_err(Into::into)
+#[native(name = "return")]
+fn return_value<T: Storage>(module: &mut Exec<T>, value: VmValue) -> Result<(), CoreError> {
+    module.return_value(value.vm_repr())
+}
+
+#[native]
+fn func<T: Storage>(module: &mut Exec<T>, spec: Block, body: Block) -> Result<VmValue, CoreError> {
+    module.alloc_func(spec.0, body.0).map(VmValue::Func)
+}
+
+#[native]
+fn either<T: Storage>(
+    module: &mut Exec<T>,
+    condition: VmValue,
+    true_block: Block,
+    false_block: Block,
+) -> Result<(), CoreError> {
+    let block = if condition.is_truthy() {
+        true_block
+    } else {
+        false_block
+    };
+    module.jmp(block.0)
+}
+
+#[native(name = "if")]
+fn if_block<T: Storage>(
+    module: &mut Exec<T>,
+    condition: VmValue,
+    block: Block,
+) -> Result<(), CoreError> {
+    if condition.is_truthy() {
+        module.jmp(block.0)
     } else {
-        module.push([VmValue::TAG_BOOL, 0]).map_err(Into::into)
+        module.push_vm_value(VmValue::None).map_err(Into::into)
     }
 }
 
-fn form<T>(module: &mut Exec<T>) -> Result<(), CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
-    let value = module.pop_to_value()?;
-    let form = value.form();
-    module
-        .alloc_string(form.as_str())
-        .and_then(|s| module.push([VmValue::TAG_INLINE_STRING, s]))
-        .map_err(Into::into)
+#[native]
+fn unless<T: Storage>(
+    module: &mut Exec<T>,
+    condition: VmValue,
+    block: Block,
+) -> Result<(), CoreError> {
+    if condition.is_truthy() {
+        module.push_vm_value(VmValue::None).map_err(Into::into)
+    } else {
+        module.jmp(block.0)
+    }
+}
+
+#[native(name = "system_print")]
+fn print(value: Value) {
+    print!("{}", value)
+}
+
+#[native(name = "block?")]
+fn is_block(value: VmValue) -> bool {
+    value.is_block()
+}
+
+#[native]
+fn form(value: Value) -> String {
+    value.form()
+}
+
+#[native]
+fn mold(value: Value) -> String {
+    value.mold()
+}
+
+#[native(name = "try")]
+fn try_block<T: Storage>(module: &mut Exec<T>, block: Block) -> Result<(), CoreError> {
+    module.jmp_handler(block.0, Op::TRY)
+}
+
+#[native]
+fn attempt<T: Storage>(module: &mut Exec<T>, block: Block) -> Result<(), CoreError> {
+    module.jmp_handler(block.0, Op::ATTEMPT)
+}
+
+#[native]
+fn catch<T: Storage>(module: &mut Exec<T>, block: Block) -> Result<(), CoreError> {
+    module.jmp_handler(block.0, Op::CATCH)
 }
 
+#[native]
+fn throw<T: Storage>(module: &mut Exec<T>, value: VmValue) -> Result<(), CoreError> {
+    match value {
+        VmValue::Error(error) => Err(module.reraise(error)?),
+        value => module.throw(value.vm_repr()),
+    }
+}
+
+#[native]
+fn make_error<T: Storage>(module: &mut Exec<T>, message: String) -> Result<VmValue, CoreError> {
+    let error = module.alloc_error("user", &message, None)?;
+    Ok(VmValue::Error(error))
+}
+
+#[native(name = "error?")]
+fn is_error(value: VmValue) -> bool {
+    value.is_error()
+}
+
+/// Infix operators and the natives they call.
+const OPERATORS: [(&str, &str); 12] = [
+    ("+", "add"),
+    ("-", "subtract"),
+    ("*", "multiply"),
+    ("/", "divide"),
+    ("=", "equal?"),
+    ("<>", "not-equal?"),
+    ("<", "lesser?"),
+    (">", "greater?"),
+    ("<=", "lesser-or-equal?"),
+    (">=", "greater-or-equal?"),
+    ("and", "and~"),
+    ("or", "or~"),
+];
+
 pub fn core_package<T>(module: &mut Module<T>) -> Result<(), CoreError>
 where
-    T: AsMut<[Word]> + AsRef<[Word]>,
+    T: Storage,
 {
-    module.add_native_fn("add", add, 2)?;
-    module.add_native_fn("lt", lt, 2)?;
-    module.add_native_fn("do", func_do, 1)?;
-    module.add_native_fn("context", context, 1)?;
-    module.add_native_fn("func", func, 2)?;
-    module.add_native_fn("either", either, 3)?;
-    module.add_native_fn("system_print", print, 1)?;
-    module.add_native_fn("block?", is_block, 1)?;
-    module.add_native_fn("reduce", reduce, 1)?;
-    module.add_native_fn("foreach", foreach, 100)?;
-    module.add_native_fn("form", form, 1)?;
+    add::register(module)?;
+    subtract::register(module)?;
+    multiply::register(module)?;
+    divide::register(module)?;
+    remainder::register(module)?;
+    negate::register(module)?;
+    abs::register(module)?;
+    min::register(module)?;
+    max::register(module)?;
+    lt::register(module)?;
+    lesser::register(module)?;
+    greater::register(module)?;
+    lesser_or_equal::register(module)?;
+    greater_or_equal::register(module)?;
+    equal::register(module)?;
+    not_equal::register(module)?;
+    not::register(module)?;
+    and::register(module)?;
+    or::register(module)?;
+    func_do::register(module)?;
+    context::register(module)?;
+    make::register(module)?;
+    words_of::register(module)?;
+    values_of::register(module)?;
+    in_context::register(module)?;
+    get::register(module)?;
+    set::register(module)?;
+    bind::register(module)?;
+    func::register(module)?;
+    either::register(module)?;
+    print::register(module)?;
+    is_block::register(module)?;
+    reduce::register(module)?;
+    foreach::register(module)?;
+    repeat::register(module)?;
+    loop_block::register(module)?;
+    while_block::register(module)?;
+    break_loop::register(module)?;
+    continue_loop::register(module)?;
+    return_value::register(module)?;
+    if_block::register(module)?;
+    unless::register(module)?;
+
+    for (op, native) in OPERATORS {
+        module.add_operator(op, native)?;
+    }
+    form::register(module)?;
+    mold::register(module)?;
+    load::register(module)?;
+    try_block::register(module)?;
+    attempt::register(module)?;
+    catch::register(module)?;
+    throw::register(module)?;
+    make_error::register(module)?;
+    is_error::register(module)?;
+    series_package(module)?;
+    date_package(module)?;
+    file_package(module)?;
     Ok(())
 }
 
 pub fn test_either(module: &mut Exec<&mut [Word]>) -> Result<(), CoreError> {
-    either(module)
+    either::native(module)
 }
 
 /// Execute the standard library code for a module
@@ -188,13 +617,52 @@ pub fn test_either(module: &mut Exec<&mut [Word]>) -> Result<(), CoreError> {
 /// This function parses and executes the standard library code that defines
 /// common functions like print and prin. The stdlib code is read from the
 /// stdlib.rebel file at compile time using the include_str! macro.
+///
+/// Modules normally start from a heap image with the standard library already
+/// evaluated, built together with the crate. The code is evaluated only when the
+/// image can not be loaded, e.g. because the natives of the module differ.
 pub fn stdlib_package<T>(module: &mut Module<T>) -> Result<(), CoreError>
 where
-    T: AsMut<[Word]> + AsRef<[Word]>,
+    T: Storage,
 {
+    stdlib_from_image(module, embedded_image())
+}
+
+const STDLIB_SOURCE: &str = include_str!("stdlib.rebel");
+
+/// Image written by `cargo xtask stdlib-image`, see [`stdlib_image`].
+const STDLIB_IMAGE: &[u8] = include_bytes!("stdlib.img");
+
+/// The embedded image, unless the standard library changed since it was written.
+fn embedded_image() -> Option<&'static [u8]> {
+    let (hash, image) = STDLIB_IMAGE.split_at_checked(32)?;
+    (hash == blake3::hash(STDLIB_SOURCE.as_bytes()).as_bytes()).then_some(image)
+}
+
+/// Heap image of a module with the core natives and the standard library evaluated from
+/// source, prefixed with the hash of the source. `Module::init` loads it instead of
+/// evaluating the source, unless the natives or the source changed since.
+pub fn stdlib_image() -> Result<Vec<u8>, CoreError> {
+    let mut module = Module::init_core(Growable::new(0x10000, 0x100_0000))?;
+    stdlib_from_image(&mut module, None)?;
+    module.collect()?;
+    let mut image = blake3::hash(STDLIB_SOURCE.as_bytes()).as_bytes().to_vec();
+    module.save_image(&mut image)?;
+    Ok(image)
+}
+
+fn stdlib_from_image<T>(module: &mut Module<T>, image: Option<&[u8]>) -> Result<(), CoreError>
+where
+    T: Storage,
+{
+    if let Some(image) = image {
+        if module.load_image(image).is_ok() {
+            return Ok(());
+        }
+    }
+
     // Read and execute the standard library
-    let stdlib_code = include_str!("stdlib.rebel");
-    let vm_block = module.parse(stdlib_code)?;
+    let vm_block = module.parse(STDLIB_SOURCE)?;
     module.eval(vm_block)?;
 
     // Read and execute the extended standard library
@@ -211,8 +679,9 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::Param;
+    use crate::parse::ParserError;
     use crate::rebel;
-    use crate::value::Value;
 
     #[test]
     fn test_add() {
@@ -242,4 +711,314 @@ mod tests {
 
         assert_eq!(value, Value::int(15));
     }
+
+    fn eval_code(module: &mut Module<Box<[Word]>>, code: &str) -> Result<Value, CoreError> {
+        let block = module.parse(code)?;
+        let result = module.eval(block)?;
+        module.to_value(result)
+    }
+
+    #[test]
+    fn test_arithmetic() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        let cases = [
+            ("subtract 7 10", -3),
+            ("multiply -6 7", -42),
+            ("divide 7 2", 3),
+            ("divide -7 2", -3),
+            ("remainder -7 2", -1),
+            ("negate 5", -5),
+            ("abs -5", 5),
+            ("min 3 -4", -4),
+            ("max 3 -4", 3),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(
+                eval_code(&mut module, code)?,
+                Value::int(expected),
+                "{code}"
+            );
+        }
+
+        let result = eval_code(&mut module, "add 9223372036854775807 1");
+        assert!(matches!(result, Err(CoreError::MathOverflow)));
+        let result = eval_code(&mut module, "negate subtract -9223372036854775807 1");
+        assert!(matches!(result, Err(CoreError::MathOverflow)));
+        let result = eval_code(&mut module, "remainder 1 0");
+        assert!(matches!(result, Err(CoreError::ZeroDivide)));
+
+        let result = eval_code(&mut module, "try [divide 1 0]")?;
+        assert_eq!(result.get("kind"), Some(&Value::word("zero-divide")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_int64() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        let cases = [
+            ("2147483647 + 1", Value::Int64(2147483648)),
+            ("negate -2147483648", Value::Int64(2147483648)),
+            ("1700000000000 * 1000", Value::Int64(1700000000000000)),
+            ("subtract 4294967296 4294967295", Value::int(1)),
+            ("divide 4294967296 2.0", Value::decimal(2147483648.0)),
+            ("equal? 4294967296 4294967296", Value::Bool(true)),
+            (
+                "lesser? 9007199254740993 9007199254740992",
+                Value::Bool(false),
+            ),
+            ("max 1 4294967296", Value::Int64(4294967296)),
+            (
+                "f: func [n [integer!]] [n] f 4294967296",
+                Value::Int64(4294967296),
+            ),
+            ("mold [-4294967296]", Value::string("[-4294967296]")),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(eval_code(&mut module, code)?, expected, "{code}");
+        }
+
+        let result = eval_code(&mut module, "9223372036854775808");
+        assert!(matches!(result, Err(CoreError::ParserError(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_decimal() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        let cases = [
+            ("1e10", Value::decimal(1e10)),
+            ("1.5 + 2", Value::decimal(3.5)),
+            ("divide 7 2.0", Value::decimal(3.5)),
+            ("0.5 * 10", Value::decimal(5.0)),
+            ("remainder 7.5 2", Value::decimal(1.5)),
+            ("negate -0.25", Value::decimal(0.25)),
+            ("abs -1.5", Value::decimal(1.5)),
+            ("max 1 2.5", Value::decimal(2.5)),
+            ("min 1 2.5", Value::int(1)),
+            ("lesser? 1 1.5", Value::Bool(true)),
+            ("2.5 >= 3", Value::Bool(false)),
+            ("equal? 1 1.0", Value::Bool(true)),
+            ("0.5 = 0.5", Value::Bool(true)),
+            ("mold [1.0 -0.25]", Value::string("[1.0 -0.25]")),
+            ("first load mold 1e20", Value::decimal(1e20)),
+            (
+                "mold [1e300 -2.5e-10 .5]",
+                Value::string("[1e300 -2.5e-10 0.5]"),
+            ),
+            ("first load mold 1.5e-300", Value::decimal(1.5e-300)),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(eval_code(&mut module, code)?, expected, "{code}");
+        }
+
+        let result = eval_code(&mut module, "divide 1.5 0");
+        assert!(matches!(result, Err(CoreError::ZeroDivide)));
+        let result = eval_code(&mut module, "multiply 1e300 1e300");
+        assert!(matches!(result, Err(CoreError::MathOverflow)));
+        // decimals out of range don't load, so there is nothing to mold as `inf`
+        for code in ["mold 1e400", "mold -1e400", r#"mold load "[1e400]""#] {
+            let result = eval_code(&mut module, code);
+            assert!(
+                matches!(
+                    result,
+                    Err(CoreError::ParserError(ParserError::InvalidDecimal))
+                ),
+                "{code}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_comparison() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        let cases = [
+            ("lesser? 1 2", true),
+            ("greater? 1 2", false),
+            ("lesser-or-equal? 2 2", true),
+            ("greater-or-equal? 1 2", false),
+            ("equal? 1 1", true),
+            ("equal? 1 \"1\"", false),
+            ("equal? \"abc\" \"abc\"", true),
+            ("not-equal? \"abc\" \"abd\"", true),
+            ("equal? [1 [a \"b\"]] [1 [a \"b\"]]", true),
+            ("equal? [1 2] [1 2 3]", false),
+            ("equal? context [a: 1] [a: 1]", false),
+            ("equal? context [a: 1 b: 2] context [b: 2 a: 1]", true),
+            ("equal? context [a: 1] context [a: 2]", false),
+            ("equal? lt 1 2 lt 0 1", true),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(
+                eval_code(&mut module, code)?,
+                Value::Bool(expected),
+                "{code}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_mold() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        let cases = [
+            (
+                "mold \"a \\\"b\\\"\\n\"",
+                Value::string("\"a \\\"b\\\"\\n\""),
+            ),
+            (
+                "mold [a: 1 :b /c \"d\" [e/f: g]]",
+                Value::string("[a: 1 :b /c \"d\" [e/f: g]]"),
+            ),
+            (
+                "mold context [a: \"b\"]",
+                Value::string("make object! [a: \"b\"]"),
+            ),
+            (
+                "load \"a: 1 [b]\"",
+                Value::block([
+                    Value::set_word("a"),
+                    Value::int(1),
+                    Value::block([Value::word("b")]),
+                ]),
+            ),
+            ("do load \"1 + 2\"", Value::int(3)),
+            ("do \"x: 2 x * 5\"", Value::int(10)),
+            (
+                "data: [\"q\\\"\" [1 x:]] equal? data first load mold data",
+                Value::Bool(true),
+            ),
+            ("do first load mold [add 1 2]", Value::int(3)),
+            ("do mold none", Value::None),
+            ("do mold true", Value::Bool(true)),
+            ("do mold equal? 1 2", Value::Bool(false)),
+            (
+                "mold context [c: 'w]",
+                Value::string("make object! [c: 'w]"),
+            ),
+            (
+                "o: context [a: 1 b: \"x\" c: 'w d: none e: context [f: [g]]] equal? o do mold o",
+                Value::Bool(true),
+            ),
+            ("mold func [x] [x]", Value::string("func [x] [x]")),
+            (
+                "mold func [x [integer! string!] /twice y] [x * 2]",
+                Value::string("func [x [integer! string!] /twice y] [x * 2]"),
+            ),
+            ("f: do mold func [x [integer!]] [x + 1] f 1", Value::int(2)),
+            (
+                "mold context [f: func [] [1]]",
+                Value::string("make object! [f: func [] [1]]"),
+            ),
+            (
+                "o: do mold context [x: 2 f: func [y] [x * y]] o/f 3",
+                Value::int(6),
+            ),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(eval_code(&mut module, code)?, expected, "{code}");
+        }
+
+        let result = eval_code(&mut module, "load \"a \\\"b\"");
+        assert!(matches!(result, Err(CoreError::ParserError(_))));
+        let result = eval_code(&mut module, "mold :add");
+        assert!(matches!(result, Err(CoreError::NativeValue(name)) if name == "add"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_context_natives() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        let cases = [
+            ("'a", Value::word("a")),
+            ("a: 1 get 'a", Value::int(1)),
+            ("set 'b 2 b", Value::int(2)),
+            (
+                "o: context [x: 1] words-of o",
+                Value::block([Value::word("x")]),
+            ),
+            (
+                "o: context [x: 5] values-of o",
+                Value::block([Value::int(5)]),
+            ),
+            ("o: context [x: 1 y: 2] length? words-of o", Value::int(2)),
+            (
+                "o: context [x: 1] p: make o [y: 2] p/x + p/y",
+                Value::int(3),
+            ),
+            ("o: context [x: 1] p: make o [x: 10] o/x", Value::int(1)),
+            (
+                "o: context [x: 1 f: func [] [x]] p: make o [x: 2] add o/f p/f",
+                Value::int(3),
+            ),
+            ("o: context [x: 1] get in o 'x", Value::int(1)),
+            ("o: context [x: 1] in o 'y", Value::none()),
+            ("o: context [x: 1] set in o 'x 7 o/x", Value::int(7)),
+            ("o: context [x: 1] x: 100 do bind [x + 1] o", Value::int(2)),
+            (
+                "o: context [x: 1] x: 100 do bind [x: x + 5] o add x o/x",
+                Value::int(106),
+            ),
+            (
+                "o: context [x: 1] p: context [x: 2] code: bind [x] o do bind code p",
+                Value::int(2),
+            ),
+            // bound words stay words
+            (
+                "mold bind [x [y: x]] context [x: 1]",
+                Value::string("[x [y: x]]"),
+            ),
+            (
+                "o: context [x: 1 y: 2] code: bind [x + y] o x: 10 y: 20 do first load mold code",
+                Value::int(30),
+            ),
+            (
+                "o: context [x: 1] y: 5 f: func [b] [do b] f bind [x + y] o",
+                Value::int(6),
+            ),
+            (
+                "o: context [n: 1 inc: func [] [n: n + 1]] p: make o [] p/inc p/inc add o/n p/n",
+                Value::int(4),
+            ),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(eval_code(&mut module, code)?, expected, "{code}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_stdlib_image() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        let image = embedded_image().expect("stdlib image out of date");
+        module.load_image(image)?;
+        let result = eval_code(&mut module, "reform [1 add 1 1]")?;
+        assert_eq!(result, Value::String("1 2".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_stdlib_image_up_to_date() -> Result<(), CoreError> {
+        assert!(
+            stdlib_image()? == STDLIB_IMAGE,
+            "src/stdlib.img is out of date, run `cargo xtask stdlib-image`"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_stdlib_fallback() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        module.add_native_fn("extra", form::native, &[Param::eval("value", &[])])?;
+        let mut image = Vec::new();
+        module.save_image(&mut image)?;
+
+        // the image has a native this module lacks, so the stdlib is evaluated instead
+        let mut other = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        stdlib_from_image(&mut other, Some(image.as_slice()))?;
+        let result = eval_code(&mut other, "reform [1 2]")?;
+        assert_eq!(result, Value::String("1 2".into()));
+        assert!(eval_code(&mut other, "extra 1").is_err());
+        Ok(())
+    }
 }
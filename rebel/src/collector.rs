@@ -43,7 +43,8 @@ impl ValueCollector {
 
     fn push(&mut self, value: Value) -> Result<(), ValueCollectorError> {
         if let Some(current) = self.stack.last_mut() {
-            Ok(current.push(value))
+            current.push(value);
+            Ok(())
         } else {
             Err(ValueCollectorError::UnexpectedError)
         }
@@ -67,18 +68,45 @@ impl Collector for ValueCollector {
             WordKind::Word => Value::Word(symbol),
             WordKind::SetWord => Value::SetWord(symbol),
             WordKind::GetWord => Value::GetWord(symbol),
+            WordKind::LitWord => Value::LitWord(symbol),
+            WordKind::Refinement => Value::Refinement(symbol),
         })
     }
 
-    fn integer(&mut self, value: i32) -> Result<(), Self::Error> {
-        self.push(Value::Int(value))
+    fn integer(&mut self, value: i64) -> Result<(), Self::Error> {
+        self.push(Value::integer(value))
+    }
+
+    fn decimal(&mut self, value: f64) -> Result<(), Self::Error> {
+        self.push(Value::Decimal(value))
+    }
+
+    fn binary(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.push(Value::Binary(bytes.into()))
+    }
+
+    fn date(&mut self, millis: i64) -> Result<(), Self::Error> {
+        self.push(Value::Date(millis))
+    }
+
+    fn time(&mut self, millis: i64) -> Result<(), Self::Error> {
+        self.push(Value::Time(millis))
+    }
+
+    fn file(&mut self, path: &str) -> Result<(), Self::Error> {
+        self.push(Value::File(SmolStr::new(path)))
+    }
+
+    fn url(&mut self, url: &str) -> Result<(), Self::Error> {
+        self.push(Value::Url(SmolStr::new(url)))
     }
 
     fn begin_block(&mut self) -> Result<(), Self::Error> {
         if self.in_path {
             return Err(ValueCollectorError::InvalidPath);
         }
-        Ok(self.stack.push(Vec::new()))
+        self.stack.push(Vec::new());
+        Ok(())
     }
 
     fn end_block(&mut self) -> Result<(), Self::Error> {
@@ -92,15 +120,20 @@ impl Collector for ValueCollector {
     fn begin_path(&mut self) -> Result<(), Self::Error> {
         println!("begin path");
         self.in_path = true;
-        Ok(self.stack.push(Vec::new()))
+        self.stack.push(Vec::new());
+        Ok(())
     }
 
-    fn end_path(&mut self) -> Result<(), Self::Error> {
+    fn end_path(&mut self, kind: WordKind) -> Result<(), Self::Error> {
         println!("end path");
         self.in_path = false;
         // if self.stack.len() > 1 {
-        let block = self.pop_block()?;
-        self.push(Value::Path(block.into_boxed_slice()))?;
+        let block = self.pop_block()?.into_boxed_slice();
+        self.push(match kind {
+            WordKind::SetWord => Value::SetPath(block),
+            WordKind::GetWord => Value::GetPath(block),
+            _ => Value::Path(block),
+        })?;
         // }
         Ok(())
     }
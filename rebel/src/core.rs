@@ -1,10 +1,18 @@
 // RebelDB™ © 2025 Huly Labs • https://hulylabs.com • SPDX-License-Identifier: MIT
 
+use crate::blob::{BlobStore, Hash, MemoryBlobStore};
 use crate::boot::{core_package, stdlib_package};
-use crate::mem::{Context, Heap, MemoryError, Offset, Stack, Symbol, SymbolId, SymbolTable, Word};
+use crate::gc::{Copier, Kind};
+use crate::image::{Image, ImageError};
+use crate::mem::{
+    Context, Header, Heap, MemoryError, Offset, Stack, Storage, Symbol, SymbolId, SymbolTable, Word,
+};
 use crate::parse::{Collector, Parser, WordKind};
+use crate::url::{parse_url, url_component};
 use crate::value::Value;
 use smol_str::SmolStr;
+use std::collections::HashMap;
+use std::io::{Read, Write};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -25,8 +33,20 @@ pub enum CoreError {
     SymbolTableFull,
     #[error("bad arguments")]
     BadArguments,
+    #[error("{native} does not accept {received} as {param}")]
+    InvalidArgument {
+        native: SmolStr,
+        param: SmolStr,
+        received: &'static str,
+    },
     #[error("unknown tag")]
     UnknownTag,
+    #[error("math overflow")]
+    MathOverflow,
+    #[error("{0} is out of range for a 32-bit integer")]
+    OutOfRange(i64),
+    #[error("attempt to divide by zero")]
+    ZeroDivide,
     #[error(transparent)]
     ParserError(#[from] crate::parse::ParserError<MemoryError>),
     #[error(transparent)]
@@ -37,6 +57,62 @@ pub enum CoreError {
     IoError(#[from] std::io::Error),
     #[error(transparent)]
     AnyError(#[from] anyhow::Error),
+    #[error("no catch for throw")]
+    NoCatch,
+    #[error("no loop to break or continue")]
+    NoLoop,
+    #[error("operator is missing its {0} operand")]
+    MissingOperand(&'static str),
+    #[error("return outside of a function")]
+    NoFunction,
+    #[error("out of fuel")]
+    OutOfFuel,
+    #[error("value contains itself")]
+    CyclicValue,
+    #[error("native {0} has no source form")]
+    NativeValue(SmolStr),
+    #[error(transparent)]
+    ImageError(#[from] ImageError),
+    #[error("{message}")]
+    Raised {
+        kind: SmolStr,
+        message: String,
+        word: Option<SymbolId>,
+    },
+}
+
+impl CoreError {
+    /// Name of the error kind as seen by scripts, stored in the `kind` field of error values.
+    pub fn kind(&self) -> &str {
+        match self {
+            CoreError::InternalError => "internal",
+            CoreError::EndOfInput => "end-of-input",
+            CoreError::UnexpectedEndOfBlock => "unexpected-end-of-block",
+            CoreError::FunctionNotFound => "function-not-found",
+            CoreError::StringTooLong => "string-too-long",
+            CoreError::BoundsCheckFailed => "bounds-check-failed",
+            CoreError::SymbolTableFull => "symbol-table-full",
+            CoreError::BadArguments | CoreError::InvalidArgument { .. } => "bad-arguments",
+            CoreError::UnknownTag => "unknown-tag",
+            CoreError::MathOverflow => "math-overflow",
+            CoreError::OutOfRange(_) => "out-of-range",
+            CoreError::ZeroDivide => "zero-divide",
+            CoreError::ParserError(_) => "parse",
+            CoreError::MemoryError(err) => err.kind(),
+            CoreError::Utf8Error(_) => "utf8",
+            CoreError::IoError(_) => "io",
+            CoreError::AnyError(_) => "native",
+            CoreError::NoCatch => "no-catch",
+            CoreError::NoLoop => "no-loop",
+            CoreError::MissingOperand(_) => "missing-operand",
+            CoreError::NoFunction => "no-function",
+            CoreError::OutOfFuel => "out-of-fuel",
+            CoreError::CyclicValue => "cyclic-value",
+            CoreError::NativeValue(_) => "native-value",
+            CoreError::ImageError(_) => "image",
+            CoreError::Raised { kind, .. } => kind,
+        }
+    }
 }
 
 // V M  V A L U E
@@ -47,15 +123,27 @@ pub type MemValue = [Word; 2];
 pub enum VmValue {
     None,
     Int(i32),
+    Int64(Offset),
+    Decimal(Offset),
+    Date(Offset),
+    Time(Offset),
     Bool(bool),
     String(Offset),
+    Binary(Offset),
+    File(Offset),
+    Url(Offset),
     Block(Offset),
     Context(Offset),
     Path(Offset),
+    SetPath(Offset),
+    GetPath(Offset),
     Word(SymbolId),
     SetWord(SymbolId),
     GetWord(SymbolId),
+    LitWord(SymbolId),
+    Refinement(SymbolId),
     Func(Offset),
+    Error(Offset),
 }
 
 impl VmValue {
@@ -71,6 +159,18 @@ impl VmValue {
     pub const TAG_FUNC: Word = 9;
     pub const TAG_BOOL: Word = 10;
     pub const TAG_PATH: Word = 11;
+    pub const TAG_ERROR: Word = 12;
+    pub const TAG_REFINEMENT: Word = 13;
+    pub const TAG_SET_PATH: Word = 14;
+    pub const TAG_GET_PATH: Word = 15;
+    pub const TAG_LIT_WORD: Word = 16;
+    pub const TAG_DECIMAL: Word = 17;
+    pub const TAG_INT64: Word = 18;
+    pub const TAG_BINARY: Word = 19;
+    pub const TAG_DATE: Word = 20;
+    pub const TAG_TIME: Word = 21;
+    pub const TAG_FILE: Word = 22;
+    pub const TAG_URL: Word = 23;
 
     /// Convert a tag and data word into a VmValue
     ///
@@ -88,14 +188,27 @@ impl VmValue {
         match tag {
             Self::TAG_NONE => Ok(VmValue::None),
             Self::TAG_INT => Ok(VmValue::Int(data as i32)),
+            Self::TAG_DECIMAL => Ok(VmValue::Decimal(data)),
+            Self::TAG_INT64 => Ok(VmValue::Int64(data)),
+            Self::TAG_DATE => Ok(VmValue::Date(data)),
+            Self::TAG_TIME => Ok(VmValue::Time(data)),
             Self::TAG_BLOCK => Ok(VmValue::Block(data)),
             Self::TAG_CONTEXT => Ok(VmValue::Context(data)),
             Self::TAG_INLINE_STRING => Ok(VmValue::String(data)),
+            Self::TAG_BINARY => Ok(VmValue::Binary(data)),
+            Self::TAG_FILE => Ok(VmValue::File(data)),
+            Self::TAG_URL => Ok(VmValue::Url(data)),
             Self::TAG_WORD => Ok(VmValue::Word(data)),
             Self::TAG_SET_WORD => Ok(VmValue::SetWord(data)),
+            Self::TAG_GET_WORD => Ok(VmValue::GetWord(data)),
+            Self::TAG_LIT_WORD => Ok(VmValue::LitWord(data)),
             Self::TAG_FUNC => Ok(VmValue::Func(data)),
             Self::TAG_PATH => Ok(VmValue::Path(data)),
+            Self::TAG_SET_PATH => Ok(VmValue::SetPath(data)),
+            Self::TAG_GET_PATH => Ok(VmValue::GetPath(data)),
             Self::TAG_BOOL => Ok(VmValue::Bool(data != 0)),
+            Self::TAG_ERROR => Ok(VmValue::Error(data)),
+            Self::TAG_REFINEMENT => Ok(VmValue::Refinement(data)),
             _ => Err(CoreError::UnknownTag),
         }
     }
@@ -104,18 +217,75 @@ impl VmValue {
         match self {
             VmValue::None => [Self::TAG_NONE, 0],
             VmValue::Int(value) => [Self::TAG_INT, *value as u32],
+            VmValue::Decimal(offset) => [Self::TAG_DECIMAL, *offset],
+            VmValue::Int64(offset) => [Self::TAG_INT64, *offset],
+            VmValue::Date(offset) => [Self::TAG_DATE, *offset],
+            VmValue::Time(offset) => [Self::TAG_TIME, *offset],
             VmValue::Bool(value) => [Self::TAG_BOOL, if *value { 1 } else { 0 }],
             VmValue::String(offset) => [Self::TAG_INLINE_STRING, *offset],
+            VmValue::Binary(offset) => [Self::TAG_BINARY, *offset],
+            VmValue::File(offset) => [Self::TAG_FILE, *offset],
+            VmValue::Url(offset) => [Self::TAG_URL, *offset],
             VmValue::Word(symbol) => [Self::TAG_WORD, *symbol],
             VmValue::SetWord(symbol) => [Self::TAG_SET_WORD, *symbol],
             VmValue::GetWord(symbol) => [Self::TAG_GET_WORD, *symbol],
+            VmValue::LitWord(symbol) => [Self::TAG_LIT_WORD, *symbol],
+            VmValue::Refinement(symbol) => [Self::TAG_REFINEMENT, *symbol],
             VmValue::Block(offset) => [Self::TAG_BLOCK, *offset],
             VmValue::Context(offset) => [Self::TAG_CONTEXT, *offset],
             VmValue::Func(offset) => [Self::TAG_FUNC, *offset],
             VmValue::Path(offset) => [Self::TAG_PATH, *offset],
+            VmValue::SetPath(offset) => [Self::TAG_SET_PATH, *offset],
+            VmValue::GetPath(offset) => [Self::TAG_GET_PATH, *offset],
+            VmValue::Error(offset) => [Self::TAG_ERROR, *offset],
         }
     }
 
+    /// Datatype names, as used in function specs. Integers have two representations.
+    const TYPE_NAMES: [(Word, &'static str); 24] = [
+        (Self::TAG_NONE, "none!"),
+        (Self::TAG_INT, "integer!"),
+        (Self::TAG_INT64, "integer!"),
+        (Self::TAG_DECIMAL, "decimal!"),
+        (Self::TAG_DATE, "date!"),
+        (Self::TAG_TIME, "time!"),
+        (Self::TAG_BLOCK, "block!"),
+        (Self::TAG_CONTEXT, "object!"),
+        (Self::TAG_NATIVE_FN, "native!"),
+        (Self::TAG_INLINE_STRING, "string!"),
+        (Self::TAG_BINARY, "binary!"),
+        (Self::TAG_FILE, "file!"),
+        (Self::TAG_URL, "url!"),
+        (Self::TAG_WORD, "word!"),
+        (Self::TAG_SET_WORD, "set-word!"),
+        (Self::TAG_GET_WORD, "get-word!"),
+        (Self::TAG_LIT_WORD, "lit-word!"),
+        (Self::TAG_FUNC, "function!"),
+        (Self::TAG_BOOL, "logic!"),
+        (Self::TAG_PATH, "path!"),
+        (Self::TAG_SET_PATH, "set-path!"),
+        (Self::TAG_GET_PATH, "get-path!"),
+        (Self::TAG_ERROR, "error!"),
+        (Self::TAG_REFINEMENT, "refinement!"),
+    ];
+
+    /// Name of the datatype of values with the given tag, e.g. `integer!`.
+    pub fn type_name(tag: Word) -> &'static str {
+        Self::TYPE_NAMES
+            .iter()
+            .find(|(t, _)| *t == tag)
+            .map_or("unknown!", |(_, name)| name)
+    }
+
+    /// Mask of the tags of the datatype with the given name.
+    pub fn type_mask(name: &str) -> Option<Word> {
+        Self::TYPE_NAMES
+            .iter()
+            .filter(|(_, n)| *n == name)
+            .map(|(tag, _)| 1 << tag)
+            .reduce(|mask, bit| mask | bit)
+    }
+
     pub fn is_none(&self) -> bool {
         matches!(self, VmValue::None)
     }
@@ -135,13 +305,22 @@ impl VmValue {
     pub fn is_context(&self) -> bool {
         matches!(self, VmValue::Context(_))
     }
+
+    /// Conditions fail on `none` and `false`, any other value passes.
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, VmValue::None | VmValue::Bool(false))
+    }
+
+    pub fn is_error(&self) -> bool {
+        matches!(self, VmValue::Error(_))
+    }
 }
 
 // Implement TryFrom for VmValue to allow more ergonomic conversion from [Word; 2]
 impl TryFrom<[Word; 2]> for VmValue {
     type Error = CoreError;
 
-    fn try_from(value: [Word; 2]) -> Result<Self, Self::Error> {
+    fn try_from(value: [Word; 2]) -> Result<Self, CoreError> {
         let [tag, data] = value;
         VmValue::from_tag_data(tag, data)
     }
@@ -156,23 +335,88 @@ impl From<VmValue> for [Word; 2] {
 
 // M O D U L E
 
-type NativeFn<T> = fn(module: &mut Exec<T>) -> Result<(), CoreError>;
+pub type NativeFn<T> = fn(module: &mut Exec<T>) -> Result<(), CoreError>;
+
+/// How a native function takes one of its arguments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind {
+    /// Value of the next expression.
+    Eval,
+    /// Next value as written, e.g. a word or a block, without evaluating it.
+    Literal,
+    /// Next value, looked up if it is a word but never called.
+    Get,
+}
+
+/// Parameter of a native function: how the argument is taken and the tags it accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Param {
+    pub name: &'static str,
+    pub kind: ArgKind,
+    /// Accepted tags, any value if empty.
+    pub types: &'static [Word],
+}
+
+impl Param {
+    pub const fn eval(name: &'static str, types: &'static [Word]) -> Self {
+        Self {
+            name,
+            kind: ArgKind::Eval,
+            types,
+        }
+    }
+
+    pub const fn literal(name: &'static str, types: &'static [Word]) -> Self {
+        Self {
+            name,
+            kind: ArgKind::Literal,
+            types,
+        }
+    }
+
+    pub const fn get(name: &'static str, types: &'static [Word]) -> Self {
+        Self {
+            name,
+            kind: ArgKind::Get,
+            types,
+        }
+    }
+}
 
 struct FuncDesc<T> {
     func: NativeFn<T>,
     arity: u32,
+    params: Box<[Param]>,
+    symbol: SymbolId,
 }
 
 pub struct Module<T> {
     heap: Heap<T>,
     system_words: Offset,
     functions: Vec<FuncDesc<T>>,
+    /// Infix operator words and the natives they call.
+    operators: Vec<(SymbolId, Word)>,
+    gc_threshold: usize,
+    /// Where binaries too large to keep in the heap go.
+    blobs: Box<dyn BlobStore>,
+    /// Values pinned by the host, as tag and data pairs. Free slots hold none.
+    pinned: Vec<Word>,
 }
 
+/// Handle to a value pinned with [`Module::pin`]. Pinned values survive collections,
+/// which update them as blocks move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pin(usize);
+
 impl<T> Module<T> {
     // const NULL: Offset = 0;
     const SYMBOLS: Offset = 1;
-    // const CONTEXT: Offset = 2;
+    const CONTEXT: Offset = 2;
+    const HEADER_SIZE: usize = 3;
+    /// Flag on the length of a binary kept in the blob store.
+    const BLOB: Word = 0x8000_0000;
+    /// Binaries longer than this many bytes are kept in the blob store.
+    pub const BLOB_THRESHOLD: usize = 1024;
 
     fn get_func(&self, index: u32) -> Result<&FuncDesc<T>, CoreError> {
         self.functions
@@ -180,31 +424,54 @@ impl<T> Module<T> {
             .ok_or(CoreError::FunctionNotFound)
     }
 
-    pub fn new_process(&mut self, block: VmValue) -> Result<Exec<T>, CoreError> {
+    pub fn new_process(&mut self, block: VmValue) -> Result<Exec<'_, T>, CoreError> {
+        self.new_process_with_limits(block, Limits::default())
+    }
+
+    pub fn new_process_with_limits(
+        &mut self,
+        block: VmValue,
+        limits: Limits,
+    ) -> Result<Exec<'_, T>, CoreError> {
         let block = match block {
             VmValue::Block(offset) => offset,
             _ => return Err(CoreError::BadArguments),
         };
-        Exec::new(self, block).map_err(Into::into)
+        let mut exec = Exec::new(self, block)?;
+        exec.fuel = limits.fuel;
+        Ok(exec)
     }
 }
 
 impl<T> Module<T>
 where
-    T: AsRef<[Word]> + AsMut<[Word]>,
+    T: Storage,
 {
     pub fn init(data: T) -> Result<Self, CoreError> {
+        let mut module = Self::init_core(data)?;
+        stdlib_package(&mut module)?;
+        Ok(module)
+    }
+
+    /// Module with the core natives but without the standard library.
+    pub(crate) fn init_core(data: T) -> Result<Self, CoreError> {
         let mut heap = Heap::new(data);
         heap.init(3)?;
 
         let system_words = heap.alloc_context(1024)?;
 
+        let gc_threshold = Self::next_gc_threshold(&heap)?;
         let mut module = Self {
             heap,
             system_words,
             functions: Vec::new(),
+            operators: Vec::new(),
+            gc_threshold,
+            blobs: Box::new(MemoryBlobStore::new()),
+            pinned: Vec::new(),
         };
 
+        // grows as symbols are added, see `insert_symbol`
         let (symbols_addr, symbols_data) = module.heap.alloc_empty_block(1024)?;
         SymbolTable::new(symbols_data).init()?;
 
@@ -212,23 +479,26 @@ where
             .heap
             .put(0, [0xdeadbeef, symbols_addr, system_words])?;
         core_package(&mut module)?;
-        stdlib_package(&mut module)?;
         Ok(module)
     }
 
+    /// Register a native function taking arguments as described by `params`. The VM
+    /// collects and type-checks them, the function finds them on top of the stack.
     pub fn add_native_fn(
         &mut self,
         name: &str,
         func: NativeFn<T>,
-        arity: u32,
+        params: &[Param],
     ) -> Result<(), MemoryError> {
         let index = self.functions.len() as u32;
+        let symbol = Symbol::from(name)?;
+        let id = self.insert_symbol(symbol)?;
         self.functions.push(FuncDesc {
             func,
-            arity: arity * 2,
+            arity: params.len() as u32 * 2,
+            params: params.into(),
+            symbol: id,
         });
-        let symbol = Symbol::from(name)?;
-        let id = self.get_symbols_mut()?.get_or_insert(symbol)?;
         let mut words = self
             .heap
             .get_block_mut(self.system_words)
@@ -236,9 +506,267 @@ where
         words.put(id, [VmValue::TAG_NATIVE_FN, index])
     }
 
+    /// Make `op` an infix operator calling the native `native`, which takes two arguments:
+    /// the value on the left of the operator and the one on its right.
+    pub fn add_operator(&mut self, op: &str, native: &str) -> Result<(), CoreError> {
+        let symbol = self.get_or_insert_symbol(native)?;
+        let index = self
+            .functions
+            .iter()
+            .position(|desc| desc.symbol == symbol)
+            .ok_or(CoreError::FunctionNotFound)?;
+        if self.functions[index].arity != 4 {
+            return Err(CoreError::BadArguments);
+        }
+        let op = self.get_or_insert_symbol(op)?;
+        self.operators.push((op, index as Word));
+        Ok(())
+    }
+
+    /// Evaluate `block`. The process may collect garbage, invalidating values held by the
+    /// host unless they are pinned, see [`Module::pin`].
     pub fn eval(&mut self, block: VmValue) -> Result<VmValue, CoreError> {
         self.new_process(block).and_then(|mut exec| exec.eval())
     }
+
+    /// Collect garbage, keeping only what is reachable from the system words.
+    ///
+    /// Collection moves heap blocks, so any `VmValue` or `Offset` obtained before the call
+    /// is invalid afterwards. Processes collect on their own as the heap fills up; see
+    /// [`Exec::collect`].
+    pub fn collect(&mut self) -> Result<(), CoreError> {
+        self.collect_with(|_| Ok(()))
+    }
+
+    /// Copy live blocks into a fresh heap. `trace` must relocate any additional roots.
+    fn collect_with<F>(&mut self, trace: F) -> Result<(), CoreError>
+    where
+        F: FnOnce(&mut Copier) -> Result<(), MemoryError>,
+    {
+        let pinned = &mut self.pinned;
+        let words = Self::compact(self.heap.allocated()?, |copier| {
+            copier.values(pinned)?;
+            trace(copier)
+        })?;
+        self.install(&words)
+    }
+
+    /// Keep `value` alive across collections. Offsets held by the host are invalid once
+    /// a collection moves blocks, including collections during [`Module::eval`], so values
+    /// needed after evaluation must be pinned and read back with [`Module::pinned`].
+    pub fn pin(&mut self, value: VmValue) -> Pin {
+        let repr = value.vm_repr();
+        match self
+            .pinned
+            .chunks_exact(2)
+            .position(|slot| slot[0] == VmValue::TAG_NONE)
+        {
+            Some(index) => {
+                self.pinned[index * 2..index * 2 + 2].copy_from_slice(&repr);
+                Pin(index)
+            }
+            None => {
+                self.pinned.extend_from_slice(&repr);
+                Pin(self.pinned.len() / 2 - 1)
+            }
+        }
+    }
+
+    /// Current value of a pinned value, with offsets updated by any collection since.
+    pub fn pinned(&self, pin: Pin) -> Result<VmValue, CoreError> {
+        let slot = self
+            .pinned
+            .get(pin.0 * 2..pin.0 * 2 + 2)
+            .ok_or(CoreError::BadArguments)?;
+        VmValue::from_tag_data(slot[0], slot[1])
+    }
+
+    /// Release a pinned value, letting collections reclaim it.
+    pub fn unpin(&mut self, pin: Pin) {
+        if let Some(slot) = self.pinned.get_mut(pin.0 * 2..pin.0 * 2 + 2) {
+            slot.fill(0);
+        }
+    }
+
+    /// Copy the blocks of `from` reachable from its header, and from whatever `trace` adds.
+    fn compact<F>(from: &[Word], trace: F) -> Result<Vec<Word>, MemoryError>
+    where
+        F: FnOnce(&mut Copier) -> Result<(), MemoryError>,
+    {
+        let [_, symbols, system_words] = from
+            .get(..Self::HEADER_SIZE)
+            .ok_or(MemoryError::OutOfBounds)?
+            .try_into()?;
+        let mut copier = Copier::new(from, Self::HEADER_SIZE)?;
+        let symbols = copier.copy(symbols, Kind::Opaque)?;
+        let system_words = copier.copy(system_words, Kind::Context)?;
+        trace(&mut copier)?;
+        let mut words = copier.finish()?;
+        words[Self::SYMBOLS as usize] = symbols;
+        words[Self::CONTEXT as usize] = system_words;
+        Ok(words)
+    }
+
+    /// Replace the heap with compacted `words`.
+    fn install(&mut self, words: &[Word]) -> Result<(), CoreError> {
+        self.heap.load(words)?;
+        self.system_words = words[Self::CONTEXT as usize];
+        self.gc_threshold = Self::next_gc_threshold(&self.heap)?;
+        Ok(())
+    }
+
+    /// Write the heap and the native function table to an image, see [`crate::image`].
+    ///
+    /// Only what is reachable from the system words is saved, together with the blobs of
+    /// the large binaries among it.
+    pub fn save_image<W: Write>(&self, writer: W) -> Result<(), CoreError> {
+        let mut natives = Vec::with_capacity(self.functions.len());
+        for desc in &self.functions {
+            natives.push((self.get_symbol(desc.symbol)?, desc.arity));
+        }
+        let mut binaries = Vec::new();
+        let heap = Self::compact(self.heap.allocated()?, |copier| {
+            copier.trace()?;
+            binaries = copier.binaries().to_vec();
+            Ok(())
+        })?;
+        let mut hashes = Vec::new();
+        let mut blobs = Vec::new();
+        for binary in binaries {
+            let start = binary as usize + 1;
+            let len = heap.get(binary as usize).ok_or(MemoryError::OutOfBounds)?;
+            let block = heap
+                .get(start..start + Header::len(*len))
+                .ok_or(MemoryError::OutOfBounds)?;
+            if let Some(hash) = Self::blob_hash(block)? {
+                if !hashes.contains(&hash) {
+                    blobs.push(self.blobs.get(&hash)?.to_vec());
+                    hashes.push(hash);
+                }
+            }
+        }
+        Image {
+            natives,
+            blobs,
+            heap,
+        }
+        .write(writer)
+        .map_err(Into::into)
+    }
+
+    /// Replace the heap with an image written by [`Module::save_image`].
+    ///
+    /// Natives referenced by the image are re-bound by name to the natives of this module,
+    /// which must all be present with the same arity. Natives the image does not know about
+    /// are added to the system words, unless their name is bound there already. Nothing
+    /// changes if loading fails.
+    pub fn load_image<R: Read>(&mut self, reader: R) -> Result<(), CoreError> {
+        let image = Image::read(reader)?;
+
+        let mut names = Vec::with_capacity(self.functions.len());
+        for desc in &self.functions {
+            names.push(self.get_symbol(desc.symbol)?);
+        }
+        let mut operators = Vec::with_capacity(self.operators.len());
+        for (op, _) in &self.operators {
+            operators.push(self.get_symbol(*op)?);
+        }
+        let mut natives = Vec::with_capacity(image.natives.len());
+        for (name, arity) in image.natives {
+            let index = names
+                .iter()
+                .position(|native| *native == name)
+                .ok_or_else(|| ImageError::NativeNotFound(name.clone()))?;
+            if self.functions[index].arity != arity {
+                return Err(ImageError::NativeMismatch(name).into());
+            }
+            natives.push(index as Word);
+        }
+
+        if image.heap.first() != Some(&0xdeadbeef) {
+            return Err(ImageError::BadMagic.into());
+        }
+        for blob in &image.blobs {
+            self.blobs.put(blob)?;
+        }
+        let words = Self::compact(&image.heap, |copier| {
+            copier.rebind_natives(natives);
+            Ok(())
+        })?;
+        self.install(&words)?;
+        // pinned values pointed into the replaced heap
+        self.pinned.fill(0);
+
+        for (index, name) in names.iter().enumerate() {
+            let symbol = self.get_or_insert_symbol(name)?;
+            self.functions[index].symbol = symbol;
+            let mut words = self
+                .heap
+                .get_block_mut(self.system_words)
+                .map(Context::new)?;
+            if let Err(MemoryError::WordNotFound) = words.get(symbol) {
+                words.put(symbol, [VmValue::TAG_NATIVE_FN, index as Word])?;
+            }
+        }
+        for (index, name) in operators.iter().enumerate() {
+            self.operators[index].0 = self.get_or_insert_symbol(name)?;
+        }
+        Ok(())
+    }
+
+    /// Keep large binaries in `store`. Binaries already in the previous store are not
+    /// moved, so set the store before making any. Images refer to blobs by hash only.
+    pub fn set_blob_store(&mut self, store: Box<dyn BlobStore>) {
+        self.blobs = store;
+    }
+
+    /// Words of a binary block. Short binaries are packed like strings, longer ones go to
+    /// the blob store and the block has their length, flagged with `BLOB`, then the hash.
+    fn pack_binary(&mut self, bytes: &[u8]) -> Result<Vec<Word>, MemoryError> {
+        if bytes.len() <= Self::BLOB_THRESHOLD {
+            return Ok(Self::pack_bytes(bytes));
+        }
+        let len = Word::try_from(bytes.len())
+            .ok()
+            .filter(|len| len & Self::BLOB == 0)
+            .ok_or(MemoryError::OutOfMemory)?;
+        let hash = self.blobs.put(bytes)?;
+        let mut words = vec![len | Self::BLOB];
+        for chunk in hash.chunks_exact(4) {
+            words.push(Word::from_le_bytes(chunk.try_into()?));
+        }
+        Ok(words)
+    }
+
+    /// Words of a string or short binary: the length in bytes, then the bytes packed four
+    /// per word.
+    fn pack_bytes(bytes: &[u8]) -> Vec<Word> {
+        let word_count = bytes.len().div_ceil(4);
+        let mut words = Vec::with_capacity(word_count + 1);
+        words.push(bytes.len() as u32);
+        let mut current_word = 0u32;
+        for (i, &byte) in bytes.iter().enumerate() {
+            let shift = (i % 4) * 8;
+            current_word |= (byte as u32) << shift;
+
+            // If we've filled a word (or reached the end), add it to the vector
+            if (i + 1) % 4 == 0 || i == bytes.len() - 1 {
+                words.push(current_word);
+                current_word = 0;
+            }
+        }
+        words
+    }
+
+    /// Next collection happens once half of the currently free space is used up.
+    fn next_gc_threshold(heap: &Heap<T>) -> Result<usize, MemoryError> {
+        let live = heap.len()? as usize;
+        Ok(live + heap.capacity().saturating_sub(live) / 2)
+    }
+
+    fn needs_gc(&self) -> Result<bool, MemoryError> {
+        self.heap.len().map(|len| len as usize > self.gc_threshold)
+    }
 }
 
 impl<T> Module<T>
@@ -264,11 +792,54 @@ where
             })
             .and_then(|value| value.try_into().map_err(Into::into))
     }
+
+    /// Bits of a boxed `int64` or decimal.
+    fn get_bits(&self, offset: Offset) -> Result<u64, MemoryError> {
+        let [low, high] = self.get_block::<2>(offset, 0)?;
+        Ok((high as u64) << 32 | low as u64)
+    }
+
+    /// Bytes of a block packed by [`Module::pack_bytes`].
+    fn unpack_bytes(words: &[Word]) -> Vec<u8> {
+        let len = words.first().map_or(0, |len| *len as usize);
+        words
+            .iter()
+            .skip(1)
+            .flat_map(|word| word.to_le_bytes())
+            .take(len)
+            .collect()
+    }
+
+    /// Text of a string, file or url.
+    fn get_text(&self, offset: Offset) -> Result<SmolStr, CoreError> {
+        let bytes = Self::unpack_bytes(self.heap.get_block(offset)?);
+        Ok(String::from_utf8(bytes)?.into())
+    }
+
+    /// Bytes of a binary, from the blob store if it is kept there.
+    fn get_binary(&self, offset: Offset) -> Result<Vec<u8>, MemoryError> {
+        let block = self.heap.get_block(offset)?;
+        match Self::blob_hash(block)? {
+            Some(hash) => self.blobs.get(&hash).map(<[u8]>::to_vec),
+            None => Ok(Self::unpack_bytes(block)),
+        }
+    }
+
+    /// Hash of the blob a binary block refers to, `None` if the bytes are in the block.
+    fn blob_hash(block: &[Word]) -> Result<Option<Hash>, MemoryError> {
+        match block.split_first() {
+            Some((len, hash)) if len & Self::BLOB != 0 => {
+                let hash: Vec<u8> = hash.iter().flat_map(|word| word.to_le_bytes()).collect();
+                Ok(Some(hash.as_slice().try_into()?))
+            }
+            _ => Ok(None),
+        }
+    }
 }
 
 impl<T> Module<T>
 where
-    T: AsMut<[Word]>,
+    T: Storage,
 {
     fn get_symbols_mut(&mut self) -> Result<SymbolTable<&mut [Word]>, MemoryError> {
         let addr = self.heap.get_mut::<1>(Self::SYMBOLS).map(|[addr]| *addr)?;
@@ -283,67 +854,238 @@ where
     }
 
     pub fn alloc_string(&mut self, string: &str) -> Result<Offset, MemoryError> {
-        let bytes = string.as_bytes();
-        let word_count = (bytes.len() + 3) / 4; // ceiling division
-        let mut words = Vec::with_capacity(word_count + 1);
-        words.push(bytes.len() as u32);
-        let mut current_word = 0u32;
-        for (i, &byte) in bytes.iter().enumerate() {
-            let shift = (i % 4) * 8;
-            current_word |= (byte as u32) << shift;
+        self.heap.alloc_block(&Self::pack_bytes(string.as_bytes()))
+    }
 
-            // If we've filled a word (or reached the end), add it to the vector
-            if (i + 1) % 4 == 0 || i == bytes.len() - 1 {
-                words.push(current_word);
-                current_word = 0;
-            }
-        }
+    pub fn alloc_binary(&mut self, bytes: &[u8]) -> Result<Offset, MemoryError> {
+        let words = self.pack_binary(bytes)?;
         self.heap.alloc_block(&words)
     }
 
+    /// Decimals are boxed, the bits of the `f64` take the two words of a block.
+    pub fn alloc_decimal(&mut self, value: f64) -> Result<Offset, MemoryError> {
+        self.alloc_bits(value.to_bits())
+    }
+
+    /// Integers that don't fit in a word are boxed like decimals.
+    pub fn alloc_int64(&mut self, value: i64) -> Result<Offset, MemoryError> {
+        self.alloc_bits(value as u64)
+    }
+
+    /// Dates and times are boxed like decimals, see [`crate::date`].
+    pub fn alloc_date(&mut self, millis: i64) -> Result<Offset, MemoryError> {
+        self.alloc_bits(millis as u64)
+    }
+
+    pub fn alloc_time(&mut self, millis: i64) -> Result<Offset, MemoryError> {
+        self.alloc_bits(millis as u64)
+    }
+
+    fn alloc_bits(&mut self, bits: u64) -> Result<Offset, MemoryError> {
+        self.heap.alloc_block(&[bits as Word, (bits >> 32) as Word])
+    }
+
     pub fn get_or_insert_symbol(&mut self, symbol: &str) -> Result<Offset, MemoryError> {
-        self.get_symbols_mut()?.get_or_insert(Symbol::from(symbol)?)
+        self.insert_symbol(Symbol::from(symbol)?)
+    }
+
+    /// Id of `symbol`, adding it to the symbol table. A full table is replaced by one
+    /// twice as large, and symbols keep their ids.
+    fn insert_symbol(&mut self, symbol: Symbol) -> Result<SymbolId, MemoryError> {
+        match self.get_symbols_mut()?.get_or_insert(symbol) {
+            Err(MemoryError::SymbolTableFull) => {
+                self.grow_symbols()?;
+                self.get_symbols_mut()?.get_or_insert(symbol)
+            }
+            result => result,
+        }
     }
 
-    fn alloc_block(&mut self, values: &[Value]) -> Result<Offset, MemoryError> {
+    fn grow_symbols(&mut self) -> Result<(), MemoryError> {
+        let [addr] = self.heap.get::<1>(Self::SYMBOLS)?;
+        let block = self.heap.get_block(addr)?;
+        let size = block.len() as Offset * 2;
+        let table = SymbolTable::new(block);
+        let symbols = (1..=table.count()?)
+            .map(|id| table.get(id))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (new_addr, data) = self.heap.alloc_empty_block(size)?;
+        let mut table = SymbolTable::new(data);
+        table.init()?;
+        // inserted in id order, so every symbol gets its old id back
+        for symbol in symbols {
+            table.get_or_insert(symbol)?;
+        }
+        self.heap.put(Self::SYMBOLS, [new_addr])
+    }
+
+    fn alloc_block(&mut self, values: &[Value]) -> Result<Offset, CoreError> {
         let mut vm_values = Vec::with_capacity(values.len() * 2);
         for item in values.iter() {
             let mem = self.alloc_value(item)?.vm_repr();
             vm_values.push(mem[0]);
             vm_values.push(mem[1]);
         }
-        self.heap.alloc_block(&vm_values)
+        self.heap.alloc_block(&vm_values).map_err(Into::into)
     }
 
-    pub fn alloc_value(&mut self, value: &Value) -> Result<VmValue, MemoryError> {
-        match value {
+    pub fn alloc_value(&mut self, value: &Value) -> Result<VmValue, CoreError> {
+        let vm_value = match value {
             Value::None => Ok(VmValue::None),
             Value::Int(n) => Ok(VmValue::Int(*n)),
+            Value::Int64(n) => self.alloc_int64(*n).map(VmValue::Int64),
+            Value::Decimal(n) => self.alloc_decimal(*n).map(VmValue::Decimal),
+            Value::Date(millis) => self.alloc_date(*millis).map(VmValue::Date),
+            Value::Time(millis) => self.alloc_time(*millis).map(VmValue::Time),
             Value::Bool(b) => Ok(VmValue::Bool(*b)),
 
             Value::String(s) => self.alloc_string(s.as_ref()).map(VmValue::String),
+            Value::Binary(bytes) => self.alloc_binary(bytes).map(VmValue::Binary),
+            Value::File(path) => self.alloc_string(path.as_ref()).map(VmValue::File),
+            Value::Url(url) => self.alloc_string(url.as_ref()).map(VmValue::Url),
 
             Value::Word(w) => self.get_or_insert_symbol(w.as_ref()).map(VmValue::Word),
             Value::SetWord(w) => self.get_or_insert_symbol(w.as_ref()).map(VmValue::SetWord),
             Value::GetWord(w) => self.get_or_insert_symbol(w.as_ref()).map(VmValue::GetWord),
-            Value::Block(items) => self.alloc_block(items).map(VmValue::Block),
-            Value::Path(items) => self.alloc_block(items).map(VmValue::Path),
-
-            Value::Context(pairs) => {
-                let context = self.heap.alloc_context(pairs.len() as u32)?;
-
-                for (key, val) in pairs.iter() {
-                    let symbol = self.get_or_insert_symbol(key)?;
-                    let vm_value = self.alloc_value(val)?;
-                    self.heap
-                        .get_block_mut(context)
-                        .map(Context::new)
-                        .and_then(|mut ctx| ctx.put(symbol, vm_value.vm_repr()))?;
+            Value::LitWord(w) => self.get_or_insert_symbol(w.as_ref()).map(VmValue::LitWord),
+            Value::Refinement(w) => self
+                .get_or_insert_symbol(w.as_ref())
+                .map(VmValue::Refinement),
+            Value::Block(items) => return self.alloc_block(items).map(VmValue::Block),
+            Value::Path(items) => return self.alloc_block(items).map(VmValue::Path),
+            Value::SetPath(items) => return self.alloc_block(items).map(VmValue::SetPath),
+            Value::GetPath(items) => return self.alloc_block(items).map(VmValue::GetPath),
+
+            Value::Context(pairs) => return self.alloc_pairs(pairs).map(VmValue::Context),
+            Value::Error(pairs) => return self.alloc_pairs(pairs).map(VmValue::Error),
+
+            Value::Func(spec, body) => {
+                let spec = self.alloc_block(spec)?;
+                let body = self.alloc_block(body)?;
+                // loaded functions see the system words only
+                let scope = self.heap.alloc_block(&[
+                    VmValue::TAG_CONTEXT,
+                    self.system_words,
+                    VmValue::TAG_NONE,
+                    0,
+                ])?;
+                return self.alloc_func(spec, body, scope).map(VmValue::Func);
+            }
+        };
+        vm_value.map_err(Into::into)
+    }
+
+    fn alloc_pairs(&mut self, pairs: &[(SmolStr, Value)]) -> Result<Offset, CoreError> {
+        let context = self.heap.alloc_context(pairs.len() as u32)?;
+
+        for (key, val) in pairs.iter() {
+            let symbol = self.get_or_insert_symbol(key)?;
+            let vm_value = self.alloc_value(val)?;
+            self.heap
+                .get_block_mut(context)
+                .map(Context::new)
+                .and_then(|mut ctx| ctx.put(symbol, vm_value.vm_repr()))?;
+        }
+
+        Ok(context)
+    }
+
+    /// Allocate a function for `spec` and `body` that runs in `scope`, see [`Exec::alloc_func`].
+    pub fn alloc_func(
+        &mut self,
+        spec: Offset,
+        body: Offset,
+        scope: Offset,
+    ) -> Result<Offset, CoreError> {
+        let mut params = Vec::new();
+        let mut arity = 0;
+        let mut refinement = None;
+        let mut refinements = 0;
+        let mut offset = 0;
+        while let Ok([tag, data]) = self.get_block::<2>(spec, offset) {
+            offset += 2;
+            match tag {
+                VmValue::TAG_WORD => {
+                    params.extend([VmValue::TAG_WORD, data, VmValue::TAG_INT, 0]);
+                    match refinement {
+                        Some(args) => params[args] += 2,
+                        None => arity += 2,
+                    }
                 }
+                VmValue::TAG_BLOCK => match params.len().checked_sub(4) {
+                    Some(last) if params[last] == VmValue::TAG_WORD && params[last + 3] == 0 => {
+                        params[last + 3] = self.typeset(data)?;
+                    }
+                    _ => return Err(CoreError::BadArguments),
+                },
+                VmValue::TAG_REFINEMENT if refinements < Word::BITS => {
+                    refinement = Some(params.len() + 3);
+                    refinements += 1;
+                    params.extend([VmValue::TAG_REFINEMENT, data, VmValue::TAG_INT, 0]);
+                }
+                VmValue::TAG_INLINE_STRING => {}
+                _ => return Err(CoreError::BadArguments),
+            }
+        }
+
+        let params = self.heap.alloc_block(&params)?;
+        self.heap
+            .alloc_block(&[
+                VmValue::TAG_INT,
+                arity,
+                VmValue::TAG_BLOCK,
+                params,
+                VmValue::TAG_BLOCK,
+                body,
+                VmValue::TAG_BLOCK,
+                scope,
+                VmValue::TAG_INT,
+                0,
+            ])
+            .map_err(Into::into)
+    }
 
-                Ok(VmValue::Context(context))
+    /// Mask of the tags of the datatypes named in `types`.
+    fn typeset(&self, types: Offset) -> Result<Word, CoreError> {
+        let mut typeset = 0;
+        let mut offset = 0;
+        while let Ok([tag, symbol]) = self.get_block::<2>(types, offset) {
+            offset += 2;
+            if tag != VmValue::TAG_WORD {
+                return Err(CoreError::BadArguments);
             }
+            let name = self.get_symbol(symbol)?;
+            typeset |= match VmValue::type_mask(&name) {
+                Some(mask) => mask,
+                None if name == "any-type!" => Word::MAX,
+                None => return Err(CoreError::BadArguments),
+            };
+        }
+        Ok(typeset)
+    }
+
+    /// Allocate an error value: a context with the `kind`, `message` and `word` fields.
+    pub fn alloc_error(
+        &mut self,
+        kind: &str,
+        message: &str,
+        word: Option<SymbolId>,
+    ) -> Result<Offset, MemoryError> {
+        let kind = [VmValue::TAG_WORD, self.get_or_insert_symbol(kind)?];
+        let message = [VmValue::TAG_INLINE_STRING, self.alloc_string(message)?];
+        let word = word.map_or([VmValue::TAG_NONE, 0], |word| [VmValue::TAG_WORD, word]);
+
+        let fields = [("kind", kind), ("message", message), ("word", word)];
+        let error = self.heap.alloc_context(fields.len() as u32)?;
+        for (key, value) in fields {
+            let symbol = self.get_or_insert_symbol(key)?;
+            self.heap
+                .get_block_mut(error)
+                .map(Context::new)
+                .and_then(|mut ctx| ctx.put(symbol, value))?;
         }
+        Ok(error)
     }
 }
 
@@ -358,109 +1100,147 @@ where
         Ok(inlined.to_string())
     }
 
-    fn get_block_value(&self, offset: Offset) -> Result<Box<[Value]>, CoreError> {
+    /// Enter the block or context at `offset` while converting it, `outer` holds the ones
+    /// being converted around it. A value that contains itself has no finite conversion.
+    fn enter(outer: &mut Vec<Offset>, offset: Offset) -> Result<(), CoreError> {
+        if outer.contains(&offset) {
+            return Err(CoreError::CyclicValue);
+        }
+        outer.push(offset);
+        Ok(())
+    }
+
+    fn get_block_value(
+        &self,
+        offset: Offset,
+        outer: &mut Vec<Offset>,
+    ) -> Result<Box<[Value]>, CoreError> {
+        Self::enter(outer, offset)?;
         let block_data = self.heap.get_block(offset)?;
         let mut values = Vec::new();
 
         for pair in block_data.chunks_exact(2) {
-            let vm_value = VmValue::from_tag_data(pair[0], pair[1])?;
-            values.push(self.to_value(vm_value)?);
+            values.push(self.mem_value_within([pair[0], pair[1]], outer)?);
         }
 
+        outer.pop();
         Ok(values.into_boxed_slice())
     }
 
-    pub fn to_value(&self, vm_value: VmValue) -> Result<Value, CoreError> {
-        match vm_value {
-            VmValue::None => Ok(Value::None),
-            VmValue::Int(n) => Ok(Value::Int(n)),
-            VmValue::Bool(b) => Ok(Value::Bool(b)),
-            VmValue::Word(symbol) => Ok(Value::Word(self.get_symbol(symbol)?)),
-            VmValue::SetWord(symbol) => Ok(Value::SetWord(self.get_symbol(symbol)?)),
-            VmValue::GetWord(symbol) => Ok(Value::GetWord(self.get_symbol(symbol)?)),
-
-            VmValue::String(offset) => {
-                let string_block = self.heap.get_block(offset)?;
-                if string_block.is_empty() {
-                    return Ok(Value::String("".into()));
-                }
-
-                // First word is the length
-                let length = string_block[0] as usize;
+    fn get_context_pairs(
+        &self,
+        offset: Offset,
+        outer: &mut Vec<Offset>,
+    ) -> Result<Box<[(SmolStr, Value)]>, CoreError> {
+        let context_block = self.heap.get_block(offset)?;
+        if context_block.is_empty() {
+            return Ok(Box::new([]));
+        }
+        Self::enter(outer, offset)?;
 
-                // Convert the block data to bytes safely
-                let mut bytes = Vec::with_capacity(length);
-                let mut remaining = length;
+        let mut pairs = Vec::new();
+        let context_data = Context::new(context_block);
 
-                // Process one word at a time, extracting bytes
-                for word in string_block.iter().skip(1) {
-                    if remaining == 0 {
-                        break;
-                    }
+        // Use the iterator to efficiently iterate through all entries in the context
+        for (symbol, [tag, data]) in &context_data {
+            let symbol_name = self.get_symbol(symbol)?;
+            pairs.push((symbol_name, self.mem_value_within([tag, data], outer)?));
+        }
 
-                    // Extract up to 4 bytes from each word
-                    for j in 0..4 {
-                        if remaining == 0 {
-                            break;
-                        }
+        outer.pop();
+        Ok(pairs.into_boxed_slice())
+    }
 
-                        let byte = ((word >> (j * 8)) & 0xFF) as u8;
-                        bytes.push(byte);
-                        remaining -= 1;
+    /// Spec block of a function, rebuilt from its `params`, see [`Exec::alloc_func`].
+    fn func_spec(&self, params: Offset) -> Result<Box<[Value]>, CoreError> {
+        let mut spec = Vec::new();
+        for param in self.heap.get_block(params)?.chunks_exact(4) {
+            let name = self.get_symbol(param[1])?;
+            match param[0] {
+                VmValue::TAG_REFINEMENT => spec.push(Value::Refinement(name)),
+                _ => {
+                    spec.push(Value::Word(name));
+                    if param[3] != 0 {
+                        spec.push(Value::Block(Self::type_names(param[3])));
                     }
                 }
-
-                // Convert bytes to string
-                match String::from_utf8(bytes) {
-                    Ok(string) => Ok(Value::String(string.into())),
-                    Err(e) => Err(e.into()), // UTF-8 decoding error
-                }
             }
+        }
+        Ok(spec.into_boxed_slice())
+    }
 
-            VmValue::Block(offset) => Ok(Value::Block(self.get_block_value(offset)?)),
-            VmValue::Path(offset) => Ok(Value::Path(self.get_block_value(offset)?)),
+    /// Names of the datatypes in a mask made by [`Module::typeset`].
+    fn type_names(typeset: Word) -> Box<[Value]> {
+        if typeset == Word::MAX {
+            return Box::new([Value::Word("any-type!".into())]);
+        }
+        let mut names: Vec<&str> = Vec::new();
+        for (tag, name) in VmValue::TYPE_NAMES.iter() {
+            if typeset & (1 << tag) != 0 && !names.contains(name) {
+                names.push(name);
+            }
+        }
+        names
+            .into_iter()
+            .map(|name| Value::Word(name.into()))
+            .collect()
+    }
 
-            // Context value stored in heap
-            VmValue::Context(offset) => {
-                let context_block = self.heap.get_block(offset)?;
-                if context_block.is_empty() {
-                    return Ok(Value::Context(Box::new([])));
-                }
+    pub fn to_value(&self, vm_value: VmValue) -> Result<Value, CoreError> {
+        self.value_within(vm_value, &mut Vec::new())
+    }
 
-                let mut pairs = Vec::new();
-                let context_data = Context::new(context_block);
+    /// Value of a `[tag data]` pair as found in blocks and contexts.
+    pub fn mem_to_value(&self, value: MemValue) -> Result<Value, CoreError> {
+        self.mem_value_within(value, &mut Vec::new())
+    }
 
-                // Use the iterator to efficiently iterate through all entries in the context
-                for (symbol, [tag, data]) in &context_data {
-                    let symbol_name = self.get_symbol(symbol)?;
-                    let vm_value = VmValue::from_tag_data(tag, data)?;
-                    pairs.push((symbol_name, self.to_value(vm_value)?));
-                }
+    /// Natives live in the module only, so they have no value of their own.
+    fn mem_value_within(
+        &self,
+        [tag, data]: MemValue,
+        outer: &mut Vec<Offset>,
+    ) -> Result<Value, CoreError> {
+        if tag == VmValue::TAG_NATIVE_FN {
+            let symbol = self.get_func(data)?.symbol;
+            return Err(CoreError::NativeValue(self.get_symbol(symbol)?));
+        }
+        self.value_within(VmValue::from_tag_data(tag, data)?, outer)
+    }
 
-                Ok(Value::Context(pairs.into_boxed_slice()))
-            }
+    fn value_within(&self, vm_value: VmValue, outer: &mut Vec<Offset>) -> Result<Value, CoreError> {
+        match vm_value {
+            VmValue::None => Ok(Value::None),
+            VmValue::Int(n) => Ok(Value::Int(n)),
+            VmValue::Int64(offset) => Ok(Value::Int64(self.get_bits(offset)? as i64)),
+            VmValue::Decimal(offset) => Ok(Value::Decimal(f64::from_bits(self.get_bits(offset)?))),
+            VmValue::Date(offset) => Ok(Value::Date(self.get_bits(offset)? as i64)),
+            VmValue::Time(offset) => Ok(Value::Time(self.get_bits(offset)? as i64)),
+            VmValue::Bool(b) => Ok(Value::Bool(b)),
+            VmValue::Word(symbol) => Ok(Value::Word(self.get_symbol(symbol)?)),
+            VmValue::SetWord(symbol) => Ok(Value::SetWord(self.get_symbol(symbol)?)),
+            VmValue::GetWord(symbol) => Ok(Value::GetWord(self.get_symbol(symbol)?)),
+            VmValue::LitWord(symbol) => Ok(Value::LitWord(self.get_symbol(symbol)?)),
+            VmValue::Refinement(symbol) => Ok(Value::Refinement(self.get_symbol(symbol)?)),
 
-            // Function value stored in heap
-            VmValue::Func(_offset) => {
-                Ok(Value::None)
-                // let block = self.heap.get_block(offset)?;
-                // if block.is_empty() {
-                //     return Ok(Value::Block(Box::new([])));
-                // }
+            VmValue::String(offset) => Ok(Value::String(self.get_text(offset)?)),
+            VmValue::File(offset) => Ok(Value::File(self.get_text(offset)?)),
+            VmValue::Url(offset) => Ok(Value::Url(self.get_text(offset)?)),
+            VmValue::Binary(offset) => Ok(Value::Binary(self.get_binary(offset)?.into())),
 
-                // let [arity, ctx, blk] = block;
-                // let context = self.heap.get_block(ctx)?;
-                // let mut pairs = Vec::new();
-                // let context_data = Context::new(context);
+            VmValue::Block(offset) => Ok(Value::Block(self.get_block_value(offset, outer)?)),
+            VmValue::Path(offset) => Ok(Value::Path(self.get_block_value(offset, outer)?)),
+            VmValue::SetPath(offset) => Ok(Value::SetPath(self.get_block_value(offset, outer)?)),
+            VmValue::GetPath(offset) => Ok(Value::GetPath(self.get_block_value(offset, outer)?)),
 
-                // // Use the iterator to efficiently iterate through all entries in the context
-                // for (symbol, [tag, data]) in &context_data {
-                //     let symbol_name = self.get_symbol(symbol)?;
-                //     let vm_value = VmValue::from_tag_data(tag, data)?;
-                //     pairs.push((symbol_name, self.to_value(vm_value)?));
-                // }
+            // Context value stored in heap
+            VmValue::Context(offset) => Ok(Value::Context(self.get_context_pairs(offset, outer)?)),
+            VmValue::Error(offset) => Ok(Value::Error(self.get_context_pairs(offset, outer)?)),
 
-                // Ok(Value::Func(arity, pairs.into_boxed_slice(), blk))
+            VmValue::Func(offset) => {
+                let [_, params, _, body] = self.get_block::<4>(offset, 2)?;
+                let spec = self.func_spec(params)?;
+                Ok(Value::Func(spec, self.get_block_value(body, outer)?))
             }
         }
     }
@@ -478,6 +1258,15 @@ where
 
 // E X E C U T I O N  C O N T E X T
 
+/// Resource limits for a process.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Limits {
+    /// Number of evaluation steps the process may take, `None` for no limit. A process
+    /// that runs out stops with [`CoreError::OutOfFuel`] and can be resumed with
+    /// [`Exec::add_fuel`] followed by [`Exec::eval`].
+    pub fuel: Option<u64>,
+}
+
 pub struct Op;
 
 impl Op {
@@ -489,17 +1278,90 @@ impl Op {
     pub const CONTEXT: Word = 5;
     pub const REDUCE: Word = 6;
     pub const FOREACH: Word = 7;
-    const LIT_PARAM: Word = 8;
+    pub const TRY: Word = 9;
+    pub const ATTEMPT: Word = 10;
+    pub const CATCH: Word = 11;
+    const PAUSE: Word = 12;
+    pub const WHILE: Word = 13;
+    pub const LOOP: Word = 14;
+    pub const REPEAT: Word = 15;
+    const INFIX: Word = 16;
+    const SET_PATH: Word = 17;
+    const RESUME: Word = 18;
+
+    fn name(op: Word) -> &'static str {
+        match op {
+            Op::SET_WORD => "set-word",
+            Op::CALL_NATIVE => "call-native",
+            Op::CALL_FUNC => "call-func",
+            Op::LEAVE_BLOCK => "leave-block",
+            Op::LEAVE_FUNC => "leave-func",
+            Op::CONTEXT => "context",
+            Op::REDUCE => "reduce",
+            Op::FOREACH => "foreach",
+            Op::TRY => "try",
+            Op::ATTEMPT => "attempt",
+            Op::CATCH => "catch",
+            Op::WHILE => "while",
+            Op::LOOP => "loop",
+            Op::REPEAT => "repeat",
+            Op::INFIX => "infix",
+            Op::SET_PATH => "set-path",
+            Op::RESUME => "resume",
+            _ => "unknown",
+        }
+    }
+
+    /// Frames of loops, which `break` and `continue` unwind to.
+    fn is_loop(op: Word) -> bool {
+        matches!(op, Op::FOREACH | Op::WHILE | Op::LOOP | Op::REPEAT)
+    }
 }
 
-pub struct Exec<'a, T> {
-    block: Offset,
-    ip: Offset,
+/// Where a debugged process should stop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// Before evaluating the word with this symbol.
+    Word(SymbolId),
+    /// Before evaluating the value at the given word offset (two words per value) of a block.
+    Block(Offset, Offset),
+}
 
-    module: &'a mut Module<T>,
+/// Outcome of running a process under the debugger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// Stopped before evaluating the value at [`Exec::position`].
+    Paused,
+    /// Evaluation finished with this result.
+    Done(VmValue),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Eval,
+    Resume,
+    Step,
+    StepOver(usize),
+}
+
+pub struct Exec<'a, T> {
+    block: Offset,
+    ip: Offset,
+
+    module: &'a mut Module<T>,
     stack: Stack<[Offset; 1024]>,
     op_stack: Stack<[Offset; 1024]>,
     env: Stack<[Offset; 512]>,
+    /// Scope each block was written in, recorded when the block is evaluated as a value,
+    /// see [`Exec::scope_of`].
+    scopes: HashMap<Offset, [Offset; 2]>,
+    fuel: Option<u64>,
+
+    breakpoints: Vec<Breakpoint>,
+    mode: Mode,
+    paused: bool,
+    /// Natives continuing after a call made with [`Exec::call`], by frame word.
+    continuations: Vec<NativeFn<T>>,
 }
 
 impl<'a, T> Exec<'a, T> {
@@ -507,7 +1369,7 @@ impl<'a, T> Exec<'a, T> {
 
     fn new(module: &'a mut Module<T>, block: Offset) -> Result<Self, MemoryError> {
         let mut env = Stack::new([0; 512]);
-        env.push([module.system_words])?;
+        env.push([module.system_words, 0])?;
         Ok(Self {
             block,
             ip: 0,
@@ -515,8 +1377,26 @@ impl<'a, T> Exec<'a, T> {
             stack: Stack::new([0; 1024]),
             op_stack: Stack::new([0; 1024]),
             env,
+            scopes: HashMap::new(),
+            fuel: None,
+            breakpoints: Vec::new(),
+            mode: Mode::Eval,
+            paused: false,
+            continuations: Vec::new(),
         })
     }
+
+    /// Remaining fuel, `None` if the process is not limited.
+    pub fn fuel(&self) -> Option<u64> {
+        self.fuel
+    }
+
+    /// Top up the fuel of a limited process.
+    pub fn add_fuel(&mut self, amount: u64) {
+        if let Some(fuel) = self.fuel.as_mut() {
+            *fuel = fuel.saturating_add(amount);
+        }
+    }
 }
 
 impl<'a, T> Exec<'a, T>
@@ -535,25 +1415,69 @@ where
         self.module.heap.get_block(block).map(|block| block.len())
     }
 
-    fn find_word(&self, symbol: SymbolId) -> Result<MemValue, MemoryError> {
-        let envs = self.env.peek_all(0).ok_or(MemoryError::StackUnderflow)?;
+    /// All words of a block, `[tag, data]` pairs for blocks of values.
+    pub fn get_block_data(&self, block: Offset) -> Result<&[Word], MemoryError> {
+        self.module.heap.get_block(block)
+    }
+
+    pub fn get_symbol(&self, symbol: SymbolId) -> Result<SmolStr, MemoryError> {
+        self.module.get_symbol(symbol)
+    }
 
-        for &addr in envs.iter().rev() {
-            let context = self.module.heap.get_block(addr).map(Context::new)?;
+    /// Look `symbol` up in the scope of the block being evaluated, see
+    /// [`Exec::visible_contexts`].
+    fn find_word(&self, symbol: SymbolId) -> Result<MemValue, MemoryError> {
+        let [ctx, parent] = self.env.peek().ok_or(MemoryError::StackUnderflow)?;
+        let mut scope = [VmValue::TAG_CONTEXT, ctx, VmValue::TAG_BLOCK, parent];
+        loop {
+            let context = self.module.heap.get_block(scope[1]).map(Context::new)?;
             match context.get(symbol) {
-                Ok(result) => return Ok(result),
-                Err(MemoryError::WordNotFound) => continue,
-                Err(err) => return Err(err),
+                Err(MemoryError::WordNotFound) => {}
+                result => return result,
+            }
+            if scope[2] != VmValue::TAG_BLOCK || scope[3] == 0 {
+                return Err(MemoryError::WordNotFound);
             }
+            scope = self.module.get_block::<4>(scope[3], 0)?;
         }
+    }
 
-        Err(MemoryError::WordNotFound)
+    /// Contexts words are looked up in, innermost first. Each env entry is a whole scope:
+    /// a context and the chain of `[context parent]` blocks it is nested in, down to the
+    /// system words. Blocks are evaluated in the scope they were written in, so a block
+    /// passed to a function still sees the words of its caller, and only those.
+    fn visible_contexts(&self) -> Result<Vec<Offset>, MemoryError> {
+        let [ctx, mut parent] = self.env.peek().ok_or(MemoryError::StackUnderflow)?;
+        let mut contexts = vec![ctx];
+        while parent != 0 {
+            let [_, ctx, tag, next] = self.module.get_block::<4>(parent, 0)?;
+            contexts.push(ctx);
+            parent = if tag == VmValue::TAG_BLOCK { next } else { 0 };
+        }
+        Ok(contexts)
+    }
+
+    /// Scope `block` is evaluated in: the one it was written in if it was evaluated as a
+    /// value before, e.g. a block literal passed to a function, otherwise the current one.
+    fn scope_of(&self, block: Offset) -> Result<[Offset; 2], MemoryError> {
+        match self.scopes.get(&block) {
+            Some(scope) => Ok(*scope),
+            None => self.env.peek().ok_or(MemoryError::StackUnderflow),
+        }
     }
 
     pub fn to_value(&self, vm_value: VmValue) -> Result<Value, CoreError> {
         self.module.to_value(vm_value)
     }
 
+    pub fn mem_to_value(&self, value: MemValue) -> Result<Value, CoreError> {
+        self.module.mem_to_value(value)
+    }
+
+    pub fn get_binary(&self, binary: Offset) -> Result<Vec<u8>, MemoryError> {
+        self.module.get_binary(binary)
+    }
+
     pub fn peek<const N: usize>(&self) -> Option<[Word; N]> {
         self.stack.peek()
     }
@@ -561,7 +1485,7 @@ where
 
 impl<'a, T> Exec<'a, T>
 where
-    T: AsMut<[Word]> + AsRef<[Word]>,
+    T: Storage,
 {
     pub fn pop<const N: usize>(&mut self) -> Result<[Word; N], MemoryError> {
         self.stack.pop()
@@ -592,11 +1516,26 @@ where
     pub fn push_value(&mut self, value: Value) -> Result<(), CoreError> {
         self.module
             .alloc_value(&value)
-            .and_then(|vm_value| self.push_vm_value(vm_value))
-            .map_err(Into::into)
+            .and_then(|vm_value| self.push_vm_value(vm_value).map_err(Into::into))
     }
 
+    /// Evaluate `block` in its scope, see [`Exec::scope_of`], under a frame for `op`.
     pub fn jmp_op(&mut self, block: Offset, op: Word) -> Result<(), CoreError> {
+        let scope = self.scope_of(block)?;
+        self.enter(block, op, scope)
+    }
+
+    /// Evaluate `block` with the words of `ctx` in front of its scope, as the blocks of
+    /// `context` and of loops setting a word are.
+    pub fn jmp_op_in(&mut self, block: Offset, op: Word, ctx: Offset) -> Result<(), CoreError> {
+        let scope = self.scope_of(block)?;
+        let parent = self.alloc_scope(scope)?;
+        self.enter(block, op, [ctx, parent])
+    }
+
+    /// Every frame entering a block owns an env entry for the scope of that block.
+    fn enter(&mut self, block: Offset, op: Word, scope: [Offset; 2]) -> Result<(), CoreError> {
+        self.env.push(scope)?;
         self.op_stack.push([
             op,
             self.block,
@@ -612,6 +1551,209 @@ where
         self.jmp_op(block, Op::LEAVE_BLOCK)
     }
 
+    pub fn jmp_in(&mut self, block: Offset, ctx: Offset) -> Result<(), CoreError> {
+        self.jmp_op_in(block, Op::LEAVE_BLOCK, ctx)
+    }
+
+    /// Evaluate `block` under a handler frame: `Op::TRY`, `Op::ATTEMPT` or `Op::CATCH`.
+    ///
+    /// The env depth is saved on the stack right below the frame, so that unwinding to
+    /// the handler can drop the contexts pushed since.
+    pub fn jmp_handler(&mut self, block: Offset, op: Word) -> Result<(), CoreError> {
+        let env_len = self.env.len()?;
+        self.stack.push([VmValue::TAG_INT, env_len])?;
+        self.jmp_op(block, op)
+    }
+
+    /// Unwind to the innermost `catch` and make `value` its result.
+    pub fn throw(&mut self, value: MemValue) -> Result<(), CoreError> {
+        match self.find_handler(|op| op == Op::CATCH)? {
+            Some((frame, _)) => self.unwind(frame, value),
+            None => Err(CoreError::NoCatch),
+        }
+    }
+
+    /// Leave the innermost loop, which evaluates to none.
+    pub fn break_loop(&mut self) -> Result<(), CoreError> {
+        let frame = self.find_loop()?;
+        self.drop_frames(frame + 4)?;
+        let [op, block, bp, ip] = self.op_stack.pop()?;
+        self.stack.set_len(bp)?;
+        self.end_loop(op, [VmValue::TAG_NONE, 0])?;
+        self.block = block;
+        self.ip = ip - Self::LEAVE_MARKER;
+        Ok(())
+    }
+
+    /// Skip the rest of the body of the innermost loop and go on with the next iteration.
+    pub fn continue_loop(&mut self) -> Result<(), CoreError> {
+        let frame = self.find_loop()?;
+        self.drop_frames(frame + 4)?;
+        let [_, _, bp, _] = self.op_stack.get(frame)?;
+        self.stack.set_len(bp)?;
+        self.skip_block()
+    }
+
+    /// Leave the innermost function with `value` as its result.
+    pub fn return_value(&mut self, value: MemValue) -> Result<(), CoreError> {
+        let (frame, _) = self
+            .find_handler(|op| op == Op::LEAVE_FUNC)?
+            .ok_or(CoreError::NoFunction)?;
+        self.drop_frames(frame + 4)?;
+        let [_, _, bp, _] = self.op_stack.get(frame)?;
+        self.stack.set_len(bp)?;
+        self.stack.push(value)?;
+        self.skip_block()
+    }
+
+    /// Turn an error value back into a `CoreError`, so that it can be raised again.
+    pub fn reraise(&mut self, error: Offset) -> Result<CoreError, CoreError> {
+        let error = self.to_value(VmValue::Error(error))?;
+        let kind = match error.get("kind") {
+            Some(Value::Word(kind)) => kind.clone(),
+            _ => "user".into(),
+        };
+        let message = match error.get("message") {
+            Some(Value::String(message)) => message.to_string(),
+            Some(other) => other.form(),
+            None => String::new(),
+        };
+        let word = match error.get("word") {
+            Some(Value::Word(word)) => Some(self.module.get_or_insert_symbol(word)?),
+            _ => None,
+        };
+        Ok(CoreError::Raised {
+            kind,
+            message,
+            word,
+        })
+    }
+
+    pub fn alloc_error(
+        &mut self,
+        kind: &str,
+        message: &str,
+        word: Option<SymbolId>,
+    ) -> Result<Offset, MemoryError> {
+        self.module.alloc_error(kind, message, word)
+    }
+
+    /// Find the innermost handler frame accepted by `handles`, returns its offset and op.
+    fn find_handler<F>(&self, handles: F) -> Result<Option<(Offset, Word)>, MemoryError>
+    where
+        F: Fn(Word) -> bool,
+    {
+        let frames = self
+            .op_stack
+            .peek_all(0)
+            .ok_or(MemoryError::StackUnderflow)?;
+        Ok(frames
+            .chunks_exact(4)
+            .enumerate()
+            .rev()
+            .find(|(_, frame)| handles(frame[0]))
+            .map(|(i, frame)| ((i * 4) as Offset, frame[0])))
+    }
+
+    /// Find the innermost loop frame of the current function.
+    fn find_loop(&self) -> Result<Offset, CoreError> {
+        match self.find_handler(|op| op == Op::LEAVE_FUNC || Op::is_loop(op))? {
+            Some((frame, op)) if op != Op::LEAVE_FUNC => Ok(frame),
+            _ => Err(CoreError::NoLoop),
+        }
+    }
+
+    /// Drop the frames above the first `len` words of the op stack, leaving the scopes
+    /// of the blocks they entered.
+    fn drop_frames(&mut self, len: Offset) -> Result<(), MemoryError> {
+        while self.op_stack.len()? > len {
+            let [_, _, _, ip] = self.op_stack.pop()?;
+            if ip >= Self::LEAVE_MARKER {
+                self.env.pop::<2>()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Move to the end of the current block, so that the frame on top of the op stack is
+    /// left next.
+    fn skip_block(&mut self) -> Result<(), CoreError> {
+        self.ip = self.get_block_len(self.block)? as Offset;
+        Ok(())
+    }
+
+    /// Drop the state of a finished loop from the stack and leave the scope of its body,
+    /// the loop evaluates to `result`.
+    fn end_loop(&mut self, op: Word, result: MemValue) -> Result<(), CoreError> {
+        self.env.pop::<2>()?;
+        match op {
+            Op::FOREACH | Op::REPEAT | Op::WHILE => {
+                self.stack.pop::<8>()?;
+            }
+            Op::LOOP => {
+                self.stack.pop::<4>()?;
+            }
+            _ => return Err(CoreError::InternalError),
+        }
+        self.stack.push(result)?;
+        Ok(())
+    }
+
+    /// Drop everything above the handler frame at `frame` and continue after the handler
+    /// with `value` as its result.
+    fn unwind(&mut self, frame: Offset, value: MemValue) -> Result<(), CoreError> {
+        let [_, block, bp, ip] = self.op_stack.get(frame)?;
+        self.op_stack.set_len(frame)?;
+        self.stack.set_len(bp)?;
+        let [_, env_len] = self.stack.pop()?;
+        self.env.set_len(env_len)?;
+        self.stack.push(value)?;
+        self.block = block;
+        self.ip = ip - Self::LEAVE_MARKER;
+        Ok(())
+    }
+
+    /// Unwind to the innermost `try` or `attempt`. Errors with no handler are returned.
+    fn recover(&mut self, error: CoreError, word: Option<SymbolId>) -> Result<(), CoreError> {
+        let Some((frame, op)) = self.find_handler(|op| op == Op::TRY || op == Op::ATTEMPT)? else {
+            return Err(error);
+        };
+        let value = if op == Op::TRY {
+            let word = match &error {
+                CoreError::Raised { word, .. } => *word,
+                _ => word,
+            };
+            let message = error.to_string();
+            [
+                VmValue::TAG_ERROR,
+                self.alloc_error(error.kind(), &message, word)?,
+            ]
+        } else {
+            [VmValue::TAG_NONE, 0]
+        };
+        self.unwind(frame, value)
+    }
+
+    /// Word to blame for an error raised by `op`.
+    fn failing_word(&self, op: Word, word: Word) -> Option<SymbolId> {
+        match op {
+            Op::CALL_NATIVE | Op::INFIX => self.module.get_func(word).ok().map(|desc| desc.symbol),
+            Op::SET_WORD => Some(word),
+            _ => None,
+        }
+    }
+
+    /// Word at the current position, which is the one being resolved when `next_op` fails.
+    fn current_word(&self) -> Option<SymbolId> {
+        let ip = self.ip.checked_sub(2)?;
+        match self.get_block::<2>(self.block, ip).ok()? {
+            [VmValue::TAG_WORD | VmValue::TAG_SET_WORD | VmValue::TAG_GET_WORD, symbol] => {
+                Some(symbol)
+            }
+            _ => None,
+        }
+    }
+
     pub fn push_op(&mut self, op: Word, word: Word, arity: Word) -> Result<(), MemoryError> {
         self.op_stack.push([op, word, self.stack.len()?, arity])
     }
@@ -628,103 +1770,478 @@ where
         self.module.alloc_string(string)
     }
 
+    pub fn alloc_binary(&mut self, bytes: &[u8]) -> Result<Offset, MemoryError> {
+        self.module.alloc_binary(bytes)
+    }
+
+    /// Parse `code` into a new block, see [`Module::parse`].
+    pub fn parse(&mut self, code: &str) -> Result<VmValue, CoreError> {
+        self.module.parse(code)
+    }
+
+    /// Replace `remove` words of a block, starting at `at`, with `values`. The block keeps
+    /// its offset even if it has to move to grow, see [`Heap::splice`].
+    pub fn splice(
+        &mut self,
+        block: Offset,
+        at: usize,
+        remove: usize,
+        values: &[Word],
+    ) -> Result<(), MemoryError> {
+        self.module.heap.splice(block, at, remove, values)
+    }
+
+    /// Replace the contents of a string in place.
+    pub fn set_string(&mut self, string: Offset, value: &str) -> Result<(), MemoryError> {
+        let len = self.get_block_len(string)?;
+        self.splice(string, 0, len, &Module::<T>::pack_bytes(value.as_bytes()))
+    }
+
+    /// Replace the contents of a binary in place.
+    pub fn set_binary(&mut self, binary: Offset, bytes: &[u8]) -> Result<(), MemoryError> {
+        let len = self.get_block_len(binary)?;
+        let words = self.module.pack_binary(bytes)?;
+        self.splice(binary, 0, len, &words)
+    }
+
     pub fn alloc_context(&mut self, size: u32) -> Result<Offset, MemoryError> {
         self.module.heap.alloc_context(size)
     }
 
+    /// Allocate a function from its spec and body, capturing the current context.
+    ///
+    /// The spec lists the arguments, each optionally followed by a block of accepted
+    /// types such as `[integer! string!]`, then refinements with their own arguments.
+    /// Locals are the arguments of the `/local` refinement. Strings are doc strings.
+    ///
+    /// The function is `[arity params body scope refinements]`, where `params` has a
+    /// `[word types]` entry per argument and a `[refinement argument-words]` entry per
+    /// refinement, `scope` is the chain of contexts it was defined in (see
+    /// [`Exec::capture_scope`]), and `refinements` is the mask of refinements the call uses.
+    pub fn alloc_func(&mut self, spec: Offset, body: Offset) -> Result<Offset, CoreError> {
+        // words of the defining contexts stay visible to the body when called later
+        let scope = self.capture_scope()?;
+        self.module.alloc_func(spec, body, scope)
+    }
+
+    /// Chain of the visible contexts, as `[context parent]` blocks linked from the innermost
+    /// one down to the system words, where `parent` is none. Scopes of enclosing functions
+    /// are shared rather than copied.
+    fn capture_scope(&mut self) -> Result<Offset, MemoryError> {
+        let scope = self.env.peek().ok_or(MemoryError::StackUnderflow)?;
+        self.alloc_scope(scope)
+    }
+
+    /// Allocate a `[context parent]` block for an env entry, see [`Exec::visible_contexts`].
+    fn alloc_scope(&mut self, [ctx, parent]: [Offset; 2]) -> Result<Offset, MemoryError> {
+        let parent = match parent {
+            0 => [VmValue::TAG_NONE, 0],
+            parent => [VmValue::TAG_BLOCK, parent],
+        };
+        self.alloc_block(&[VmValue::TAG_CONTEXT, ctx, parent[0], parent[1]])
+    }
+
+    /// Evaluate `block` from now on with `ctx` in front of its scope, see [`Exec::scope_of`].
+    pub fn bind(&mut self, block: Offset, ctx: Offset) -> Result<(), MemoryError> {
+        let scope = self.scope_of(block)?;
+        let parent = self.alloc_scope(scope)?;
+        self.scopes.insert(block, [ctx, parent]);
+        Ok(())
+    }
+
     pub fn get_context(&mut self, offset: Offset) -> Result<Context<&mut [u32]>, MemoryError> {
         self.module.heap.get_block_mut(offset).map(Context::new)
     }
 
     fn peek_context(&mut self) -> Result<Context<&mut [u32]>, MemoryError> {
-        let [ctx] = self.env.peek().ok_or(MemoryError::StackUnderflow)?;
+        let [ctx, _] = self.env.peek().ok_or(MemoryError::StackUnderflow)?;
         self.module.heap.get_block_mut(ctx).map(Context::new)
     }
 
+    /// Innermost context of the environment, the one words are looked up in first.
+    pub fn peek_env(&self) -> Result<Offset, MemoryError> {
+        self.env
+            .peek()
+            .map(|[ctx, _]| ctx)
+            .ok_or(MemoryError::StackUnderflow)
+    }
+
+    /// Enter `ctx` in front of the current scope, until [`Exec::pop_context`].
     pub fn push_context(&mut self, ctx: Offset) -> Result<(), MemoryError> {
-        self.env.push([ctx])
+        let scope = self.env.peek().ok_or(MemoryError::StackUnderflow)?;
+        let parent = self.alloc_scope(scope)?;
+        self.env.push([ctx, parent])
     }
 
     pub fn pop_context(&mut self) -> Result<Offset, MemoryError> {
-        self.env.pop().map(|[addr]| addr)
+        self.env.pop().map(|[addr, _]| addr)
     }
 
-    pub fn alloc_value(&mut self, value: &Value) -> Result<VmValue, MemoryError> {
+    pub fn alloc_value(&mut self, value: &Value) -> Result<VmValue, CoreError> {
         self.module.alloc_value(value)
     }
 
+    /// Set a word in the innermost context that has it, or that can take new words.
+    fn set_word(&mut self, symbol: SymbolId, value: MemValue) -> Result<(), MemoryError> {
+        for ctx in self.visible_contexts()? {
+            let mut context = self.module.heap.get_block_mut(ctx).map(Context::new)?;
+            match context.put(symbol, value) {
+                Ok(_) => return Ok(()),
+                Err(MemoryError::WordNotFound) => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        Err(MemoryError::WordNotFound)
+    }
+
+    /// Value of a word or a path, as a get-word or a get-path would evaluate it.
+    pub fn get(&mut self, target: VmValue) -> Result<MemValue, CoreError> {
+        match target {
+            VmValue::Word(symbol) => self.find_word(symbol).map_err(Into::into),
+            VmValue::Path(path) => self.get_path(path),
+            _ => Err(CoreError::BadArguments),
+        }
+    }
+
+    /// Set a word or a path, as a set-word or a set-path would.
+    pub fn set(&mut self, target: VmValue, value: MemValue) -> Result<(), CoreError> {
+        match target {
+            VmValue::Word(symbol) => self.set_word(symbol, value).map_err(Into::into),
+            VmValue::Path(path) => self.set_path(path, value),
+            _ => Err(CoreError::BadArguments),
+        }
+    }
+
     fn resolve(&mut self, value: MemValue) -> Result<MemValue, CoreError> {
         match value[0] {
             VmValue::TAG_WORD => {
                 let word = self.find_word(value[1])?;
                 self.resolve(word)
             }
-            VmValue::TAG_PATH => {
-                let env_len = self.env.len()?;
-                let block = value[1];
-                let mut offset = 0;
-                while let Ok(path_segment) = self.get_block::<2>(block, offset) {
-                    match path_segment[0] {
-                        VmValue::TAG_WORD => {
-                            let result = self.find_word(path_segment[1])?;
-                            if result[0] == VmValue::TAG_CONTEXT {
-                                self.env.push([result[1]])?;
-                                offset += 2;
-                            } else {
-                                self.env.set_len(env_len)?;
-                                return Ok(result);
-                            }
-                        }
-                        _ => unimplemented!(),
+            VmValue::TAG_PATH => self.resolve_path(value[1]).map(|(value, _)| value),
+            _ => Ok(value),
+        }
+    }
+
+    /// Resolve a path up to the first segment that can't be selected into, e.g. a function
+    /// followed by its refinements. Returns its value and the offset of the segments left.
+    fn resolve_path(&mut self, path: Offset) -> Result<(MemValue, Offset), CoreError> {
+        let end = self.get_block_len(path)? as Offset;
+        self.walk_path(path, end)
+    }
+
+    /// Value of a path, functions included are returned rather than called.
+    fn get_path(&mut self, path: Offset) -> Result<MemValue, CoreError> {
+        let end = self.get_block_len(path)? as Offset;
+        match self.walk_path(path, end)? {
+            (value, offset) if offset == end => Ok(value),
+            _ => Err(CoreError::BadArguments),
+        }
+    }
+
+    /// Paths start with a word, or with the context a word is in, as `in` returns them.
+    fn walk_path(&mut self, path: Offset, end: Offset) -> Result<(MemValue, Offset), CoreError> {
+        let mut value = match self.get_block::<2>(path, 0)? {
+            [VmValue::TAG_WORD | VmValue::TAG_GET_WORD, symbol] => self.find_word(symbol)?,
+            ctx @ [VmValue::TAG_CONTEXT, _] => ctx,
+            _ => return Err(CoreError::BadArguments),
+        };
+        let mut offset = 2;
+        while offset < end {
+            let segment = self.get_block::<2>(path, offset)?;
+            match self.select(value, segment)? {
+                Some(selected) => value = selected,
+                None => break,
+            }
+            offset += 2;
+        }
+        Ok((value, offset))
+    }
+
+    /// Select a path segment in `value`: a word in a context or an error, an index
+    /// starting at 1 in a block or a string, or a component of a url such as `host`.
+    /// Indexes past the end select none. Returns `None` if `value` can't be selected into.
+    fn select(
+        &mut self,
+        value: MemValue,
+        segment: MemValue,
+    ) -> Result<Option<MemValue>, CoreError> {
+        match (value, segment) {
+            ([VmValue::TAG_CONTEXT | VmValue::TAG_ERROR, ctx], [VmValue::TAG_WORD, symbol]) => {
+                let context = self.module.heap.get_block(ctx).map(Context::new)?;
+                Ok(Some(context.get(symbol)?))
+            }
+            ([VmValue::TAG_BLOCK | VmValue::TAG_PATH, block], [VmValue::TAG_INT, index]) => {
+                let index = Self::series_index(index)?;
+                Ok(Some(
+                    self.get_block(block, index * 2)
+                        .unwrap_or([VmValue::TAG_NONE, 0]),
+                ))
+            }
+            ([VmValue::TAG_INLINE_STRING, string], [VmValue::TAG_INT, index]) => {
+                let index = Self::series_index(index)?;
+                let char = match self.to_value(VmValue::String(string))? {
+                    Value::String(string) => string.chars().nth(index as usize),
+                    _ => None,
+                };
+                match char {
+                    Some(char) => {
+                        let string = self.alloc_string(char.encode_utf8(&mut [0; 4]))?;
+                        Ok(Some([VmValue::TAG_INLINE_STRING, string]))
                     }
+                    None => Ok(Some([VmValue::TAG_NONE, 0])),
                 }
-                Err(CoreError::UnexpectedEndOfBlock)
             }
-            _ => Ok(value),
+            ([VmValue::TAG_URL, url], [VmValue::TAG_WORD, symbol]) => {
+                let text = match self.to_value(VmValue::Url(url))? {
+                    Value::Url(text) => text,
+                    _ => return Err(CoreError::InternalError),
+                };
+                let url = parse_url(&text).ok_or(CoreError::BadArguments)?;
+                let component = url_component(&url, &self.get_symbol(symbol)?)
+                    .ok_or(MemoryError::WordNotFound)?;
+                Ok(Some(self.module.alloc_value(&component)?.vm_repr()))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Zero based position of a path index, which starts at 1.
+    fn series_index(index: Word) -> Result<Offset, CoreError> {
+        match index as i32 {
+            index if index >= 1 => Ok(index as Offset - 1),
+            _ => Err(CoreError::BoundsCheckFailed),
+        }
+    }
+
+    /// Set the target of a set-path, i.e. the last segment selected in the rest of the
+    /// path. Words have to exist in the context.
+    fn set_path(&mut self, path: Offset, value: MemValue) -> Result<(), CoreError> {
+        let last = (self.get_block_len(path)? as Offset)
+            .checked_sub(2)
+            .filter(|last| *last > 0)
+            .ok_or(CoreError::BadArguments)?;
+        let target = match self.walk_path(path, last)? {
+            (target, offset) if offset == last => target,
+            _ => return Err(CoreError::BadArguments),
+        };
+        match (target, self.get_block::<2>(path, last)?) {
+            ([VmValue::TAG_CONTEXT | VmValue::TAG_ERROR, ctx], [VmValue::TAG_WORD, symbol]) => {
+                let mut context = self.get_context(ctx)?;
+                context.get(symbol)?;
+                context.put(symbol, value).map_err(Into::into)
+            }
+            ([VmValue::TAG_BLOCK, block], [VmValue::TAG_INT, index]) => {
+                let index = Self::series_index(index)? as usize * 2;
+                let slot = self
+                    .module
+                    .heap
+                    .get_block_mut(block)?
+                    .get_mut(index..index + 2)
+                    .ok_or(CoreError::BoundsCheckFailed)?;
+                slot.copy_from_slice(&value);
+                Ok(())
+            }
+            _ => Err(CoreError::BadArguments),
+        }
+    }
+
+    /// Descriptor for calling a function through a path such as `f/only`: a copy with
+    /// the refinements named by the rest of the path switched on and their arguments
+    /// added to the arity.
+    fn refine(
+        &mut self,
+        desc: Offset,
+        path: Offset,
+        mut offset: Offset,
+    ) -> Result<Offset, CoreError> {
+        let mut func: [Word; 10] = self.get_block(desc, 0)?;
+        let params = func[3];
+        while let Ok([tag, symbol]) = self.get_block::<2>(path, offset) {
+            offset += 2;
+            if tag != VmValue::TAG_WORD {
+                return Err(CoreError::BadArguments);
+            }
+            let mut index = 0;
+            let mut entry = 0;
+            loop {
+                // running past the last entry means there is no such refinement
+                let [tag, name, _, args] = self
+                    .get_block(params, entry)
+                    .map_err(|_| CoreError::BadArguments)?;
+                entry += 4;
+                if tag == VmValue::TAG_REFINEMENT {
+                    if name == symbol {
+                        func[1] += args;
+                        func[9] |= 1 << index;
+                        break;
+                    }
+                    index += 1;
+                }
+            }
+        }
+        if func[9] == 0 {
+            return Ok(desc);
         }
+        self.alloc_block(&func).map_err(Into::into)
+    }
+
+    /// Call `func`, a function or a native, with `args` and continue with `resume` once it
+    /// returns. `resume` finds the result on top of the stack, above anything the caller
+    /// left there, so natives keep their state on the stack from one call to the next.
+    pub fn call(
+        &mut self,
+        func: MemValue,
+        args: &[MemValue],
+        resume: NativeFn<T>,
+    ) -> Result<(), CoreError> {
+        let (op, arity) = match func {
+            [VmValue::TAG_FUNC, desc] => (Op::CALL_FUNC, self.get_block::<1>(desc, 1)?[0]),
+            [VmValue::TAG_NATIVE_FN, native] => {
+                (Op::CALL_NATIVE, self.module.get_func(native)?.arity)
+            }
+            _ => return Err(CoreError::BadArguments),
+        };
+        if arity as usize != args.len() * 2 {
+            return Err(CoreError::BadArguments);
+        }
+        let index = match self
+            .continuations
+            .iter()
+            .position(|known| std::ptr::fn_addr_eq(*known, resume))
+        {
+            Some(index) => index,
+            None => {
+                self.continuations.push(resume);
+                self.continuations.len() - 1
+            }
+        };
+        let frame = [Op::RESUME, index as Word, self.stack.len()?, 2];
+        self.op_stack.push(frame)?;
+        for arg in args {
+            self.stack.push(*arg)?;
+        }
+        self.do_op(op, func[1])?;
+        // natives returning nothing resume with none
+        if self.op_stack.peek() == Some(frame) && self.stack.len()? == frame[2] {
+            self.stack.push([VmValue::TAG_NONE, 0])?;
+        }
+        Ok(())
+    }
+
+    /// Native called through a path such as `copy/part`: the one registered under the
+    /// name of the native followed by the refinements in the rest of the path.
+    fn refine_native(
+        &self,
+        func: Word,
+        path: Offset,
+        mut offset: Offset,
+    ) -> Result<Word, CoreError> {
+        let mut name = self
+            .get_symbol(self.module.get_func(func)?.symbol)?
+            .to_string();
+        while let Ok([tag, symbol]) = self.get_block::<2>(path, offset) {
+            offset += 2;
+            if tag != VmValue::TAG_WORD {
+                return Err(CoreError::BadArguments);
+            }
+            name.push('/');
+            name.push_str(&self.get_symbol(symbol)?);
+        }
+        for (index, desc) in self.module.functions.iter().enumerate() {
+            if self.get_symbol(desc.symbol)? == name {
+                return Ok(index as Word);
+            }
+        }
+        Err(CoreError::BadArguments)
     }
 
     fn do_op(&mut self, op: Word, word: Word) -> Result<(), CoreError> {
         match op {
             Op::SET_WORD => {
                 let value = self.stack.peek().ok_or(MemoryError::StackUnderflow)?;
-                let contexts = self.env.peek_all(0).ok_or(MemoryError::StackUnderflow)?;
-                for &ctx in contexts.iter().rev() {
-                    let mut context = self.module.heap.get_block_mut(ctx).map(Context::new)?;
-                    match context.put(word, value) {
-                        Ok(_) => return Ok(()),
-                        Err(MemoryError::WordNotFound) => continue,
-                        Err(err) => return Err(err.into()),
-                    }
-                }
-                Err(MemoryError::WordNotFound.into())
+                self.set_word(word, value).map_err(Into::into)
+            }
+            Op::SET_PATH => {
+                let value = self.stack.peek().ok_or(MemoryError::StackUnderflow)?;
+                self.set_path(word, value)
+            }
+            Op::RESUME => {
+                let resume = self
+                    .continuations
+                    .get(word as usize)
+                    .ok_or(CoreError::InternalError)?;
+                resume(self)
             }
-            Op::CALL_NATIVE => {
-                let native_fn = self.module.get_func(word)?;
-                (native_fn.func)(self)
+            Op::CALL_NATIVE | Op::INFIX => {
+                let native_fn = self.module.get_func(word)?.func;
+                self.check_args(word)?;
+                native_fn(self)
             }
             Op::CALL_FUNC => {
-                let [_, arity, _, params, _, body] = self.get_block(word, 0)?;
-                let mut offset = arity;
-                let ctx = self.alloc_context(arity)?;
-                while offset > 0 {
-                    offset -= 2;
-                    let [tag, symbol] = self.get_block(params, offset)?;
-                    if tag != VmValue::TAG_WORD {
-                        return Err(CoreError::BadArguments);
-                    }
-                    let value = self.pop()?;
+                let [_, arity, _, params, _, body, _, scope, _, refinements] =
+                    self.get_block(word, 0)?;
+                let entries = self.get_block_len(params)? as Offset / 4;
+                let ctx = self.alloc_context(entries)?;
+
+                // arguments of the function, then of the refinements used, in spec order
+                let base = self
+                    .stack
+                    .len()?
+                    .checked_sub(arity)
+                    .ok_or(MemoryError::StackUnderflow)?;
+                let mut arg = base;
+                let mut refinement = 0;
+                let mut active = true;
+                for entry in 0..entries {
+                    let [tag, symbol, _, types] = self.get_block(params, entry * 4)?;
+                    let value = if tag == VmValue::TAG_REFINEMENT {
+                        active = refinements & (1 << refinement) != 0;
+                        refinement += 1;
+                        if active {
+                            [VmValue::TAG_BOOL, 1]
+                        } else {
+                            [VmValue::TAG_NONE, 0]
+                        }
+                    } else if active {
+                        let value = self.stack.get::<2>(arg)?;
+                        arg += 2;
+                        if types != 0 && types & (1 << value[0]) == 0 {
+                            return Err(CoreError::InvalidArgument {
+                                native: "function".into(),
+                                param: self.module.get_symbol(symbol)?,
+                                received: VmValue::type_name(value[0]),
+                            });
+                        }
+                        value
+                    } else {
+                        [VmValue::TAG_NONE, 0]
+                    };
                     self.get_context(ctx)?.put(symbol, value)?;
                 }
-
-                self.env.push([ctx])?;
-                let bp = self.stack.len()?;
-                self.op_stack.push([
-                    Op::LEAVE_FUNC,
-                    self.block,
-                    bp,
-                    Self::LEAVE_MARKER + self.ip,
-                ])?;
+                self.stack.set_len(base)?;
+                // other words are set where they are bound, e.g. in the defining context
+                self.get_context(ctx)?.seal()?;
+
+                if let Some((frames, bp)) = self.tail_call()? {
+                    // reuse the caller's frame, nothing is left to do in its body
+                    let dropped = (self.op_stack.len()? - frames) / 4;
+                    for _ in 0..=dropped {
+                        self.env.pop::<2>()?;
+                    }
+                    self.op_stack.set_len(frames)?;
+                    self.stack.set_len(bp)?;
+                    self.env.push([ctx, scope])?;
+                } else {
+                    self.env.push([ctx, scope])?;
+                    let bp = self.stack.len()?;
+                    self.op_stack.push([
+                        Op::LEAVE_FUNC,
+                        self.block,
+                        bp,
+                        Self::LEAVE_MARKER + self.ip,
+                    ])?;
+                }
 
                 self.block = body;
                 self.ip = 0;
@@ -733,14 +2250,7 @@ where
             }
             Op::CONTEXT => {
                 self.stack.pop::<2>()?; // we have result on stack after context's block leave, let's remove it and push context
-                let ctx = self.pop_context()?;
-                self.stack.push([VmValue::TAG_CONTEXT, ctx])?;
-                Ok(())
-            }
-            Op::LIT_PARAM => {
-                let value = self.get_block::<2>(self.block, self.ip)?;
-                self.ip += 2;
-                self.stack.push(value)?;
+                self.stack.push([VmValue::TAG_CONTEXT, word])?;
                 Ok(())
             }
             _ => Err(CoreError::InternalError),
@@ -750,31 +2260,81 @@ where
     fn next_op(&mut self) -> Result<(Word, Word), CoreError> {
         loop {
             // Check pending operations
-            if let Some([bp, arity]) = self.op_stack.peek() {
+            if let Some([op, word, bp, arity]) = self.op_stack.peek() {
                 let sp = self.stack.len()?;
-                if sp == bp + arity {
-                    let [op, word, _, _] = self.op_stack.pop()?;
+                // an operator after the last argument takes it as its left operand first
+                if sp == bp + arity
+                    && (matches!(op, Op::INFIX | Op::RESUME) || self.next_operator().is_none())
+                {
+                    self.op_stack.pop::<4>()?;
                     return Ok((op, word));
                 }
+                if op == Op::CALL_NATIVE && arity < Self::LEAVE_MARKER {
+                    let index = (sp - bp) as usize / 2;
+                    if self.take_arg(word, index)? {
+                        continue;
+                    }
+                }
             }
 
             if let Ok(val) = self.get_block(self.block, self.ip) {
+                if self.mode != Mode::Eval && self.should_pause(val)? {
+                    return Ok((Op::PAUSE, 0));
+                }
                 self.ip += 2;
-                match self.resolve(val)? {
+                if let Some(func) = self.operator(val) {
+                    self.push_infix(func)?;
+                    continue;
+                }
+                let (value, rest) = match val {
+                    [VmValue::TAG_PATH, path] => {
+                        let (value, offset) = self.resolve_path(path)?;
+                        if !matches!(value[0], VmValue::TAG_FUNC | VmValue::TAG_NATIVE_FN)
+                            && offset < self.get_block_len(path)? as Offset
+                        {
+                            return Err(CoreError::BadArguments);
+                        }
+                        (value, Some((path, offset)))
+                    }
+                    // get-words and get-paths don't call functions they refer to
+                    [VmValue::TAG_GET_WORD, symbol] => {
+                        let value = self.find_word(symbol)?;
+                        self.push(value)?;
+                        continue;
+                    }
+                    [VmValue::TAG_GET_PATH, path] => {
+                        let value = self.get_path(path)?;
+                        self.push(value)?;
+                        continue;
+                    }
+                    [VmValue::TAG_LIT_WORD, symbol] => {
+                        self.push([VmValue::TAG_WORD, symbol])?;
+                        continue;
+                    }
+                    [VmValue::TAG_SET_PATH, path] => {
+                        self.push_op(Op::SET_PATH, path, 2)?;
+                        continue;
+                    }
+                    _ => (self.resolve(val)?, None),
+                };
+                match value {
                     [VmValue::TAG_NATIVE_FN, func] => {
+                        let func = match rest {
+                            Some((path, offset)) => self.refine_native(func, path, offset)?,
+                            None => func,
+                        };
                         let desc = self.module.get_func(func)?;
-                        if desc.arity == 200 {
-                            self.push_op(Op::CALL_NATIVE, func, 6)?;
-                            return Ok((Op::LIT_PARAM, 0));
+                        if desc.arity == 0 {
+                            return Ok((Op::CALL_NATIVE, func));
                         } else {
-                            if desc.arity == 0 {
-                                return Ok((Op::CALL_NATIVE, func));
-                            } else {
-                                self.push_op(Op::CALL_NATIVE, func, desc.arity)?;
-                            }
+                            self.push_op(Op::CALL_NATIVE, func, desc.arity)?;
                         }
                     }
                     [VmValue::TAG_FUNC, desc] => {
+                        let desc = match rest {
+                            Some((path, offset)) => self.refine(desc, path, offset)?,
+                            None => desc,
+                        };
                         let [arity] = self.module.get_block::<1>(desc, 1)?;
                         if arity == 0 {
                             return Ok((Op::CALL_FUNC, desc));
@@ -783,6 +2343,12 @@ where
                         }
                     }
                     [VmValue::TAG_SET_WORD, sym] => self.push_op(Op::SET_WORD, sym, 2)?,
+                    // block literals are evaluated later in the scope they are written in
+                    [VmValue::TAG_BLOCK, block] if val == value => {
+                        let scope = self.env.peek().ok_or(MemoryError::StackUnderflow)?;
+                        self.scopes.insert(block, scope);
+                        self.push(value)?
+                    }
                     other => self.push(other)?,
                 }
             } else {
@@ -790,54 +2356,239 @@ where
                 if self.op_stack.is_empty()? {
                     return Err(CoreError::EndOfInput);
                 }
+                // loops iterate here without returning to `run`
+                self.use_fuel()?;
 
                 let (block, ip) = {
                     let [op, block, bp, ip] = self.op_stack.pop()?;
 
                     match op {
-                        Op::LEAVE_FUNC => {
-                            self.pop_context()?;
+                        Op::LEAVE_FUNC | Op::LEAVE_BLOCK => {
+                            self.env.pop::<2>()?;
                             self.leave(bp)?;
                             (block, ip)
                         }
-                        Op::LEAVE_BLOCK => {
+                        Op::TRY | Op::ATTEMPT | Op::CATCH => {
+                            self.env.pop::<2>()?;
                             self.leave(bp)?;
+                            let result = self.stack.pop::<2>()?;
+                            self.stack.pop::<2>()?; // saved env depth
+                            self.stack.push(result)?;
                             (block, ip)
                         }
                         Op::REDUCE => {
+                            self.env.pop::<2>()?;
                             let result = self.stack.pop_all(bp).ok_or(CoreError::InternalError)?;
-                            let block = self.module.heap.alloc_block(&result)?;
-                            self.stack.push([VmValue::TAG_BLOCK, block])?;
+                            let reduced = self.module.heap.alloc_block(result)?;
+                            self.stack.push([VmValue::TAG_BLOCK, reduced])?;
                             (block, ip)
                         }
                         Op::FOREACH => {
-                            self.stack.pop_all(bp).ok_or(CoreError::InternalError)?; // drop result
-                            let [_, i] = self.stack.pop()?;
-                            let [_, word, _, data, _, body] =
+                            self.leave(bp)?;
+                            let result = self.stack.pop::<2>()?;
+                            let [_, word, _, data, _, _body, _, i] =
                                 self.stack.peek().ok_or(MemoryError::StackUnderflow)?;
                             let index = i + 2;
                             if let Ok(value) = self.get_block(data, index) {
-                                let mut ctx = self.peek_context()?;
-                                ctx.put(word, value)?;
-                                self.push([VmValue::TAG_INT, index])?;
-                                self.op_stack
-                                    .push([Op::FOREACH, block, self.stack.len()?, ip])?;
-                                (body, Self::LEAVE_MARKER + 0)
+                                self.peek_context()?.put(word, value)?;
+                                self.next_iteration(op, block, ip, index)?
                             } else {
-                                self.stack.pop::<6>()?;
-                                self.pop_context()?;
+                                self.end_loop(op, result)?;
                                 (block, ip)
                             }
                         }
-                        _ => return Ok((op, block)),
-                    }
-                };
-
-                self.block = block;
-                self.ip = ip - Self::LEAVE_MARKER;
-            }
-        }
-    }
+                        Op::REPEAT => {
+                            self.leave(bp)?;
+                            let result = self.stack.pop::<2>()?;
+                            let [_, word, _, count, _, _body, _, i] =
+                                self.stack.peek().ok_or(MemoryError::StackUnderflow)?;
+                            let index = i + 1;
+                            if (index as i32) <= (count as i32) {
+                                self.peek_context()?.put(word, [VmValue::TAG_INT, index])?;
+                                self.next_iteration(op, block, ip, index)?
+                            } else {
+                                self.end_loop(op, result)?;
+                                (block, ip)
+                            }
+                        }
+                        Op::LOOP => {
+                            self.leave(bp)?;
+                            let result = self.stack.pop::<2>()?;
+                            let [_, _body, _, count] =
+                                self.stack.peek().ok_or(MemoryError::StackUnderflow)?;
+                            if count > 1 {
+                                self.next_iteration(op, block, ip, count - 1)?
+                            } else {
+                                self.end_loop(op, result)?;
+                                (block, ip)
+                            }
+                        }
+                        Op::WHILE => {
+                            self.leave(bp)?;
+                            let result = self.stack.pop::<2>()?;
+                            let [_, condition, _, body, tag, last, _, phase] =
+                                self.stack.peek().ok_or(MemoryError::StackUnderflow)?;
+                            if phase == 1 {
+                                // body done, keep its result and evaluate the condition again
+                                self.stack.pop::<4>()?;
+                                self.stack
+                                    .push([result[0], result[1], VmValue::TAG_INT, 0])?;
+                                self.op_stack
+                                    .push([Op::WHILE, block, self.stack.len()?, ip])?;
+                                self.swap_scope(condition)?;
+                                (condition, Self::LEAVE_MARKER)
+                            } else if VmValue::try_from(result)?.is_truthy() {
+                                self.stack.pop::<2>()?;
+                                self.stack.push([VmValue::TAG_INT, 1])?;
+                                self.op_stack
+                                    .push([Op::WHILE, block, self.stack.len()?, ip])?;
+                                self.swap_scope(body)?;
+                                (body, Self::LEAVE_MARKER)
+                            } else {
+                                self.end_loop(op, [tag, last])?;
+                                (block, ip)
+                            }
+                        }
+                        Op::INFIX => return Err(CoreError::MissingOperand("right")),
+                        _ => return Ok((op, block)),
+                    }
+                };
+
+                self.block = block;
+                self.ip = ip - Self::LEAVE_MARKER;
+            }
+        }
+    }
+
+    /// Start the next iteration of a loop keeping its state on the stack as
+    /// `[.. body counter]`: replace the counter and evaluate the body again. Returns
+    /// the position to continue at.
+    fn next_iteration(
+        &mut self,
+        op: Word,
+        block: Offset,
+        ip: Offset,
+        counter: Word,
+    ) -> Result<(Offset, Offset), MemoryError> {
+        self.stack.pop::<2>()?;
+        let [_, body] = self.stack.peek().ok_or(MemoryError::StackUnderflow)?;
+        self.stack.push([VmValue::TAG_INT, counter])?;
+        self.op_stack.push([op, block, self.stack.len()?, ip])?;
+        Ok((body, Self::LEAVE_MARKER))
+    }
+
+    /// Leave the scope of the block just evaluated for the scope of `block`, evaluated next
+    /// under the same frame.
+    fn swap_scope(&mut self, block: Offset) -> Result<(), MemoryError> {
+        self.env.pop::<2>()?;
+        let scope = self.scope_of(block)?;
+        self.env.push(scope)
+    }
+
+    /// Native called by `value` if it is an operator word.
+    fn operator(&self, value: MemValue) -> Option<Word> {
+        match value {
+            [VmValue::TAG_WORD, symbol] => self
+                .module
+                .operators
+                .iter()
+                .find(|(op, _)| *op == symbol)
+                .map(|(_, func)| *func),
+            _ => None,
+        }
+    }
+
+    /// Native called by the operator at the current position, if there is one.
+    fn next_operator(&self) -> Option<Word> {
+        let value = self.get_block::<2>(self.block, self.ip).ok()?;
+        self.operator(value)
+    }
+
+    /// Call `func` with the value on top of the stack as its first argument. The value has
+    /// to be evaluated in the current frame, operators can't take arguments of a caller,
+    /// nor the left operand of a pending operator.
+    fn push_infix(&mut self, func: Word) -> Result<(), CoreError> {
+        let sp = self.stack.len()?;
+        let base = match self.op_stack.peek() {
+            Some([Op::INFIX, _, bp, _]) => bp + 2,
+            Some([_, _, bp, _]) => bp,
+            None => 0,
+        };
+        if sp < base + 2 {
+            return Err(CoreError::MissingOperand("left"));
+        }
+        self.op_stack.push([Op::INFIX, func, sp - 2, 4])?;
+        Ok(())
+    }
+
+    /// Collect argument `index` of a pending native call if it is not evaluated, i.e. it
+    /// is taken literally or by its value. Returns `false` if evaluation should supply it.
+    fn take_arg(&mut self, func: Word, index: usize) -> Result<bool, CoreError> {
+        let kind = match self.module.get_func(func)?.params.get(index) {
+            Some(param) => param.kind,
+            None => return Ok(false),
+        };
+        if kind == ArgKind::Eval {
+            return Ok(false);
+        }
+        let value = self
+            .get_block::<2>(self.block, self.ip)
+            .map_err(|_| CoreError::UnexpectedEndOfBlock)?;
+        self.ip += 2;
+        let value = match value {
+            [VmValue::TAG_WORD, symbol] if kind == ArgKind::Get => self.find_word(symbol)?,
+            _ => value,
+        };
+        self.stack.push(value)?;
+        Ok(true)
+    }
+
+    /// Check the arguments on top of the stack against the parameters of a native function.
+    fn check_args(&self, func: Word) -> Result<(), CoreError> {
+        let desc = self.module.get_func(func)?;
+        let sp = self.stack.len()?;
+        let bp = sp
+            .checked_sub(desc.arity)
+            .ok_or(MemoryError::StackUnderflow)?;
+        let args = self.stack.peek_all(bp).ok_or(MemoryError::StackUnderflow)?;
+        for (param, arg) in desc.params.iter().zip(args.chunks_exact(2)) {
+            if !param.types.is_empty() && !param.types.contains(&arg[0]) {
+                return Err(CoreError::InvalidArgument {
+                    native: self.module.get_symbol(desc.symbol)?,
+                    param: param.name.into(),
+                    received: VmValue::type_name(arg[0]),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Check whether a function called now is in tail position of the current function.
+    ///
+    /// That is the case when the current block is exhausted and only `LEAVE_BLOCK` frames
+    /// with exhausted blocks separate it from the `LEAVE_FUNC` frame of the function. Returns
+    /// the op stack length up to and including that frame, and the frame's base pointer.
+    fn tail_call(&self) -> Result<Option<(Offset, Offset)>, MemoryError> {
+        let frames = self
+            .op_stack
+            .peek_all(0)
+            .ok_or(MemoryError::StackUnderflow)?;
+        let (mut block, mut ip) = (self.block, self.ip);
+        for (i, frame) in frames.chunks_exact(4).enumerate().rev() {
+            if (ip as usize) < self.get_block_len(block)? {
+                return Ok(None);
+            }
+            match frame {
+                [Op::LEAVE_FUNC, _, bp, _] => return Ok(Some(((i as Offset + 1) * 4, *bp))),
+                [Op::LEAVE_BLOCK, return_block, _, return_ip] => {
+                    block = *return_block;
+                    ip = return_ip - Self::LEAVE_MARKER;
+                }
+                _ => return Ok(None),
+            }
+        }
+        Ok(None)
+    }
 
     fn leave(&mut self, bp: Offset) -> Result<(), MemoryError> {
         let sp = self.stack.len()?;
@@ -858,24 +2609,287 @@ where
         Ok(())
     }
 
+    /// Collect garbage, treating this process' stacks as additional roots.
+    ///
+    /// Blocks move during collection, so offsets held outside of the process (including
+    /// values returned earlier by [`Module::alloc_value`] or [`Module::parse`]) become
+    /// invalid unless pinned with [`Module::pin`]. Evaluation calls this automatically
+    /// between operations once the heap usage crosses a threshold.
+    pub fn collect(&mut self) -> Result<(), CoreError> {
+        let stack = self
+            .stack
+            .peek_all_mut(0)
+            .ok_or(MemoryError::StackUnderflow)?;
+        let op_stack = self
+            .op_stack
+            .peek_all_mut(0)
+            .ok_or(MemoryError::StackUnderflow)?;
+        let env = self
+            .env
+            .peek_all_mut(0)
+            .ok_or(MemoryError::StackUnderflow)?;
+        let block = &mut self.block;
+        let breakpoints = &mut self.breakpoints;
+        let scopes = &mut self.scopes;
+
+        self.module.collect_with(|copier| {
+            *block = copier.copy(*block, Kind::Values)?;
+            for breakpoint in breakpoints.iter_mut() {
+                if let Breakpoint::Block(block, _) = breakpoint {
+                    *block = copier.copy(*block, Kind::Values)?;
+                }
+            }
+            copier.values(stack)?;
+            // pending function calls and saved return blocks reference the heap
+            for frame in op_stack.chunks_exact_mut(4) {
+                if matches!(
+                    frame[0],
+                    Op::CALL_FUNC
+                        | Op::LEAVE_BLOCK
+                        | Op::LEAVE_FUNC
+                        | Op::REDUCE
+                        | Op::FOREACH
+                        | Op::WHILE
+                        | Op::LOOP
+                        | Op::REPEAT
+                        | Op::TRY
+                        | Op::ATTEMPT
+                        | Op::CATCH
+                        | Op::SET_PATH
+                ) {
+                    frame[1] = copier.copy(frame[1], Kind::Values)?;
+                } else if frame[0] == Op::CONTEXT {
+                    frame[1] = copier.copy(frame[1], Kind::Context)?;
+                }
+            }
+            for entry in env.chunks_exact_mut(2) {
+                entry[0] = copier.copy(entry[0], Kind::Context)?;
+                if entry[1] != 0 {
+                    entry[1] = copier.copy(entry[1], Kind::Values)?;
+                }
+            }
+            // scopes are kept for as long as their blocks are, which they may keep alive
+            let mut pending: Vec<_> = scopes.drain().collect();
+            loop {
+                copier.trace()?;
+                let (live, dead): (Vec<_>, Vec<_>) = pending
+                    .into_iter()
+                    .partition(|(block, _)| copier.forwarded(*block).is_some());
+                if live.is_empty() {
+                    return Ok(());
+                }
+                pending = dead;
+                for (block, [ctx, parent]) in live {
+                    let block = copier.forwarded(block).ok_or(MemoryError::OutOfBounds)?;
+                    let ctx = copier.copy(ctx, Kind::Context)?;
+                    let parent = match parent {
+                        0 => 0,
+                        parent => copier.copy(parent, Kind::Values)?,
+                    };
+                    scopes.insert(block, [ctx, parent]);
+                }
+            }
+        })
+    }
+
     pub fn eval(&mut self) -> Result<VmValue, CoreError> {
+        self.paused = false;
+        match self.run(Mode::Eval)? {
+            Stop::Done(result) => Ok(result),
+            Stop::Paused => Err(CoreError::InternalError),
+        }
+    }
+
+    /// Take one unit of fuel. Checked between steps and before a block is left, where
+    /// the process can be resumed.
+    fn use_fuel(&mut self) -> Result<(), CoreError> {
+        if let Some(fuel) = self.fuel.as_mut() {
+            if *fuel == 0 {
+                return Err(CoreError::OutOfFuel);
+            }
+            *fuel -= 1;
+        }
+        Ok(())
+    }
+
+    fn run(&mut self, mode: Mode) -> Result<Stop, CoreError> {
+        self.mode = mode;
         loop {
-            match self.next_op() {
-                Ok((op, word)) => self.do_op(op, word)?,
+            self.use_fuel()?;
+            if self.module.needs_gc()? {
+                self.collect()?;
+            }
+            let result = match self.next_op() {
+                Ok((Op::PAUSE, _)) => return Ok(Stop::Paused),
+                Ok((op, word)) => self
+                    .do_op(op, word)
+                    .map_err(|error| (error, self.failing_word(op, word))),
+                // can't be caught, the process is resumed from where it stopped
+                Err(CoreError::OutOfFuel) => return Err(CoreError::OutOfFuel),
                 Err(CoreError::EndOfInput) => {
-                    if self.stack.is_empty()? {
-                        return [VmValue::TAG_NONE, 0].try_into();
+                    let result = if self.stack.is_empty()? {
+                        [VmValue::TAG_NONE, 0]
                     } else {
-                        let result = self.stack.pop()?;
-                        return result.try_into();
-                    }
+                        self.stack.pop()?
+                    };
+                    return result.try_into().map(Stop::Done);
                 }
-                Err(error) => return Err(error),
+                Err(error) => Err((error, self.current_word())),
+            };
+            if let Err((error, word)) = result {
+                self.recover(error, word)?;
             }
         }
     }
 }
 
+// D E B U G G E R
+
+impl<T> Exec<'_, T>
+where
+    T: Storage,
+{
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+    }
+
+    pub fn remove_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.retain(|bp| *bp != breakpoint);
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    /// Run until the next value is about to be evaluated, in any block.
+    pub fn step(&mut self) -> Result<Stop, CoreError> {
+        self.run(Mode::Step)
+    }
+
+    /// Like [`Exec::step`], but run through blocks and functions entered on the way.
+    pub fn step_over(&mut self) -> Result<Stop, CoreError> {
+        let depth = self.depth()?;
+        self.run(Mode::StepOver(depth))
+    }
+
+    /// Run until a breakpoint is hit or evaluation finishes.
+    pub fn resume(&mut self) -> Result<Stop, CoreError> {
+        self.run(Mode::Resume)
+    }
+
+    /// Block being evaluated and the index of the value to be evaluated next.
+    pub fn position(&self) -> Result<(Value, usize), CoreError> {
+        let block = self.to_value(VmValue::Block(self.block))?;
+        Ok((block, (self.ip / 2) as usize))
+    }
+
+    /// Values on the stack, bottom first.
+    pub fn stack_values(&self) -> Result<Vec<Value>, CoreError> {
+        let stack = self.stack.peek_all(0).ok_or(MemoryError::StackUnderflow)?;
+        stack
+            .chunks_exact(2)
+            .map(|pair| self.to_value(VmValue::from_tag_data(pair[0], pair[1])?))
+            .collect()
+    }
+
+    /// Op stack frames, outermost first. Blocks being evaluated show up with the position
+    /// to return to, pending operations with the word and number of arguments they wait for.
+    pub fn frames(&self) -> Result<Vec<Value>, CoreError> {
+        let frames = self
+            .op_stack
+            .peek_all(0)
+            .ok_or(MemoryError::StackUnderflow)?;
+        frames
+            .chunks_exact(4)
+            .map(|frame| {
+                let [op, word, _, ip] = [frame[0], frame[1], frame[2], frame[3]];
+                let name = Value::word(Op::name(op));
+                if ip >= Self::LEAVE_MARKER {
+                    let block = self.to_value(VmValue::Block(word))?;
+                    let index = Value::int(((ip - Self::LEAVE_MARKER) / 2) as i32);
+                    Ok(Value::context([
+                        ("op", name),
+                        ("block", block),
+                        ("index", index),
+                    ]))
+                } else {
+                    let word = match op {
+                        Op::CALL_NATIVE | Op::INFIX => {
+                            let symbol = self.module.get_func(word)?.symbol;
+                            Value::Word(self.module.get_symbol(symbol)?)
+                        }
+                        Op::SET_WORD => Value::SetWord(self.module.get_symbol(word)?),
+                        Op::SET_PATH => self.to_value(VmValue::SetPath(word))?,
+                        _ => Value::None,
+                    };
+                    let args = Value::int((ip / 2) as i32);
+                    Ok(Value::context([
+                        ("op", name),
+                        ("word", word),
+                        ("args", args),
+                    ]))
+                }
+            })
+            .collect()
+    }
+
+    /// Contexts words are looked up in, outermost (system words) first. Natives are left out.
+    pub fn env_chain(&self) -> Result<Vec<Value>, CoreError> {
+        self.visible_contexts()?
+            .into_iter()
+            .rev()
+            .map(|addr| {
+                let context = self.module.heap.get_block(addr).map(Context::new)?;
+                let mut pairs = Vec::new();
+                for (symbol, [tag, data]) in &context {
+                    if tag != VmValue::TAG_NATIVE_FN {
+                        let value = self.to_value(VmValue::from_tag_data(tag, data)?)?;
+                        pairs.push((self.module.get_symbol(symbol)?, value));
+                    }
+                }
+                Ok(Value::Context(pairs.into_boxed_slice()))
+            })
+            .collect()
+    }
+
+    /// Number of blocks being evaluated below the current one.
+    fn depth(&self) -> Result<usize, MemoryError> {
+        let frames = self
+            .op_stack
+            .peek_all(0)
+            .ok_or(MemoryError::StackUnderflow)?;
+        Ok(frames
+            .chunks_exact(4)
+            .filter(|frame| frame[3] >= Self::LEAVE_MARKER)
+            .count())
+    }
+
+    /// Called before `value` at the current position is evaluated.
+    fn should_pause(&mut self, value: MemValue) -> Result<bool, MemoryError> {
+        // leaving the position we stopped at last time
+        if std::mem::take(&mut self.paused) {
+            return Ok(false);
+        }
+        let pause = match self.mode {
+            Mode::Eval => false,
+            Mode::Step => true,
+            Mode::StepOver(depth) => self.depth()? <= depth || self.at_breakpoint(value),
+            Mode::Resume => self.at_breakpoint(value),
+        };
+        self.paused = pause;
+        Ok(pause)
+    }
+
+    fn at_breakpoint(&self, value: MemValue) -> bool {
+        self.breakpoints.iter().any(|breakpoint| match *breakpoint {
+            Breakpoint::Word(symbol) => value == [VmValue::TAG_WORD, symbol],
+            Breakpoint::Block(block, ip) => block == self.block && ip == self.ip,
+        })
+    }
+}
+
 // P A R S E  C O L L E C T O R
 
 struct ParseCollector<'a, T> {
@@ -896,7 +2910,7 @@ impl<'a, T> ParseCollector<'a, T> {
 
 impl<T> Collector for ParseCollector<'_, T>
 where
-    T: AsMut<[Word]>,
+    T: Storage,
 {
     type Error = MemoryError;
 
@@ -912,13 +2926,57 @@ where
                 WordKind::Word => VmValue::Word(id),
                 WordKind::SetWord => VmValue::SetWord(id),
                 WordKind::GetWord => VmValue::GetWord(id),
+                WordKind::LitWord => VmValue::LitWord(id),
+                WordKind::Refinement => VmValue::Refinement(id),
             };
             self.parse.push(value.vm_repr())
         })
     }
 
-    fn integer(&mut self, value: i32) -> Result<(), MemoryError> {
-        self.parse.push([VmValue::TAG_INT, value as u32])
+    fn integer(&mut self, value: i64) -> Result<(), MemoryError> {
+        match i32::try_from(value) {
+            Ok(value) => self.parse.push([VmValue::TAG_INT, value as u32]),
+            Err(_) => self
+                .module
+                .alloc_int64(value)
+                .and_then(|offset| self.parse.push([VmValue::TAG_INT64, offset])),
+        }
+    }
+
+    fn decimal(&mut self, value: f64) -> Result<(), MemoryError> {
+        self.module
+            .alloc_decimal(value)
+            .and_then(|offset| self.parse.push([VmValue::TAG_DECIMAL, offset]))
+    }
+
+    fn binary(&mut self, bytes: &[u8]) -> Result<(), MemoryError> {
+        self.module
+            .alloc_binary(bytes)
+            .and_then(|offset| self.parse.push([VmValue::TAG_BINARY, offset]))
+    }
+
+    fn date(&mut self, millis: i64) -> Result<(), MemoryError> {
+        self.module
+            .alloc_date(millis)
+            .and_then(|offset| self.parse.push([VmValue::TAG_DATE, offset]))
+    }
+
+    fn time(&mut self, millis: i64) -> Result<(), MemoryError> {
+        self.module
+            .alloc_time(millis)
+            .and_then(|offset| self.parse.push([VmValue::TAG_TIME, offset]))
+    }
+
+    fn file(&mut self, path: &str) -> Result<(), MemoryError> {
+        self.module
+            .alloc_string(path)
+            .and_then(|offset| self.parse.push([VmValue::TAG_FILE, offset]))
+    }
+
+    fn url(&mut self, url: &str) -> Result<(), MemoryError> {
+        self.module
+            .alloc_string(url)
+            .and_then(|offset| self.parse.push([VmValue::TAG_URL, offset]))
     }
 
     fn begin_block(&mut self) -> Result<(), MemoryError> {
@@ -936,11 +2994,16 @@ where
         self.parse.len().and_then(|len| self.ops.push([len]))
     }
 
-    fn end_path(&mut self) -> Result<(), Self::Error> {
+    fn end_path(&mut self, kind: WordKind) -> Result<(), Self::Error> {
         let [bp] = self.ops.pop()?;
         let block_data = self.parse.pop_all(bp).ok_or(MemoryError::UnexpectedError)?;
         let offset = self.module.heap.alloc_block(block_data)?;
-        self.parse.push([VmValue::TAG_PATH, offset])
+        let tag = match kind {
+            WordKind::SetWord => VmValue::TAG_SET_PATH,
+            WordKind::GetWord => VmValue::TAG_GET_PATH,
+            _ => VmValue::TAG_PATH,
+        };
+        self.parse.push([tag, offset])
     }
 }
 
@@ -967,6 +3030,7 @@ pub fn eval(module: &mut Exec<&mut [Word]>) -> Result<VmValue, CoreError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mem::Growable;
     use crate::rebel;
     use crate::value::Value;
 
@@ -1765,6 +3829,16 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_either_truthy() -> Result<(), CoreError> {
+        // like `if`, any value but none and false picks the first block
+        assert_eq!(VmValue::Int(2), eval("either none [1] [2]")?);
+        assert_eq!(VmValue::Int(1), eval("either 0 [1] [2]")?);
+        let input = "f: func [x /twice] [either twice [x * 2] [x]] add f 1 f/twice 5";
+        assert_eq!(VmValue::Int(11), eval(input)?);
+        Ok(())
+    }
+
     #[test]
     fn test_do_1() -> Result<(), CoreError> {
         let input = "do [add 1 2]";
@@ -1875,7 +3949,7 @@ mod tests {
         // Stack should now have one value (the result)
         assert_eq!(
             exec.stack.len().unwrap(),
-            1 * 2,
+            2,
             "Stack should have 1 value after do_op"
         );
 
@@ -2013,7 +4087,7 @@ mod tests {
         // Now the stack should have 1 value: 6 (the final result)
         assert_eq!(
             exec.stack.len().unwrap(),
-            1 * 2,
+            2,
             "Stack should have 1 value after outer add"
         );
 
@@ -2207,12 +4281,889 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_path_segments() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        eval_code(
+            &mut module,
+            "data: [10 [20 30] \"abc\"] obj: context [inner: context [x: 1] items: [1 2]]",
+        )?;
+        let cases = [
+            ("data/2", Value::block([Value::int(20), Value::int(30)])),
+            ("data/2/2", Value::int(30)),
+            ("data/3/2", Value::String("b".into())),
+            ("data/4", Value::None),
+            ("obj/inner/x", Value::int(1)),
+            ("obj/items/2", Value::int(2)),
+            ("f: func [a] [a/items/1] f obj", Value::int(1)),
+            ("obj/inner/x: 5 obj/inner/x", Value::int(5)),
+            (
+                "obj/items/2: 7 obj/items",
+                Value::block([Value::int(1), Value::int(7)]),
+            ),
+            ("data/1: add 1 2", Value::int(3)),
+            ("data/1", Value::int(3)),
+            (
+                "f: func [a] [a/x: 8] f obj/inner obj/inner/x",
+                Value::int(8),
+            ),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(eval_code(&mut module, code)?, expected, "{code}");
+        }
+
+        let result = eval_code(&mut module, "data/0");
+        assert!(matches!(result, Err(CoreError::BoundsCheckFailed)));
+        let result = eval_code(&mut module, "data/5: 1");
+        assert!(matches!(result, Err(CoreError::BoundsCheckFailed)));
+        let result = eval_code(&mut module, "obj/missing: 1");
+        assert!(matches!(
+            result,
+            Err(CoreError::MemoryError(MemoryError::WordNotFound))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_path() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        eval_code(&mut module, "obj: context [f: func [] [42]] g: :obj/f")?;
+        let block = module.parse(":obj/f")?;
+        assert!(matches!(module.eval(block)?, VmValue::Func(_)));
+        assert_eq!(eval_code(&mut module, "obj/f")?, Value::int(42));
+        assert_eq!(eval_code(&mut module, "g")?, Value::int(42));
+
+        let block = module.parse("a/b: :c :d/e [x/1:]")?;
+        let path = |segments: [Value; 2]| Box::from(segments);
+        assert_eq!(
+            module.to_value(block)?,
+            Value::block([
+                Value::SetPath(path([Value::word("a"), Value::word("b")])),
+                Value::GetWord("c".into()),
+                Value::GetPath(path([Value::word("d"), Value::word("e")])),
+                Value::block([Value::SetPath(path([Value::word("x"), Value::int(1)]))]),
+            ])
+        );
+        Ok(())
+    }
+
+    /// Values that contain themselves can't be formed, molded or compared.
+    #[test]
+    fn test_cyclic_values() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        let cases = [
+            "a: [1 2] a/1: a form a",
+            "a: [1 2] a/1: a b: [1 2] b/1: b equal? a b",
+            "c: context [x: 1] c/x: c mold c",
+        ];
+        for code in cases {
+            let result = eval_code(&mut module, code);
+            assert!(matches!(result, Err(CoreError::CyclicValue)), "{code}");
+        }
+        let result = eval_code(&mut module, "try [mold a]")?;
+        assert_eq!(result.get("kind"), Some(&Value::word("cyclic-value")));
+        // a block may hold another one more than once
+        let result = eval_code(&mut module, "b: [1] form reduce [b b]")?;
+        assert_eq!(result, Value::string("1 1"));
+        Ok(())
+    }
+
     /// Helper function to evaluate code and convert the result to a Value
     fn eval_code(module: &mut Module<Box<[u32]>>, code: &str) -> Result<Value, CoreError> {
         let block = module.parse(code)?;
         let result = module.eval(block)?;
         module.to_value(result)
     }
+
+    /// Binaries over the threshold live in the blob store, the heap only has their hash.
+    #[test]
+    fn test_blob_binary() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        let bytes: Vec<u8> = (0..4096).map(|i| i as u8).collect();
+        let offset = module.alloc_binary(&bytes)?;
+        assert_eq!(module.heap.get_block(offset)?.len(), 9);
+        let value = module.to_value(VmValue::Binary(offset))?;
+        assert_eq!(value, Value::binary(bytes));
+
+        let short = module.alloc_binary(&[1, 2, 3])?;
+        assert_eq!(module.heap.get_block(short)?, &[3, 0x030201]);
+
+        let cases = [
+            (
+                "big: to-binary [] repeat i 1500 [append big 7] length? big",
+                Value::int(1500),
+            ),
+            ("append big 8 last big", Value::int(8)),
+            ("length? copy/part big 10", Value::int(10)),
+            ("first skip big 1499", Value::int(7)),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(eval_code(&mut module, code)?, expected, "{code}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_url_path() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        let cases = [
+            ("u: ssh://user@host:22/path u/host", Value::string("host")),
+            ("u/port", Value::int(22)),
+            ("u/user", Value::string("user")),
+            ("u/path", Value::string("/path")),
+            ("u/pass", Value::None),
+            (
+                "f: func [x [url!]] [x/host] f ftp://example.com",
+                Value::string("example.com"),
+            ),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(eval_code(&mut module, code)?, expected, "{code}");
+        }
+        assert!(eval_code(&mut module, "u/query").is_err());
+        Ok(())
+    }
+
+    /// Explicit collection drops unreachable blocks and keeps everything bound in system words.
+    #[test]
+    fn test_collect() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        eval_code(
+            &mut module,
+            r#"data: [1 "two" [3]] ctx: context [a: 42 b: data]"#,
+        )?;
+
+        let before = module.heap.len()?;
+        module.collect()?;
+        assert!(module.heap.len()? < before);
+
+        let data = Value::block([
+            Value::int(1),
+            Value::string("two"),
+            Value::block([Value::int(3)]),
+        ]);
+        let result = eval_code(&mut module, "reduce [data ctx/a ctx/b]")?;
+        assert_eq!(result, Value::block([data.clone(), Value::int(42), data]));
+        Ok(())
+    }
+
+    /// The symbol table grows past its initial size, and words defined before keep their
+    /// bindings.
+    #[test]
+    fn test_many_words() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x40000].into_boxed_slice())?;
+        for i in 0..600 {
+            eval_code(&mut module, &format!("w{i}: {i}"))?;
+        }
+        assert_eq!(eval_code(&mut module, "add w1 w599")?, Value::int(600));
+        module.collect()?;
+        assert_eq!(
+            eval_code(&mut module, "w-last: add w0 w300")?,
+            Value::int(300)
+        );
+        Ok(())
+    }
+
+    /// A module on growable storage starts small and extends its heap as needed,
+    /// without invalidating values allocated earlier.
+    #[test]
+    fn test_growable_module() -> Result<(), CoreError> {
+        let mut module = Module::init(Growable::new(0x100, 0x10000))?;
+        let data = module.alloc_value(&Value::string("kept"))?;
+
+        let items = (1..=30)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        for _ in 0..64 {
+            module.parse(&items)?;
+        }
+
+        assert!(module.heap.capacity() > 0x1000);
+        assert_eq!(module.to_value(data)?, Value::string("kept"));
+        Ok(())
+    }
+
+    /// Garbage produced by a running process is reclaimed between operations, so the
+    /// process can allocate much more than the heap holds.
+    #[test]
+    fn test_collect_during_eval() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x2000].into_boxed_slice())?;
+        let free = module.heap.capacity() - module.heap.len()? as usize;
+
+        let items = (1..=16)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let input = format!(
+            "sum: 0 foreach x [{items}] [foreach y [{items}] [reduce [x y x y x y x y x y] sum: add sum y]] sum"
+        );
+        let result = eval_code(&mut module, &input)?;
+
+        // every iteration leaves a 21-word block behind
+        assert!(16 * 16 * 21 > free);
+        assert_eq!(result, Value::int(16 * 136));
+        Ok(())
+    }
+
+    /// Values the host holds across evaluation stay valid when pinned, even though the
+    /// process collects and moves blocks.
+    #[test]
+    fn test_pinned_across_eval() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x2000].into_boxed_slice())?;
+        let unreachable = module.alloc_value(&Value::block((0..64).map(Value::int)))?;
+        let data = module.alloc_value(&Value::block([Value::string("kept"), Value::int(7)]))?;
+        let pin = module.pin(data);
+        let dropped = module.pin(unreachable);
+        module.unpin(dropped);
+
+        let items = (1..=16)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let input =
+            format!("foreach x [{items}] [foreach y [{items}] [reduce [x y x y x y x y x y]]]");
+        eval_code(&mut module, &input)?;
+
+        // the block moved
+        assert_ne!(module.pinned(pin)?, data);
+        let data = module.pinned(pin)?;
+        assert_eq!(
+            module.to_value(data)?,
+            Value::block([Value::string("kept"), Value::int(7)])
+        );
+        assert_eq!(module.pinned(dropped)?, VmValue::None);
+        Ok(())
+    }
+
+    /// A failing native inside `try` yields an error value describing the failure.
+    #[test]
+    fn test_try_error_value() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+
+        let error = eval_code(&mut module, r#"e: try [add 1 "one"]"#)?;
+        assert_eq!(error.get("kind"), Some(&Value::word("bad-arguments")));
+        assert_eq!(
+            error.get("message"),
+            Some(&Value::string("add does not accept string! as b"))
+        );
+        assert_eq!(error.get("word"), Some(&Value::word("add")));
+
+        assert_eq!(
+            eval_code(&mut module, "e/kind")?,
+            Value::word("bad-arguments")
+        );
+        assert_eq!(eval_code(&mut module, "error? e")?, Value::Bool(true));
+        assert_eq!(eval_code(&mut module, "try [add 1 2]")?, Value::int(3));
+
+        let error = eval_code(&mut module, "try [add 1 missing]")?;
+        assert_eq!(error.get("kind"), Some(&Value::word("word-not-found")));
+        assert_eq!(error.get("word"), Some(&Value::word("missing")));
+        Ok(())
+    }
+
+    /// Unwinding drops the stack, pending ops and contexts of everything inside the handler.
+    #[test]
+    fn test_try_unwinds() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+
+        let input = r#"
+            f: func [x] [add x "one"]
+            g: func [y] [add y f y]
+            r: either error? try [g 5] [1] [2]
+            reduce [r attempt [x] attempt [add 40 2]]
+        "#;
+        let result = eval_code(&mut module, input)?;
+        assert_eq!(
+            result,
+            Value::block([Value::int(1), Value::None, Value::int(42)])
+        );
+
+        // errors outside of any handler still abort evaluation
+        let result = eval_code(&mut module, "add 1 attempt [g 5]");
+        assert!(matches!(
+            result,
+            Err(CoreError::InvalidArgument { native, param, received: "none!" })
+                if native == "add" && param == "b"
+        ));
+        Ok(())
+    }
+
+    /// `throw` exits to the innermost `catch`; errors pass through `catch` and are rethrown as is.
+    #[test]
+    fn test_throw_catch() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+
+        assert_eq!(
+            eval_code(&mut module, "catch [add 1 throw 5]")?,
+            Value::int(5)
+        );
+        assert_eq!(
+            eval_code(&mut module, "catch [try [foreach x [1 2 3] [throw x]]]")?,
+            Value::int(1)
+        );
+
+        let error = eval_code(&mut module, "try [throw 5]")?;
+        assert_eq!(error.get("kind"), Some(&Value::word("no-catch")));
+
+        let error = eval_code(&mut module, r#"try [catch [add 1 "one"]]"#)?;
+        assert_eq!(error.get("kind"), Some(&Value::word("bad-arguments")));
+
+        let error = eval_code(&mut module, r#"e: try [add 1 "one"] try [throw e]"#)?;
+        assert_eq!(error.get("kind"), Some(&Value::word("bad-arguments")));
+        assert_eq!(error.get("word"), Some(&Value::word("add")));
+
+        let error = eval_code(&mut module, r#"try [throw make-error "boom"]"#)?;
+        assert_eq!(error.get("kind"), Some(&Value::word("user")));
+        assert_eq!(error.get("message"), Some(&Value::string("boom")));
+
+        let result = eval_code(&mut module, r#"throw make-error "boom""#);
+        assert!(matches!(result, Err(CoreError::Raised { kind, .. }) if kind == "user"));
+        Ok(())
+    }
+
+    /// Running out of fuel stops the process without unwinding, even inside `attempt`.
+    #[test]
+    fn test_out_of_fuel() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        eval_code(&mut module, "f: func [] [f]")?;
+
+        let block = module.parse("attempt [f]")?;
+        let mut process = module.new_process_with_limits(block, Limits { fuel: Some(100) })?;
+        assert!(matches!(process.eval(), Err(CoreError::OutOfFuel)));
+        assert_eq!(process.fuel(), Some(0));
+        Ok(())
+    }
+
+    /// Loops with empty bodies iterate without returning to `run` and still use fuel.
+    #[test]
+    fn test_loops_out_of_fuel() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        for code in [
+            "loop 2000000000 []",
+            "repeat i 2000000000 []",
+            "attempt [loop 2000000000 []]",
+        ] {
+            let block = module.parse(code)?;
+            let mut process = module.new_process_with_limits(block, Limits { fuel: Some(1000) })?;
+            assert!(matches!(process.eval(), Err(CoreError::OutOfFuel)));
+        }
+        Ok(())
+    }
+
+    /// A process that ran out of fuel continues where it stopped once topped up.
+    #[test]
+    fn test_resume_after_out_of_fuel() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        let block = module.parse("sum: 0 foreach x [1 2 3 4 5 6 7 8] [sum: add sum x] sum")?;
+        let mut process = module.new_process_with_limits(block, Limits { fuel: Some(5) })?;
+
+        let mut refills = 0;
+        let result = loop {
+            match process.eval() {
+                Err(CoreError::OutOfFuel) => {
+                    refills += 1;
+                    process.add_fuel(5);
+                }
+                result => break result?,
+            }
+        };
+
+        assert!(refills > 1);
+        assert_eq!(result, VmValue::Int(36));
+        Ok(())
+    }
+
+    /// Single stepping stops before every value, in every block entered on the way.
+    #[test]
+    fn test_step() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        let block = module.parse("f: func [a] [add a 1] f 5")?;
+        let mut process = module.new_process(block)?;
+
+        let mut positions = Vec::new();
+        let result = loop {
+            match process.step()? {
+                Stop::Paused => positions.push(process.position()?.1),
+                Stop::Done(result) => break result,
+            }
+        };
+
+        // f: func [a] [add a 1] f 5, then add a 1 in the body
+        assert_eq!(positions, vec![0, 1, 2, 3, 4, 5, 0, 1, 2]);
+        assert_eq!(result, VmValue::Int(6));
+        Ok(())
+    }
+
+    /// Stepping over a call runs the function body without stopping in it.
+    #[test]
+    fn test_step_over() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        let block = module.parse("f: func [a] [add a 1] x: f 5 add x x")?;
+        let mut process = module.new_process(block)?;
+
+        let mut positions = Vec::new();
+        let result = loop {
+            match process.step_over()? {
+                Stop::Paused => positions.push(process.position()?.1),
+                Stop::Done(result) => break result,
+            }
+        };
+
+        assert_eq!(positions, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(result, VmValue::Int(12));
+        Ok(())
+    }
+
+    /// Breakpoints on a word stop inside the function using it, with its arguments in the env.
+    #[test]
+    fn test_breakpoint_inspect() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        let block = module.parse("f: func [a] [add a 1] add 100 f 5")?;
+        let add = module.get_or_insert_symbol("add")?;
+        let mut process = module.new_process(block)?;
+        process.add_breakpoint(Breakpoint::Word(add));
+
+        // first hit is the `add` at the top level
+        assert_eq!(process.resume()?, Stop::Paused);
+        assert_eq!(process.position()?.1, 4);
+
+        assert_eq!(process.resume()?, Stop::Paused);
+        let (body, index) = process.position()?;
+        assert_eq!(body, rebel!([add a 1]));
+        assert_eq!(index, 0);
+        assert_eq!(process.stack_values()?.last(), Some(&Value::int(100)));
+
+        let frames = process.frames()?;
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].get("word"), Some(&Value::word("add")));
+        assert_eq!(frames[0].get("args"), Some(&Value::int(2)));
+        assert_eq!(frames[1].get("op"), Some(&Value::word("leave-func")));
+
+        let env = process.env_chain()?;
+        assert_eq!(env.last(), Some(&Value::context([("a", Value::int(5))])));
+
+        process.remove_breakpoint(Breakpoint::Word(add));
+        assert_eq!(process.resume()?, Stop::Done(VmValue::Int(106)));
+        Ok(())
+    }
+
+    /// Breakpoints on a block offset stop right before the value at that offset.
+    #[test]
+    fn test_breakpoint_block() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        let block = module.parse("x: 1 x: add x 1 x: add x 1")?;
+        let VmValue::Block(offset) = block else {
+            panic!("expected a block");
+        };
+        let mut process = module.new_process(block)?;
+        process.add_breakpoint(Breakpoint::Block(offset, 12));
+
+        assert_eq!(process.resume()?, Stop::Paused);
+        assert_eq!(process.position()?.1, 6);
+        assert_eq!(process.env_chain()?[0].get("x"), Some(&Value::int(2)));
+        assert_eq!(process.resume()?, Stop::Done(VmValue::Int(3)));
+        Ok(())
+    }
+
+    fn twice<T>(module: &mut Exec<T>) -> Result<(), CoreError>
+    where
+        T: Storage,
+    {
+        match module.pop()? {
+            [VmValue::TAG_INT, n] => Ok(module.push([VmValue::TAG_INT, n * 2])?),
+            _ => Err(CoreError::BadArguments),
+        }
+    }
+
+    const N: Param = Param::eval("n", &[VmValue::TAG_INT]);
+
+    /// Images keep words, functions and contexts, and re-bind natives by name even when
+    /// the loading module registered them in a different order.
+    #[test]
+    fn test_image_roundtrip() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        module.add_native_fn("twice", twice, &[N])?;
+        eval_code(
+            &mut module,
+            r#"ctx: context [name: "image"] quad: func [x] [twice twice x]"#,
+        )?;
+        module.collect()?;
+        let mut image = Vec::new();
+        module.save_image(&mut image)?;
+
+        let mut loaded = Module::init(Growable::new(0x100, 0x10000))?;
+        loaded.add_native_fn("other", twice, &[N, N])?;
+        loaded.add_native_fn("twice", twice, &[N])?;
+        loaded.load_image(image.as_slice())?;
+
+        let block = loaded.parse("reduce [ctx/name quad 3 twice 5 add 1 2]")?;
+        let result = loaded.eval(block)?;
+        assert_eq!(
+            loaded.to_value(result)?,
+            Value::block([
+                Value::string("image"),
+                Value::int(12),
+                Value::int(10),
+                Value::int(3)
+            ])
+        );
+        Ok(())
+    }
+
+    /// Binaries kept in the blob store travel with the image.
+    #[test]
+    fn test_image_blobs() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        eval_code(
+            &mut module,
+            r#"s: "" loop 300 [s: append s "abcd"] b: to-binary s c: b"#,
+        )?;
+        let mut image = Vec::new();
+        module.save_image(&mut image)?;
+
+        let mut loaded = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        loaded.load_image(image.as_slice())?;
+        assert_eq!(
+            eval_code(&mut loaded, "b")?,
+            Value::binary("abcd".repeat(300).into_bytes())
+        );
+        assert_eq!(eval_code(&mut loaded, "length? c")?, Value::int(1200));
+        Ok(())
+    }
+
+    /// An image referring to a native the module does not have is rejected.
+    #[test]
+    fn test_image_missing_native() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        module.add_native_fn("twice", twice, &[N])?;
+        let mut image = Vec::new();
+        module.save_image(&mut image)?;
+
+        let mut other = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        let result = other.load_image(image.as_slice());
+        assert!(matches!(
+            result,
+            Err(CoreError::ImageError(ImageError::NativeNotFound(name))) if name == "twice"
+        ));
+        assert_eq!(eval_code(&mut other, "add 1 2")?, Value::int(3));
+        Ok(())
+    }
+
+    /// A call in tail position reuses the caller's frame, so deep recursion does not overflow.
+    #[test]
+    fn test_tail_call() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        eval_code(
+            &mut module,
+            "count: func [n acc] [either lt n 1 [acc] [count add n -1 add acc 2]]",
+        )?;
+        assert_eq!(eval_code(&mut module, "count 10000 0")?, Value::int(20000));
+
+        // calls followed by more code or with pending operations are not tail calls
+        eval_code(
+            &mut module,
+            "sum: func [n] [either lt n 1 [0] [add n sum add n -1]]",
+        )?;
+        assert_eq!(eval_code(&mut module, "sum 10")?, Value::int(55));
+        eval_code(&mut module, "last: func [n] [do [count n 0] n]")?;
+        assert_eq!(eval_code(&mut module, "last 3")?, Value::int(3));
+
+        let result = eval_code(&mut module, "sum 1000");
+        assert!(matches!(
+            result,
+            Err(CoreError::MemoryError(MemoryError::StackOverflow))
+        ));
+
+        // block arguments don't keep the caller's frame
+        eval_code(
+            &mut module,
+            "f: func [n acc] [either n = 0 [length? acc] [f n - 1 append acc n]]",
+        )?;
+        assert_eq!(eval_code(&mut module, "f 2000 []")?, Value::int(2000));
+        eval_code(
+            &mut module,
+            "g: func [n code] [either n = 0 [do code] [g n - 1 [n]]]",
+        )?;
+        assert_eq!(eval_code(&mut module, "g 2000 none")?, Value::int(1));
+        Ok(())
+    }
+
+    /// Functions see the words of the context they were defined in, also after it is left.
+    #[test]
+    fn test_closure() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        eval_code(
+            &mut module,
+            "make-counter: func [n] [func [] [n: add n 1]] c: make-counter 10 d: make-counter 0",
+        )?;
+        assert_eq!(eval_code(&mut module, "c")?, Value::int(11));
+        assert_eq!(eval_code(&mut module, "c")?, Value::int(12));
+        assert_eq!(eval_code(&mut module, "d")?, Value::int(1));
+
+        eval_code(
+            &mut module,
+            "adder: func [x] [func [y] [add x y]] add5: adder 5",
+        )?;
+        assert_eq!(eval_code(&mut module, "add5 2")?, Value::int(7));
+
+        // functions defined in functions see all enclosing arguments
+        eval_code(
+            &mut module,
+            "f: func [a] [func [b] [func [c] [add a add b c]]] g: f 1 h: g 2",
+        )?;
+        assert_eq!(eval_code(&mut module, "h 3")?, Value::int(6));
+        assert_eq!(eval_code(&mut module, "h 10")?, Value::int(13));
+
+        // words of the caller are not visible to the function, called last or not
+        eval_code(
+            &mut module,
+            "get-q: func [] [q] call-last: func [q] [get-q] call-first: func [q] [get-q 0]",
+        )?;
+        assert!(eval_code(&mut module, "call-last 5").is_err());
+        assert!(eval_code(&mut module, "call-first 5").is_err());
+
+        // blocks passed in are evaluated with the words of the caller
+        eval_code(
+            &mut module,
+            "run: func [code] [do code] greet: func [name] [reform [run [name] \"!\"]]",
+        )?;
+        assert_eq!(
+            eval_code(&mut module, "greet \"Bob\"")?,
+            Value::string("Bob !")
+        );
+
+        // and set the words of the caller, not of the function evaluating them
+        eval_code(
+            &mut module,
+            "f: func [b] [do b] g: func [/local y] [y: 1 f [y: 5] y]",
+        )?;
+        assert_eq!(eval_code(&mut module, "g")?, Value::int(5));
+        assert!(eval_code(&mut module, "y").is_err());
+        eval_code(
+            &mut module,
+            "h: func [b /local y] [y: 0 do b y] k: func [/local y] [y: 1 h [y: 5] y]",
+        )?;
+        assert_eq!(eval_code(&mut module, "k")?, Value::int(5));
+        Ok(())
+    }
+
+    /// Specs take doc strings, argument types, refinements with arguments and locals.
+    #[test]
+    fn test_func_spec() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        eval_code(
+            &mut module,
+            r#"f: func ["Adds numbers" a [integer!] /twice "add b as well" b /local tmp] [
+                tmp: add a 1
+                reduce [tmp twice b]
+            ]"#,
+        )?;
+
+        assert_eq!(
+            eval_code(&mut module, "f 1")?,
+            Value::block([Value::int(2), Value::None, Value::None])
+        );
+        assert_eq!(
+            eval_code(&mut module, "f/twice 1 7")?,
+            Value::block([Value::int(2), Value::Bool(true), Value::int(7)])
+        );
+        // locals do not leak into the defining context
+        assert!(eval_code(&mut module, "tmp").is_err());
+
+        assert!(matches!(
+            eval_code(&mut module, r#"f "one""#),
+            Err(CoreError::InvalidArgument { native, param, received: "string!" })
+                if native == "function" && param == "a"
+        ));
+        assert!(matches!(
+            eval_code(&mut module, "f/thrice 1 2"),
+            Err(CoreError::BadArguments)
+        ));
+        assert!(matches!(
+            eval_code(&mut module, "func [a [integer] b] [a]"),
+            Err(CoreError::BadArguments)
+        ));
+        Ok(())
+    }
+
+    /// Natives get literal and get-word arguments as described by their parameters.
+    #[test]
+    fn test_native_params() -> Result<(), CoreError> {
+        fn last<T: Storage>(module: &mut Exec<T>) -> Result<(), CoreError> {
+            let [_, _, tag, data] = module.pop()?;
+            module.push([tag, data]).map_err(Into::into)
+        }
+
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        let params = [Param::eval("a", &[]), Param::get("b", &[])];
+        module.add_native_fn("second", last, &params)?;
+        let params = [
+            Param::eval("a", &[]),
+            Param::literal("b", &[VmValue::TAG_WORD]),
+        ];
+        module.add_native_fn("quote", last, &params)?;
+
+        assert_eq!(eval_code(&mut module, "quote 1 foo")?, Value::word("foo"));
+        assert_eq!(
+            eval_code(&mut module, "quote add 1 2 foo")?,
+            Value::word("foo")
+        );
+        eval_code(&mut module, "x: 5")?;
+        assert_eq!(eval_code(&mut module, "second 1 x")?, Value::int(5));
+        assert_eq!(
+            eval_code(&mut module, "second 1 [x]")?,
+            Value::block([Value::word("x")])
+        );
+
+        let result = eval_code(&mut module, "quote 1 [foo]");
+        assert!(matches!(
+            result,
+            Err(CoreError::InvalidArgument { native, param, received: "block!" })
+                if native == "quote" && param == "b"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_if_unless() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        assert_eq!(eval_code(&mut module, "if lt 1 2 [10]")?, Value::int(10));
+        assert_eq!(eval_code(&mut module, "if lt 2 1 [10]")?, Value::None);
+        assert_eq!(eval_code(&mut module, "if 0 [10]")?, Value::int(10));
+        assert_eq!(
+            eval_code(&mut module, "unless lt 2 1 [10]")?,
+            Value::int(10)
+        );
+        assert_eq!(eval_code(&mut module, "unless lt 1 2 [10]")?, Value::None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_loops() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        let code = "n: 0 sum: 0 while [lt n 5] [n: add n 1 sum: add sum n] sum";
+        assert_eq!(eval_code(&mut module, code)?, Value::int(15));
+        assert_eq!(eval_code(&mut module, "while [lt 1 0] [1]")?, Value::None);
+
+        let code = "n: 0 loop 4 [n: add n 2] n";
+        assert_eq!(eval_code(&mut module, code)?, Value::int(8));
+        assert_eq!(eval_code(&mut module, "loop 0 [1]")?, Value::None);
+
+        let code = "sum: 0 repeat i 4 [sum: add sum i] sum";
+        assert_eq!(eval_code(&mut module, code)?, Value::int(10));
+        assert_eq!(
+            eval_code(&mut module, "x: repeat i 3 [i] x")?,
+            Value::int(3)
+        );
+        assert_eq!(eval_code(&mut module, "loop 2 [5]")?, Value::int(5));
+        assert_eq!(
+            eval_code(&mut module, "foreach x [1 2] [x]")?,
+            Value::int(2)
+        );
+        let code = "n: 0 while [lt n 3] [n: add n 1 multiply n 10]";
+        assert_eq!(eval_code(&mut module, code)?, Value::int(30));
+        assert_eq!(eval_code(&mut module, "foreach x [] [1]")?, Value::None);
+        assert!(matches!(
+            eval_code(&mut module, "loop 4294967296 [1]"),
+            Err(CoreError::OutOfRange(4294967296))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_break_continue() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        let code = "n: 0 while [lt 0 1] [n: add n 1 if lt 9 n [break]] n";
+        assert_eq!(eval_code(&mut module, code)?, Value::int(10));
+
+        // continue skips the rest of the body, also from a nested block
+        let code =
+            "sum: 0 foreach x [1 2 3 4 5] [do [either lt 2 x [continue] [0]] sum: add sum x] sum";
+        assert_eq!(eval_code(&mut module, code)?, Value::int(3));
+
+        // break leaves the innermost loop and the contexts entered in it
+        let code = "n: 0 repeat i 3 [repeat j 5 [if lt 1 j [break] n: add n 1] context [break]] i";
+        let result = eval_code(&mut module, code);
+        assert!(matches!(
+            result,
+            Err(CoreError::MemoryError(MemoryError::WordNotFound))
+        ));
+        assert_eq!(eval_code(&mut module, "n")?, Value::int(1));
+
+        let result = eval_code(&mut module, "break");
+        assert!(matches!(result, Err(CoreError::NoLoop)));
+        eval_code(&mut module, "f: func [] [break]")?;
+        let result = eval_code(&mut module, "loop 3 [f]");
+        assert!(matches!(result, Err(CoreError::NoLoop)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_return() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        eval_code(
+            &mut module,
+            "find-first: func [data limit] [foreach x data [if lt limit x [return x]] 0]",
+        )?;
+        assert_eq!(
+            eval_code(&mut module, "find-first [1 5 9] 4")?,
+            Value::int(5)
+        );
+        assert_eq!(eval_code(&mut module, "find-first [1 2] 4")?, Value::int(0));
+        assert_eq!(
+            eval_code(&mut module, "x: 7 add find-first [8] 1 x")?,
+            Value::int(15)
+        );
+
+        eval_code(&mut module, "g: func [] [try [return 1] 2]")?;
+        assert_eq!(eval_code(&mut module, "g")?, Value::int(1));
+
+        let result = eval_code(&mut module, "return 1");
+        assert!(matches!(result, Err(CoreError::NoFunction)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_infix() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        let cases = [
+            // operators go left to right and take precedence over function arguments
+            ("1 + 2 * 3", Value::int(9)),
+            ("add 1 2 * 3", Value::int(7)),
+            ("1 + add 2 3 * 4", Value::int(15)),
+            ("1 + negate 2", Value::int(-1)),
+            ("x: 10 - 4 x", Value::int(6)),
+            ("7 / 2 - 1", Value::int(2)),
+            ("either 1 + 1 = 2 [10] [20]", Value::int(10)),
+            ("\"a\" <> \"b\"", Value::Bool(true)),
+            ("3 >= 3", Value::Bool(true)),
+            ("x: 3 > 2 y: 1 <= 0 x and y", Value::Bool(false)),
+            ("x or y", Value::Bool(true)),
+            ("f: func [n] [n * 2 + 1] f 5", Value::int(11)),
+            (
+                "reduce [1 + 2 3 * 4]",
+                Value::block([Value::int(3), Value::int(12)]),
+            ),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(eval_code(&mut module, code)?, expected, "{code}");
+        }
+
+        let result = eval_code(&mut module, "+ 1");
+        assert!(matches!(result, Err(CoreError::MissingOperand("left"))));
+        let result = eval_code(&mut module, "1 add + 2");
+        assert!(matches!(result, Err(CoreError::MissingOperand("left"))));
+        let result = eval_code(&mut module, "a: 1 a + + 2");
+        assert!(matches!(result, Err(CoreError::MissingOperand("left"))));
+        let result = eval_code(&mut module, "1 +");
+        assert!(matches!(result, Err(CoreError::MissingOperand("right"))));
+        let result = eval_code(&mut module, "try [1 +]")?;
+        assert_eq!(result.get("kind"), Some(&Value::word("missing-operand")));
+        let result = eval_code(&mut module, "try [1 / 0]")?;
+        assert_eq!(result.get("word"), Some(&Value::word("divide")));
+        Ok(())
+    }
 }
 
 //
@@ -0,0 +1,353 @@
+// RebelDB™ © 2025 Huly Labs • https://hulylabs.com • SPDX-License-Identifier: MIT
+
+//! Dates and times. A `date!` is a moment in UTC and a `time!` is a duration, both in
+//! milliseconds, since the Unix epoch for dates.
+//!
+//! Date literals are ISO-8601: `2025-03-01`, `2025-03-01T12:00:00Z`, or with an offset
+//! from UTC such as `2025-03-01T14:00:00.250+02:00`, which is normalized to UTC. Time
+//! literals are `h:mm`, `h:mm:ss` or `h:mm:ss.fff`, with an optional sign.
+
+use crate::core::{CoreError, Exec, Module, VmValue};
+use crate::mem::Storage;
+use crate::native;
+use crate::native::{Date, Time};
+use crate::value::Value;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MS_PER_SECOND: i64 = 1000;
+const MS_PER_MINUTE: i64 = 60 * MS_PER_SECOND;
+const MS_PER_HOUR: i64 = 60 * MS_PER_MINUTE;
+const MS_PER_DAY: i64 = 24 * MS_PER_HOUR;
+
+/// Days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1; // from March 1st
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Year, month and day of a number of days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153; // from March
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn parse_digits(text: &str) -> Option<i64> {
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+/// Milliseconds of `ss` or `ss.fff`. Digits past milliseconds are dropped.
+fn parse_seconds(text: &str) -> Option<i64> {
+    let (seconds, fraction) = text.split_once('.').unwrap_or((text, "0"));
+    parse_digits(fraction)?;
+    let millis = fraction
+        .bytes()
+        .chain(std::iter::repeat(b'0'))
+        .take(3)
+        .fold(0, |millis, digit| millis * 10 + (digit - b'0') as i64);
+    Some(parse_digits(seconds)? * MS_PER_SECOND + millis)
+}
+
+/// Milliseconds of `h:mm`, `h:mm:ss` or `h:mm:ss.fff`.
+fn parse_clock(text: &str) -> Option<i64> {
+    let mut parts = text.split(':');
+    let hours = parse_digits(parts.next()?)?;
+    let minutes = parse_digits(parts.next()?)?;
+    let seconds = parts.next().map_or(Some(0), parse_seconds)?;
+    if parts.next().is_some() || minutes >= 60 || seconds >= MS_PER_MINUTE {
+        return None;
+    }
+    hours
+        .checked_mul(MS_PER_HOUR)?
+        .checked_add(minutes * MS_PER_MINUTE + seconds)
+}
+
+/// Clock time and its offset from UTC in milliseconds: `Z`, `+hh:mm`, `-hh:mm`, or no
+/// offset for UTC.
+fn split_offset(text: &str) -> Option<(&str, i64)> {
+    if let Some(clock) = text.strip_suffix('Z') {
+        return Some((clock, 0));
+    }
+    match text.rfind(['+', '-']) {
+        Some(at) => {
+            let offset = parse_clock(&text[at + 1..])?;
+            let sign = if text[at..].starts_with('-') { -1 } else { 1 };
+            Some((&text[..at], sign * offset))
+        }
+        None => Some((text, 0)),
+    }
+}
+
+/// Milliseconds since the Unix epoch of an ISO-8601 date, with an optional time of day.
+pub fn parse_date(text: &str) -> Option<i64> {
+    let (date, clock) = match text.split_once('T') {
+        Some((date, clock)) => (date, Some(clock)),
+        None => (text, None),
+    };
+    let mut parts = date.split('-');
+    let year = parts.next().filter(|year| year.len() == 4)?;
+    let year = parse_digits(year)?;
+    let month = parse_digits(parts.next()?)?;
+    let day = parse_digits(parts.next()?)?;
+    if parts.next().is_some()
+        || !(1..=12).contains(&month)
+        || !(1..=days_in_month(year, month)).contains(&day)
+    {
+        return None;
+    }
+    let mut millis = days_from_civil(year, month, day) * MS_PER_DAY;
+    if let Some(clock) = clock {
+        let (clock, offset) = split_offset(clock)?;
+        let time = parse_clock(clock).filter(|time| *time < MS_PER_DAY)?;
+        millis += time - offset;
+    }
+    Some(millis)
+}
+
+/// Milliseconds of a time literal, negative with a leading `-`.
+pub fn parse_time(text: &str) -> Option<i64> {
+    match text.strip_prefix('-') {
+        Some(text) => parse_clock(text).map(|millis| -millis),
+        None => parse_clock(text.strip_prefix('+').unwrap_or(text)),
+    }
+}
+
+/// `hh:mm:ss` with hours at least `width` digits, and milliseconds if there are any.
+fn form_clock(millis: u64, width: usize) -> String {
+    let hours = millis / MS_PER_HOUR as u64;
+    let minutes = millis / MS_PER_MINUTE as u64 % 60;
+    let seconds = millis / MS_PER_SECOND as u64 % 60;
+    let mut result = format!("{hours:0width$}:{minutes:02}:{seconds:02}");
+    if !millis.is_multiple_of(1000) {
+        result.push_str(&format!(".{:03}", millis % 1000));
+    }
+    result
+}
+
+/// ISO-8601 text of a date, in UTC.
+pub fn form_date(millis: i64) -> String {
+    let (year, month, day) = civil_from_days(millis.div_euclid(MS_PER_DAY));
+    let clock = form_clock(millis.rem_euclid(MS_PER_DAY) as u64, 2);
+    format!("{year:04}-{month:02}-{day:02}T{clock}Z")
+}
+
+/// Text of a time, such as `1:30:00` or `-0:00:00.500`.
+pub fn form_time(millis: i64) -> String {
+    let sign = if millis < 0 { "-" } else { "" };
+    format!("{sign}{}", form_clock(millis.unsigned_abs(), 1))
+}
+
+/// Milliseconds since the Unix epoch of a system time, such as a file modification time.
+pub fn unix_millis(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_millis() as i64,
+        Err(err) => -(err.duration().as_millis() as i64),
+    }
+}
+
+/// Current date and time.
+#[native]
+fn now() -> Date {
+    Date(unix_millis(SystemTime::now()))
+}
+
+/// Time from `b` to `a`.
+#[native(name = "date-diff")]
+fn date_diff(a: Date, b: Date) -> Result<Time, CoreError> {
+    a.0.checked_sub(b.0)
+        .map(Time)
+        .ok_or(CoreError::MathOverflow)
+}
+
+/// Date `duration` after `date`, or before it if the duration is negative.
+#[native(name = "add-duration")]
+fn add_duration(date: Date, duration: Time) -> Result<Date, CoreError> {
+    date.0
+        .checked_add(duration.0)
+        .map(Date)
+        .ok_or(CoreError::MathOverflow)
+}
+
+/// Seconds since the Unix epoch, rounded down.
+#[native(name = "to-unix")]
+fn to_unix(date: Date) -> Value {
+    Value::integer(date.0.div_euclid(MS_PER_SECOND))
+}
+
+/// Date of a number of seconds since the Unix epoch, or of an ISO-8601 string.
+#[native(name = "to-date")]
+fn to_date<T: Storage>(exec: &mut Exec<T>, value: VmValue) -> Result<Date, CoreError> {
+    match exec.to_value(value)? {
+        Value::Int(seconds) => Ok(Date(seconds as i64 * MS_PER_SECOND)),
+        Value::Int64(seconds) => seconds
+            .checked_mul(MS_PER_SECOND)
+            .map(Date)
+            .ok_or(CoreError::MathOverflow),
+        Value::String(text) => parse_date(&text).map(Date).ok_or(CoreError::BadArguments),
+        _ => Err(CoreError::BadArguments),
+    }
+}
+
+/// Text of a date in UTC following `pattern`, where `%Y`, `%m`, `%d`, `%H`, `%M`, `%S`
+/// stand for the year, month, day, hours, minutes and seconds, and `%%` for `%`.
+#[native(name = "format-date")]
+fn format_date(date: Date, pattern: String) -> String {
+    let (year, month, day) = civil_from_days(date.0.div_euclid(MS_PER_DAY));
+    let time = date.0.rem_euclid(MS_PER_DAY);
+    let mut result = String::new();
+    let mut chars = pattern.chars();
+    while let Some(char) = chars.next() {
+        if char != '%' {
+            result.push(char);
+            continue;
+        }
+        match chars.next() {
+            Some('Y') => result.push_str(&format!("{year:04}")),
+            Some('m') => result.push_str(&format!("{month:02}")),
+            Some('d') => result.push_str(&format!("{day:02}")),
+            Some('H') => result.push_str(&format!("{:02}", time / MS_PER_HOUR)),
+            Some('M') => result.push_str(&format!("{:02}", time / MS_PER_MINUTE % 60)),
+            Some('S') => result.push_str(&format!("{:02}", time / MS_PER_SECOND % 60)),
+            Some('%') => result.push('%'),
+            Some(other) => {
+                result.push('%');
+                result.push(other);
+            }
+            None => result.push('%'),
+        }
+    }
+    result
+}
+
+pub fn date_package<T>(module: &mut Module<T>) -> Result<(), CoreError>
+where
+    T: Storage,
+{
+    now::register(module)?;
+    date_diff::register(module)?;
+    add_duration::register(module)?;
+    to_unix::register(module)?;
+    to_date::register(module)?;
+    format_date::register(module)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::Growable;
+
+    fn eval(module: &mut Module<Growable>, code: &str) -> Result<Value, CoreError> {
+        let block = module.parse(code)?;
+        let result = module.eval(block)?;
+        module.to_value(result)
+    }
+
+    #[test]
+    fn test_parse_and_form() {
+        let cases = [
+            ("1970-01-01", 0, "1970-01-01T00:00:00Z"),
+            (
+                "2025-03-01T12:00:00Z",
+                1740830400000,
+                "2025-03-01T12:00:00Z",
+            ),
+            (
+                "2025-03-01T14:00:00+02:00",
+                1740830400000,
+                "2025-03-01T12:00:00Z",
+            ),
+            (
+                "2024-02-29T23:59:59.5",
+                1709251199500,
+                "2024-02-29T23:59:59.500Z",
+            ),
+            ("1969-12-31T23:00-01:00", 0, "1970-01-01T00:00:00Z"),
+            ("1900-03-01", -2203891200000, "1900-03-01T00:00:00Z"),
+        ];
+        for (text, millis, formed) in cases {
+            assert_eq!(parse_date(text), Some(millis), "{text}");
+            assert_eq!(form_date(millis), formed, "{text}");
+        }
+        for text in [
+            "2025-02-29",
+            "2025-13-01",
+            "25-03-01",
+            "2025-03-01T24:00",
+            "2025-03",
+        ] {
+            assert_eq!(parse_date(text), None, "{text}");
+        }
+
+        assert_eq!(parse_time("1:30"), Some(90 * MS_PER_MINUTE));
+        assert_eq!(parse_time("-0:00:00.25"), Some(-250));
+        assert_eq!(parse_time("100:00:01"), Some(100 * MS_PER_HOUR + 1000));
+        assert_eq!(parse_time("1:60"), None);
+        assert_eq!(form_time(90 * MS_PER_MINUTE), "1:30:00");
+        assert_eq!(form_time(-250), "-0:00:00.250");
+    }
+
+    #[test]
+    fn test_date_natives() -> Result<(), CoreError> {
+        let mut module = Module::init(Growable::new(0x10000, 0x100000))?;
+        eval(&mut module, "start: 2025-03-01T12:00:00Z")?;
+        let cases = [
+            ("date-diff 2025-03-02 start", Value::Time(12 * MS_PER_HOUR)),
+            ("add-duration start 36:00", Value::Date(1740960000000)),
+            ("add-duration start -12:00", Value::Date(1740787200000)),
+            ("to-unix start", Value::int(1740830400)),
+            ("to-date 1740830400", Value::Date(1740830400000)),
+            (
+                "equal? start to-date \"2025-03-01T12:00Z\"",
+                Value::Bool(true),
+            ),
+            (
+                "format-date start \"%d.%m.%Y %H:%M:%S %%\"",
+                Value::string("01.03.2025 12:00:00 %"),
+            ),
+            (
+                "mold [2025-03-01 1:30]",
+                Value::string("[2025-03-01T00:00:00Z 1:30:00]"),
+            ),
+            ("first load mold start", Value::Date(1740830400000)),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(eval(&mut module, code)?, expected, "{code}");
+        }
+
+        let before = unix_millis(SystemTime::now());
+        match eval(&mut module, "now")? {
+            Value::Date(now) => assert!(now >= before),
+            other => panic!("now returned {other:?}"),
+        }
+        Ok(())
+    }
+}
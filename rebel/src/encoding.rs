@@ -8,6 +8,9 @@
 //! - Single byte for common small values
 //! - Progressively more bytes for larger values
 //! - Sign-magnitude representation to handle negative numbers efficiently
+//!
+//! It also has the hex and base64 text encodings of binaries, as used by `#{...}` and
+//! `64#{...}` literals.
 
 /// Encodes an i32 value into a buffer using a variable-length encoding scheme.
 ///
@@ -219,6 +222,148 @@ pub fn encoded_size(value: i32) -> usize {
     }
 }
 
+/// Encodes an i64 value into a buffer, extending the i32 scheme.
+///
+/// Values that fit in an i32 are encoded exactly as [`encode_i32`] does. Larger values
+/// take 4 to 8 bytes of magnitude after a tag: 0x43/0x47 (four bytes positive/negative)
+/// and 0x48-0x4B/0x4C-0x4F (five to eight bytes positive/negative).
+///
+/// # Arguments
+/// * `value` - The i64 value to encode
+/// * `buffer` - The destination buffer to write into (must be at least 9 bytes long)
+///
+/// # Returns
+/// `Some(n)` with the number of bytes written on success, `None` if the buffer is too small
+pub fn encode_i64(value: i64, buffer: &mut [u8]) -> Option<usize> {
+    if buffer.len() < 9 {
+        return None;
+    }
+    if let Ok(value) = i32::try_from(value) {
+        return encode_i32(value, buffer);
+    }
+
+    let magnitude = value.unsigned_abs();
+    let len = (8 - magnitude.leading_zeros() as usize / 8).max(4);
+    let sign = if value < 0 { 0x04 } else { 0 };
+    let long = if len > 4 { 0x08 } else { 0 };
+    buffer[0] = 0x40 | long | sign | ((len - 1) & 0x03) as u8;
+    buffer[1..=len].copy_from_slice(&magnitude.to_be_bytes()[8 - len..]);
+    Some(len + 1)
+}
+
+/// Returns the length in bytes of an i64 encoded with [`encode_i64`], given its first byte.
+///
+/// # Returns
+/// `Some(n)` with the length including the first byte, `None` if the byte is not a valid tag
+pub fn encoded_i64_len(first_byte: u8) -> Option<usize> {
+    match first_byte {
+        0..=0x3F | 0x80..=0xBF => Some(1),
+        0x40..=0x4F => {
+            let long = if first_byte & 0x08 != 0 { 4 } else { 0 };
+            Some((first_byte & 0x03) as usize + 2 + long)
+        }
+        _ => None,
+    }
+}
+
+/// Decodes a variable-length encoded i64, or any value encoded with [`encode_i32`].
+///
+/// # Returns
+/// `Some((value, bytes_read))` on success, `None` if the buffer is too small, has an
+/// invalid tag or the value doesn't fit in an i64
+pub fn decode_i64(buffer: &[u8]) -> Option<(i64, usize)> {
+    let first_byte = *buffer.first()?;
+    match first_byte {
+        0..=0x3F | 0x80..=0xBF => decode_i32(buffer).map(|(value, len)| (value as i64, len)),
+        0x40..=0x4F => {
+            let len = encoded_i64_len(first_byte)?;
+            let bytes = buffer.get(1..len)?;
+            let magnitude = bytes
+                .iter()
+                .fold(0u64, |magnitude, byte| magnitude << 8 | *byte as u64);
+            let value = if first_byte & 0x04 != 0 {
+                0i64.checked_sub_unsigned(magnitude)?
+            } else {
+                i64::try_from(magnitude).ok()?
+            };
+            Some((value, len))
+        }
+        _ => None,
+    }
+}
+
+/// Encodes bytes as uppercase hex digits, two per byte.
+pub fn encode_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+    let mut text = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        text.push(DIGITS[(byte >> 4) as usize] as char);
+        text.push(DIGITS[(byte & 0x0F) as usize] as char);
+    }
+    text
+}
+
+/// Decodes hex digits in either case, ignoring whitespace.
+///
+/// # Returns
+/// `None` if there is a character other than a digit or whitespace, or an odd number of digits
+pub fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    let digits = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_digit(16).map(|digit| digit as u8))
+        .collect::<Option<Vec<u8>>>()?;
+    if digits.len() % 2 != 0 {
+        return None;
+    }
+    Some(digits.chunks_exact(2).map(|pair| pair[0] << 4 | pair[1]).collect())
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes bytes as standard base64, padded with `=`.
+pub fn encode_base64(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let bits = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |bits, (i, byte)| bits | (*byte as u32) << (16 - i * 8));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(BASE64[(bits >> (18 - i * 6)) as usize & 0x3F] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+/// Decodes standard base64, ignoring whitespace. Padding is optional.
+///
+/// # Returns
+/// `None` if there is a character outside of the alphabet, or a dangling character
+pub fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let text: Vec<u8> = text.bytes().filter(|c| !c.is_ascii_whitespace()).collect();
+    let data = text.strip_suffix(b"==").or_else(|| text.strip_suffix(b"=")).unwrap_or(&text);
+    let mut bytes = Vec::with_capacity(data.len() * 3 / 4);
+    for chunk in data.chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let mut bits = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            let value = BASE64.iter().position(|b| b == c)? as u32;
+            bits |= value << (18 - i * 6);
+        }
+        for i in 0..chunk.len() - 1 {
+            bytes.push((bits >> (16 - i * 8)) as u8);
+        }
+    }
+    Some(bytes)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -415,4 +560,63 @@ mod tests {
         assert_eq!(encoded_size(2147483647), 5);  // i32::MAX
         assert_eq!(encoded_size(-2147483648), 5); // i32::MIN
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_i64_roundtrip() {
+        let mut buffer = [0u8; 9];
+        let test_values = [
+            0,
+            -64,
+            100000,
+            i32::MAX as i64,
+            i32::MIN as i64,
+            i32::MAX as i64 + 1,
+            i32::MIN as i64 - 1,
+            1_700_000_000_000,
+            -1_700_000_000_000,
+            i64::MAX,
+            i64::MIN,
+        ];
+
+        for &value in &test_values {
+            let bytes_written = encode_i64(value, &mut buffer).unwrap();
+            let (decoded, bytes_read) = decode_i64(&buffer).unwrap();
+            assert_eq!(decoded, value, "Failed to round-trip value {}", value);
+            assert_eq!(bytes_read, bytes_written, "Bytes read != bytes written for value {}", value);
+            assert_eq!(encoded_i64_len(buffer[0]), Some(bytes_written));
+            if let Ok(value) = i32::try_from(value) {
+                let mut small = [0u8; 5];
+                assert_eq!(encode_i32(value, &mut small), Some(bytes_written));
+                assert_eq!(&small[..bytes_written], &buffer[..bytes_written]);
+            }
+        }
+
+        assert_eq!(encode_i64(i64::MAX, &mut buffer), Some(9));
+        assert!(encode_i64(1, &mut [0u8; 5]).is_none());
+        assert!(decode_i64(&[0x4B, 0xFF, 0, 0, 0, 0, 0, 0, 0]).is_none(), "Out of i64 range");
+    }
+
+    #[test]
+    fn test_hex_and_base64() {
+        let cases: [(&[u8], &str, &str); 5] = [
+            (b"", "", ""),
+            (b"\xDE", "DE", "3g=="),
+            (b"\xDE\xAD", "DEAD", "3q0="),
+            (b"\xDE\xAD\xBE", "DEADBE", "3q2+"),
+            (b"\xDE\xAD\xBE\xEF", "DEADBEEF", "3q2+7w=="),
+        ];
+        for (bytes, hex, base64) in cases {
+            assert_eq!(encode_hex(bytes), hex);
+            assert_eq!(decode_hex(hex).as_deref(), Some(bytes));
+            assert_eq!(encode_base64(bytes), base64);
+            assert_eq!(decode_base64(base64).as_deref(), Some(bytes));
+        }
+
+        assert_eq!(decode_hex("de ad\nbe ef"), Some(vec![0xDE, 0xAD, 0xBE, 0xEF]));
+        assert_eq!(decode_base64("3q2+ 7w"), Some(vec![0xDE, 0xAD, 0xBE, 0xEF]));
+        assert!(decode_hex("DEA").is_none(), "Odd number of digits");
+        assert!(decode_hex("DEAG").is_none(), "Not a hex digit");
+        assert!(decode_base64("3q2+7").is_none(), "Dangling character");
+        assert!(decode_base64("3q-+").is_none(), "Not in the alphabet");
+    }
+}
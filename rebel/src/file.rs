@@ -0,0 +1,94 @@
+// RebelDB™ © 2025 Huly Labs • https://hulylabs.com • SPDX-License-Identifier: MIT
+
+//! Natives working on `file!` values, paths in the file system such as `%src/main.rs`.
+
+use crate::core::{CoreError, Module};
+use crate::mem::Storage;
+use crate::native;
+use crate::native::FilePath;
+use crate::value::Value;
+
+/// Directory and name of a path: `a/b.txt` splits into `a/` and `b.txt`, a directory
+/// `a/b/` into `a/` and `b/`. Paths without a directory are in `./`.
+pub fn split_path(path: &str) -> (&str, &str) {
+    let name_end = path.strip_suffix('/').map_or(path.len(), str::len);
+    match path[..name_end].rfind('/') {
+        Some(at) => path.split_at(at + 1),
+        None if name_end == 0 => (path, ""),
+        None => ("./", path),
+    }
+}
+
+/// File of a string, or of any other value formed.
+#[native(name = "to-file")]
+fn to_file(value: Value) -> Value {
+    match value {
+        Value::File(path) | Value::String(path) => Value::File(path),
+        value => Value::File(value.form().into()),
+    }
+}
+
+/// Block of the directory and the name of a path, see [`split_path`].
+#[native(name = "split-path")]
+fn split_path_native(path: FilePath) -> Value {
+    let (dir, name) = split_path(&path.0);
+    Value::block([Value::file(dir), Value::file(name)])
+}
+
+pub fn file_package<T>(module: &mut Module<T>) -> Result<(), CoreError>
+where
+    T: Storage,
+{
+    to_file::register(module)?;
+    split_path_native::register(module)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::Growable;
+
+    fn eval(module: &mut Module<Growable>, code: &str) -> Result<Value, CoreError> {
+        let block = module.parse(code)?;
+        let result = module.eval(block)?;
+        module.to_value(result)
+    }
+
+    #[test]
+    fn test_split_path() {
+        assert_eq!(split_path("a/b/c.txt"), ("a/b/", "c.txt"));
+        assert_eq!(split_path("a/b/"), ("a/", "b/"));
+        assert_eq!(split_path("/c.txt"), ("/", "c.txt"));
+        assert_eq!(split_path("c.txt"), ("./", "c.txt"));
+        assert_eq!(split_path("/"), ("/", ""));
+    }
+
+    #[test]
+    fn test_file_natives() -> Result<(), CoreError> {
+        let mut module = Module::init(Growable::new(0x10000, 0x100000))?;
+        let cases = [
+            ("%src/main.rs", Value::file("src/main.rs")),
+            ("to-file \"notes.txt\"", Value::file("notes.txt")),
+            (
+                "split-path %src/main.rs",
+                Value::block([Value::file("src/"), Value::file("main.rs")]),
+            ),
+            (
+                "split-path \"main.rs\"",
+                Value::block([Value::file("./"), Value::file("main.rs")]),
+            ),
+            (
+                "mold [%a/b %\"my file\"]",
+                Value::string("[%a/b %\"my file\"]"),
+            ),
+            ("form %a/b", Value::string("a/b")),
+            ("equal? %a to-file \"a\"", Value::Bool(true)),
+            ("equal? %a \"a\"", Value::Bool(false)),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(eval(&mut module, code)?, expected, "{code}");
+        }
+        Ok(())
+    }
+}
@@ -1,29 +1,17 @@
 // RebelDB™ © 2025 Huly Labs • https://hulylabs.com • SPDX-License-Identifier: MIT
 
-use crate::core::{CoreError, Exec, Module};
-use crate::mem::Word;
+use crate::core::{CoreError, Module};
+use crate::date::unix_millis;
+use crate::mem::Storage;
+use crate::native;
+use crate::native::FilePath;
 use crate::value::Value;
 use std::fs;
-use std::time::UNIX_EPOCH;
-
-/// Helper function to extract a string argument from the stack
-fn get_string_arg<T>(module: &mut Exec<T>) -> Result<String, CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
-    let value = module.pop_to_value()?;
-    match value {
-        Value::String(s) => Ok(s.to_string()),
-        _ => Err(CoreError::BadArguments),
-    }
-}
 
 /// List files in the current directory
 /// Returns a block of contexts, each representing a file with its metadata
-fn ls<T>(module: &mut Exec<T>) -> Result<(), CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
+#[native]
+fn ls() -> Result<Value, CoreError> {
     // Get current directory entries
     let entries = fs::read_dir(".")?;
 
@@ -48,30 +36,24 @@ where
 
         // Add modification time if available
         if let Ok(modified) = metadata.modified() {
-            if let Ok(duration) = modified.duration_since(UNIX_EPOCH) {
-                file_ctx = file_ctx.insert("modified", duration.as_secs() as i32);
-            }
+            file_ctx = file_ctx.insert("modified", Value::Date(unix_millis(modified)));
         }
 
         // Add creation time if available
         if let Ok(created) = metadata.created() {
-            if let Ok(duration) = created.duration_since(UNIX_EPOCH) {
-                file_ctx = file_ctx.insert("created", duration.as_secs() as i32);
-            }
+            file_ctx = file_ctx.insert("created", Value::Date(unix_millis(created)));
         }
 
         files.push(file_ctx.build());
     }
 
-    // Create a block containing all file contexts and push it onto the stack
-    module.push_value(Value::block(files))
+    // Create a block containing all file contexts
+    Ok(Value::block(files))
 }
 
 /// Print the current working directory
-fn pwd<T>(module: &mut Exec<T>) -> Result<(), CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
+#[native]
+fn pwd() -> Result<String, CoreError> {
     // Get the current working directory
     let cwd = std::env::current_dir()?;
 
@@ -80,88 +62,63 @@ where
         anyhow::anyhow!("Failed to convert path to string: non-UTF8 characters in path")
     })?;
 
-    // Create a string value and push it onto the stack
-    module.push_value(Value::string(cwd_str))
+    Ok(cwd_str.to_string())
 }
 
 /// Change the current working directory
-fn cd<T>(module: &mut Exec<T>) -> Result<(), CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
-    // Get the directory path from the stack
-    let path = get_string_arg(module)?;
-
+#[native]
+fn cd(path: FilePath) -> Result<String, CoreError> {
     // Change the current directory
-    std::env::set_current_dir(&path)?;
+    std::env::set_current_dir(&path.0)?;
 
     // Return the new current directory
-    pwd(module)
+    pwd()
 }
 
 /// Read the contents of a file
-fn cat<T>(module: &mut Exec<T>) -> Result<(), CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
-    // Get the file path from the stack
-    let path = get_string_arg(module)?;
-
-    // Read the file contents
-    let contents = std::fs::read_to_string(&path)?;
-
-    // Create a string value and push it onto the stack
-    module.push_value(Value::string(contents))
+#[native]
+fn cat(path: FilePath) -> Result<String, CoreError> {
+    Ok(std::fs::read_to_string(&path.0)?)
 }
 
 /// Create a new directory
-fn mkdir<T>(module: &mut Exec<T>) -> Result<(), CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
-    // Get the directory path from the stack
-    let path = get_string_arg(module)?;
-
+#[native]
+fn mkdir(path: FilePath) -> Result<Value, CoreError> {
     // Create the directory
-    std::fs::create_dir(&path)?;
+    std::fs::create_dir(&path.0)?;
 
     // Return a boolean value (true for success)
-    module.push_value(Value::boolean(true))
+    Ok(Value::boolean(true))
 }
 
 /// Remove a file or directory
-fn rm<T>(module: &mut Exec<T>) -> Result<(), CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
-    // Get the path from the stack
-    let path = get_string_arg(module)?;
-
+#[native]
+fn rm(path: FilePath) -> Result<Value, CoreError> {
     // Check if it's a directory or a file
-    let metadata = std::fs::metadata(&path)?;
+    let metadata = std::fs::metadata(&path.0)?;
 
     // Remove the file or directory
     if metadata.is_dir() {
-        std::fs::remove_dir(&path)?;
+        std::fs::remove_dir(&path.0)?;
     } else {
-        std::fs::remove_file(&path)?;
+        std::fs::remove_file(&path.0)?;
     }
 
     // Return a boolean value (true for success)
-    module.push_value(Value::boolean(true))
+    Ok(Value::boolean(true))
 }
 
 /// Register all filesystem functions
 pub fn fs_package<T>(module: &mut Module<T>) -> Result<(), CoreError>
 where
-    T: AsMut<[Word]> + AsRef<[Word]>,
+    T: Storage,
 {
-    module.add_native_fn("ls", ls, 0)?;
-    module.add_native_fn("pwd", pwd, 0)?;
-    module.add_native_fn("cd", cd, 1)?;
-    module.add_native_fn("cat", cat, 1)?;
-    module.add_native_fn("mkdir", mkdir, 1)?;
-    module.add_native_fn("rm", rm, 1)?;
+    ls::register(module)?;
+    pwd::register(module)?;
+    cd::register(module)?;
+    cat::register(module)?;
+    mkdir::register(module)?;
+    rm::register(module)?;
     // Add more filesystem functions in the future
     Ok(())
 }
@@ -189,7 +146,7 @@ mod tests {
     }
 
     // Helper function to create an execution context from a module
-    fn setup_exec(module: &mut Module<Box<[Word]>>) -> Exec<Box<[Word]>> {
+    fn setup_exec(module: &mut Module<Box<[Word]>>) -> Exec<'_, Box<[Word]>> {
         // Create a dummy block for the execution context
         let dummy_block = module
             .alloc_value(&Value::block(vec![]))
@@ -208,7 +165,7 @@ mod tests {
         let mut exec = setup_exec(&mut module);
 
         // Call the pwd function
-        pwd(&mut exec).expect("Failed to call pwd");
+        pwd::native(&mut exec).expect("Failed to call pwd");
 
         // Get the result
         let result = exec.pop_to_value().expect("Failed to get result");
@@ -250,7 +207,7 @@ mod tests {
         let mut exec = setup_exec(&mut module);
 
         // Call the ls function
-        ls(&mut exec).expect("Failed to call ls");
+        ls::native(&mut exec).expect("Failed to call ls");
 
         // Get the result
         let result = exec.pop_to_value().expect("Failed to get result");
@@ -312,7 +269,7 @@ mod tests {
             .expect("Failed to push directory path");
 
         // Call the cd function
-        cd(&mut exec).expect("Failed to call cd");
+        cd::native(&mut exec).expect("Failed to call cd");
 
         // Get the result
         let result = exec.pop_to_value().expect("Failed to get result");
@@ -375,7 +332,7 @@ mod tests {
             .expect("Failed to push file path");
 
         // Call the cat function
-        cat(&mut exec).expect("Failed to call cat");
+        cat::native(&mut exec).expect("Failed to call cat");
 
         // Get the result
         let result = exec.pop_to_value().expect("Failed to get result");
@@ -389,6 +346,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cat_missing_file() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let missing = temp_dir.path().join("missing.txt");
+
+        let mut module = setup_module();
+        let code = format!("try [cat {:?}]", missing.to_string_lossy());
+        let block = module.parse(&code).expect("Failed to parse");
+        let result = module.eval(block).expect("Error should be caught");
+        let error = module.to_value(result).expect("Failed to get result");
+
+        // The failure is caught as an error value instead of aborting evaluation
+        assert!(error.is_error(), "try should return an error");
+        assert_eq!(error.get("kind"), Some(&Value::word("io")));
+        assert_eq!(error.get("word"), Some(&Value::word("cat")));
+    }
+
     #[test]
     fn test_mkdir() {
         // Create a temporary directory for testing
@@ -407,7 +381,7 @@ mod tests {
             .expect("Failed to push directory path");
 
         // Call the mkdir function
-        mkdir(&mut exec).expect("Failed to call mkdir");
+        mkdir::native(&mut exec).expect("Failed to call mkdir");
 
         // Get the result
         let result = exec.pop_to_value().expect("Failed to get result");
@@ -445,7 +419,7 @@ mod tests {
                 .expect("Failed to push file path");
 
             // Call the rm function
-            rm(&mut exec).expect("Failed to call rm on file");
+            rm::native(&mut exec).expect("Failed to call rm on file");
 
             // Get the result
             let result = exec.pop_to_value().expect("Failed to get result");
@@ -468,7 +442,7 @@ mod tests {
                 .expect("Failed to push directory path");
 
             // Call the rm function
-            rm(&mut exec).expect("Failed to call rm on directory");
+            rm::native(&mut exec).expect("Failed to call rm on directory");
 
             // Get the result
             let result = exec.pop_to_value().expect("Failed to get result");
@@ -0,0 +1,209 @@
+// RebelDB™ © 2025 Huly Labs • https://hulylabs.com • SPDX-License-Identifier: MIT
+
+//! Copying garbage collector for the VM heap.
+//!
+//! The heap is a bump allocator of length-prefixed blocks. Collection copies every
+//! block reachable from the roots into a fresh to-space (Cheney style), rewriting
+//! references along the way, and then replaces the heap contents with the to-space.
+//! Offsets are therefore *not* stable across a collection: any `VmValue` or `Offset`
+//! held outside of the VM roots is invalid once a collection has happened.
+
+use crate::core::{MemValue, VmValue};
+use crate::mem::{Header, MemoryError, Offset, Word};
+use std::collections::HashMap;
+
+/// Layout of a heap block, which tells the collector how to trace the words inside.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// Raw data without references: strings, the symbol table.
+    Opaque,
+    /// Sequence of `[tag, data]` pairs: blocks, paths, function descriptors.
+    Values,
+    /// Context hash table: a header word followed by `[symbol, tag, data]` entries.
+    Context,
+}
+
+impl Kind {
+    /// Kind of the block referenced by a value with the given tag, `None` for immediates.
+    pub fn of(tag: Word) -> Option<Kind> {
+        match tag {
+            VmValue::TAG_BLOCK
+            | VmValue::TAG_PATH
+            | VmValue::TAG_SET_PATH
+            | VmValue::TAG_GET_PATH
+            | VmValue::TAG_FUNC => Some(Kind::Values),
+            VmValue::TAG_CONTEXT | VmValue::TAG_ERROR => Some(Kind::Context),
+            VmValue::TAG_INLINE_STRING
+            | VmValue::TAG_BINARY
+            | VmValue::TAG_FILE
+            | VmValue::TAG_URL
+            | VmValue::TAG_DECIMAL
+            | VmValue::TAG_INT64
+            | VmValue::TAG_DATE
+            | VmValue::TAG_TIME => Some(Kind::Opaque),
+            _ => None,
+        }
+    }
+}
+
+pub struct Copier<'a> {
+    from: &'a [Word],
+    to: Vec<Word>,
+    forwarded: HashMap<Offset, Offset>,
+    scan: Vec<(Offset, Kind)>,
+    natives: Option<Vec<Word>>,
+    binaries: Vec<Offset>,
+}
+
+impl<'a> Copier<'a> {
+    /// Start a collection over `from`, keeping the first `reserved` words (heap header) in place.
+    pub fn new(from: &'a [Word], reserved: usize) -> Result<Self, MemoryError> {
+        let header = from.get(..reserved).ok_or(MemoryError::OutOfBounds)?;
+        Ok(Self {
+            from,
+            to: header.to_vec(),
+            forwarded: HashMap::new(),
+            scan: Vec::new(),
+            natives: None,
+            binaries: Vec::new(),
+        })
+    }
+
+    /// Copy the block at `addr` into to-space (once) and return its new address.
+    pub fn copy(&mut self, addr: Offset, kind: Kind) -> Result<Offset, MemoryError> {
+        if let Some(new_addr) = self.forwarded.get(&addr) {
+            return Ok(*new_addr);
+        }
+        let start = addr as usize;
+        let header = self
+            .from
+            .get(start)
+            .copied()
+            .ok_or(MemoryError::OutOfBounds)?;
+        if header & Header::FORWARD != 0 {
+            // the block grew and moved, references to either address share the copy
+            let new_addr = self.copy(header & !Header::FORWARD, kind)?;
+            self.forwarded.insert(addr, new_addr);
+            return Ok(new_addr);
+        }
+        let len = Header::len(header);
+        let block = self
+            .from
+            .get(start..start + len + 1)
+            .ok_or(MemoryError::OutOfBounds)?;
+        let new_addr = self.to.len() as Offset;
+        self.to.extend_from_slice(block);
+        if header & Header::GROWABLE != 0 {
+            self.to
+                .resize(new_addr as usize + Header::capacity(len) + 1, 0);
+        }
+        self.forwarded.insert(addr, new_addr);
+        if kind != Kind::Opaque {
+            self.scan.push((new_addr, kind));
+        }
+        Ok(new_addr)
+    }
+
+    /// Renumber native functions while copying: index `i` becomes `natives[i]`.
+    ///
+    /// Applies to values relocated from now on, which includes everything traced by
+    /// [`Copier::finish`].
+    pub fn rebind_natives(&mut self, natives: Vec<Word>) {
+        self.natives = Some(natives);
+    }
+
+    /// Relocate a value, copying the block it references if any.
+    pub fn value(&mut self, value: MemValue) -> Result<MemValue, MemoryError> {
+        let [tag, data] = value;
+        if let (VmValue::TAG_NATIVE_FN, Some(natives)) = (tag, &self.natives) {
+            let index = natives.get(data as usize).ok_or(MemoryError::OutOfBounds)?;
+            return Ok([tag, *index]);
+        }
+        let addr = match Kind::of(tag) {
+            Some(kind) => self.copy(data, kind)?,
+            None => return Ok(value),
+        };
+        if tag == VmValue::TAG_BINARY {
+            self.binaries.push(addr);
+        }
+        Ok([tag, addr])
+    }
+
+    /// New addresses of the binaries copied so far, once per reference.
+    pub fn binaries(&self) -> &[Offset] {
+        &self.binaries
+    }
+
+    /// Relocate every `[tag, data]` pair in `values` in place.
+    pub fn values(&mut self, values: &mut [Word]) -> Result<(), MemoryError> {
+        for pair in values.chunks_exact_mut(2) {
+            let [tag, data] = self.value([pair[0], pair[1]])?;
+            pair[0] = tag;
+            pair[1] = data;
+        }
+        Ok(())
+    }
+
+    /// New address of the block at `addr` if it has been copied, which after
+    /// [`Copier::trace`] tells whether it is reachable from the roots copied so far.
+    pub fn forwarded(&self, addr: Offset) -> Option<Offset> {
+        self.forwarded.get(&addr).copied()
+    }
+
+    /// Trace everything reachable from the copied roots and return the new heap contents.
+    pub fn finish(mut self) -> Result<Vec<Word>, MemoryError> {
+        self.trace()?;
+        Ok(self.to)
+    }
+
+    /// Copy everything reachable from the roots copied so far.
+    pub fn trace(&mut self) -> Result<(), MemoryError> {
+        while let Some((addr, kind)) = self.scan.pop() {
+            let start = addr as usize + 1;
+            let end = start + Header::len(self.to[addr as usize]);
+            match kind {
+                Kind::Values => {
+                    for i in (start..end).step_by(2) {
+                        if i + 1 < end {
+                            let [tag, data] = self.value([self.to[i], self.to[i + 1]])?;
+                            self.to[i] = tag;
+                            self.to[i + 1] = data;
+                        }
+                    }
+                }
+                Kind::Context => {
+                    // skip the count header, then [symbol, tag, data] entries
+                    for i in (start + 1..end).step_by(3) {
+                        if i + 2 < end && self.to[i] != 0 {
+                            let [tag, data] = self.value([self.to[i + 1], self.to[i + 2]])?;
+                            self.to[i + 1] = tag;
+                            self.to[i + 2] = data;
+                        }
+                    }
+                }
+                Kind::Opaque => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_drops_garbage_and_shares_blocks() -> Result<(), MemoryError> {
+        const STR: Word = VmValue::TAG_INLINE_STRING;
+
+        // header, garbage block at 1, string at 4, block at 6 referencing the string twice
+        let from = [7, 2, 11, 12, 1, 0x41, 4, STR, 4, STR, 4];
+        let mut copier = Copier::new(&from, 1)?;
+        let root = copier.value([VmValue::TAG_BLOCK, 6])?;
+        let to = copier.finish()?;
+
+        assert_eq!(root, [VmValue::TAG_BLOCK, 1]);
+        assert_eq!(to, vec![7, 4, STR, 6, STR, 6, 1, 0x41]);
+        Ok(())
+    }
+}
@@ -0,0 +1,167 @@
+// RebelDB™ © 2025 Huly Labs • https://hulylabs.com • SPDX-License-Identifier: MIT
+
+//! Heap images.
+//!
+//! An image is a snapshot of a module's heap (symbol table and system words included)
+//! together with the native functions the heap refers to. Natives are stored by name
+//! and arity, so that an image can be loaded into a module whose native table is
+//! ordered differently; see [`crate::core::Module::load_image`]. Large binaries live in a
+//! blob store rather than the heap, so the image carries the blobs the heap refers to.
+//!
+//! Format, all integers are little-endian `u32`:
+//! - magic `RBLI`, version
+//! - native count, then per native: arity, name length in bytes, UTF-8 name
+//! - blob count, then per blob: length in bytes, the bytes
+//! - heap length in words, then the heap words
+
+use crate::mem::Word;
+use smol_str::SmolStr;
+use std::io::{self, Read, Write};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ImageError {
+    #[error("not a heap image")]
+    BadMagic,
+    #[error("unsupported image version {0}")]
+    UnsupportedVersion(u32),
+    #[error("invalid native name in image")]
+    BadName,
+    #[error("native function `{0}` used by the image is not available")]
+    NativeNotFound(SmolStr),
+    #[error("native function `{0}` has a different arity than in the image")]
+    NativeMismatch(SmolStr),
+    #[error(transparent)]
+    IoError(#[from] io::Error),
+}
+
+pub struct Image {
+    /// Name and arity of every native function, indexed as in the heap.
+    pub natives: Vec<(SmolStr, u32)>,
+    /// Contents of the blobs the heap refers to by hash.
+    pub blobs: Vec<Vec<u8>>,
+    pub heap: Vec<Word>,
+}
+
+impl Image {
+    const MAGIC: [u8; 4] = *b"RBLI";
+    const VERSION: u32 = 2;
+
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<(), ImageError> {
+        writer.write_all(&Self::MAGIC)?;
+        write_u32(&mut writer, Self::VERSION)?;
+        write_u32(&mut writer, self.natives.len() as u32)?;
+        for (name, arity) in &self.natives {
+            write_u32(&mut writer, *arity)?;
+            write_u32(&mut writer, name.len() as u32)?;
+            writer.write_all(name.as_bytes())?;
+        }
+        write_u32(&mut writer, self.blobs.len() as u32)?;
+        for blob in &self.blobs {
+            write_u32(&mut writer, blob.len() as u32)?;
+            writer.write_all(blob)?;
+        }
+        write_u32(&mut writer, self.heap.len() as u32)?;
+        for word in &self.heap {
+            write_u32(&mut writer, *word)?;
+        }
+        writer.flush().map_err(Into::into)
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> Result<Self, ImageError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != Self::MAGIC {
+            return Err(ImageError::BadMagic);
+        }
+        let version = read_u32(&mut reader)?;
+        if version != Self::VERSION {
+            return Err(ImageError::UnsupportedVersion(version));
+        }
+
+        let count = read_u32(&mut reader)?;
+        let mut natives = Vec::new();
+        for _ in 0..count {
+            let arity = read_u32(&mut reader)?;
+            let len = read_u32(&mut reader)?;
+            let name = read_bytes(&mut reader, len as u64)?;
+            let name = String::from_utf8(name).map_err(|_| ImageError::BadName)?;
+            natives.push((name.into(), arity));
+        }
+
+        let count = read_u32(&mut reader)?;
+        let mut blobs = Vec::new();
+        for _ in 0..count {
+            let len = read_u32(&mut reader)?;
+            blobs.push(read_bytes(&mut reader, len as u64)?);
+        }
+
+        let len = read_u32(&mut reader)?;
+        let heap = read_bytes(&mut reader, len as u64 * 4)?
+            .chunks_exact(4)
+            .map(|bytes| Word::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect();
+        Ok(Self {
+            natives,
+            blobs,
+            heap,
+        })
+    }
+}
+
+fn write_u32<W: Write>(writer: &mut W, value: u32) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+/// Read `len` bytes, allocating only as much as the input actually holds, so that a
+/// corrupt length fails at the end of input rather than with a huge allocation.
+fn read_bytes<R: Read>(reader: &mut R, len: u64) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.take(len).read_to_end(&mut bytes)?;
+    if (bytes.len() as u64) < len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(bytes)
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_roundtrip() -> Result<(), ImageError> {
+        let image = Image {
+            natives: vec![("add".into(), 4), ("block?".into(), 2)],
+            blobs: vec![vec![1, 2, 3], vec![]],
+            heap: vec![11, 0xdeadbeef, 5, 7],
+        };
+        let mut bytes = Vec::new();
+        image.write(&mut bytes)?;
+
+        let read = Image::read(bytes.as_slice())?;
+        assert_eq!(read.natives, image.natives);
+        assert_eq!(read.blobs, image.blobs);
+        assert_eq!(read.heap, image.heap);
+
+        // a corrupt length fails at the end of input
+        let mut corrupt = bytes.clone();
+        corrupt[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            Image::read(corrupt.as_slice()),
+            Err(ImageError::IoError(_))
+        ));
+
+        bytes[0] = b'X';
+        assert!(matches!(
+            Image::read(bytes.as_slice()),
+            Err(ImageError::BadMagic)
+        ));
+        Ok(())
+    }
+}
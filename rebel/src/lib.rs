@@ -1,13 +1,24 @@
 // RebelDB™ © 2025 Huly Labs • https://hulylabs.com • SPDX-License-Identifier: MIT
 
+pub mod blob;
 pub mod boot;
 pub mod collector;
 pub mod core;
+pub mod date;
 pub mod encoding;
+pub mod file;
 pub mod fs;
+mod gc;
 mod hash;
+pub mod image;
 pub mod mem;
+pub mod native;
 pub mod parse;
 pub mod serialize;
+pub mod series;
 pub mod ssh;
+pub mod url;
 pub mod value;
+
+extern crate self as rebel;
+pub use rebel_macro::native;
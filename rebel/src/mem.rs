@@ -35,10 +35,57 @@ pub enum MemoryError {
     BadSymbol,
     #[error("string too long")]
     StringTooLong,
+    #[error("blob not found")]
+    BlobNotFound,
     #[error(transparent)]
     TryFromSliceError(#[from] std::array::TryFromSliceError),
 }
 
+impl MemoryError {
+    /// Name of the error kind as seen by scripts.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            MemoryError::UnexpectedError => "unexpected",
+            MemoryError::WordNotFound => "word-not-found",
+            MemoryError::StackUnderflow => "stack-underflow",
+            MemoryError::StackOverflow => "stack-overflow",
+            MemoryError::OutOfMemory => "out-of-memory",
+            MemoryError::OutOfBounds => "out-of-bounds",
+            MemoryError::SymbolNotFound => "symbol-not-found",
+            MemoryError::SymbolTableFull => "symbol-table-full",
+            MemoryError::ContextFull => "context-full",
+            MemoryError::BadSymbol => "bad-symbol",
+            MemoryError::StringTooLong => "string-too-long",
+            MemoryError::BlobNotFound => "blob-not-found",
+            MemoryError::TryFromSliceError(_) => "out-of-bounds",
+        }
+    }
+}
+
+// B L O C K   H E A D E R
+
+/// First word of a heap block: its length in words, with flags in the high bits.
+pub struct Header;
+
+impl Header {
+    /// The block moved to grow, the rest of the header is its new address.
+    pub const FORWARD: Word = 0x8000_0000;
+    /// The block was allocated with room to grow in place, see [`Header::capacity`].
+    pub const GROWABLE: Word = 0x4000_0000;
+    const LEN: Word = 0x3FFF_FFFF;
+    const MIN_CAPACITY: usize = 8;
+
+    pub fn len(header: Word) -> usize {
+        (header & Self::LEN) as usize
+    }
+
+    /// Words allocated for a growable block of `len` words. Growing in place keeps the
+    /// length within this capacity, so it never runs past the allocation.
+    pub fn capacity(len: usize) -> usize {
+        len.max(Self::MIN_CAPACITY).next_power_of_two()
+    }
+}
+
 // O P S
 
 #[derive(Debug)]
@@ -60,13 +107,29 @@ where
         self.0.as_ref().split_first()
     }
 
+    /// Address of the block at `addr`, following the forwards left by blocks that moved.
+    fn resolve(&self, mut addr: Offset) -> Result<Offset, MemoryError> {
+        loop {
+            let header = self
+                .0
+                .as_ref()
+                .get(addr as usize + 1)
+                .ok_or(MemoryError::OutOfBounds)?;
+            if header & Header::FORWARD == 0 {
+                return Ok(addr);
+            }
+            addr = header & !Header::FORWARD;
+        }
+    }
+
     fn get_block(&self, addr: Offset) -> Result<&[Word], MemoryError> {
+        let addr = self.resolve(addr)?;
         self.0
             .as_ref()
             .get(addr as usize + 1..)
             .and_then(|data| {
                 data.split_first()
-                    .and_then(|(len, block)| block.get(..*len as usize))
+                    .and_then(|(header, block)| block.get(..Header::len(*header)))
             })
             .ok_or(MemoryError::OutOfBounds)
     }
@@ -168,13 +231,19 @@ where
             .ok_or(MemoryError::OutOfMemory)
     }
 
-    fn get_block_mut(&mut self, addr: Offset) -> Result<&mut [Word], MemoryError> {
+    /// Allocated words from `addr` on, whatever blocks they belong to.
+    fn get_words_mut(&mut self, addr: Offset, size: usize) -> Result<&mut [Word], MemoryError> {
         self.0
             .as_mut()
-            .get_mut(addr as usize + 1..)
-            .and_then(|data| {
-                data.split_first_mut()
-                    .and_then(|(len, block)| block.get_mut(..*len as usize))
+            .split_first_mut()
+            .and_then(|(len, data)| {
+                let begin = addr as usize;
+                let end = begin + size;
+                if end <= *len as usize {
+                    data.get_mut(begin..end)
+                } else {
+                    None
+                }
             })
             .ok_or(MemoryError::OutOfBounds)
     }
@@ -219,6 +288,23 @@ where
     }
 }
 
+impl<T> Memory<T>
+where
+    T: AsRef<[Word]> + AsMut<[Word]>,
+{
+    fn get_block_mut(&mut self, addr: Offset) -> Result<&mut [Word], MemoryError> {
+        let addr = self.resolve(addr)?;
+        self.0
+            .as_mut()
+            .get_mut(addr as usize + 1..)
+            .and_then(|data| {
+                data.split_first_mut()
+                    .and_then(|(header, block)| block.get_mut(..Header::len(*header)))
+            })
+            .ok_or(MemoryError::OutOfBounds)
+    }
+}
+
 // B L O C K
 
 // #[derive(Debug)]
@@ -305,12 +391,22 @@ where
         self.0.init(len)
     }
 
+    /// Fails with [`MemoryError::StackOverflow`] once the stack is full.
     pub fn alloc<const N: usize>(&mut self, words: [u32; N]) -> Result<Offset, MemoryError> {
-        self.0.alloc(words)
+        self.0.alloc(words).map_err(|_| MemoryError::StackOverflow)
     }
 
     pub fn push<const N: usize>(&mut self, words: [u32; N]) -> Result<(), MemoryError> {
-        self.0.alloc(words).map(|_| ())
+        self.alloc(words).map(|_| ())
+    }
+
+    pub fn peek_all_mut(&mut self, offset: Offset) -> Option<&mut [Word]> {
+        self.0.split_first_mut().and_then(|(len, data)| {
+            len.checked_sub(offset).and_then(|size| {
+                let addr = offset as usize;
+                data.get_mut(addr..addr + size as usize)
+            })
+        })
     }
 
     pub fn peek_mut<const N: usize>(&mut self) -> Option<&mut [Word]> {
@@ -407,6 +503,16 @@ impl<T> SymbolTable<T>
 where
     T: AsRef<[Word]>,
 {
+    /// Number of symbols in the table, also the id of the last one inserted.
+    pub fn count(&self) -> Result<SymbolId, MemoryError> {
+        self.0
+             .0
+            .as_ref()
+            .first()
+            .copied()
+            .ok_or(MemoryError::UnexpectedError)
+    }
+
     pub fn get(&self, symbol: SymbolId) -> Result<Symbol, MemoryError> {
         // Symbol IDs start at 1, so return None for symbol 0
         if symbol == 0 {
@@ -756,7 +862,7 @@ where
         self.0.init(0)
     }
 
-    /// Get a value for a given symbol
+    // Get a value for a given symbol
     // pub fn replace(&mut self, symbol: SymbolId, value: [Word; 2]) -> Result<(), MemoryError> {
     //     let (_, data) = self
     //         .0
@@ -815,7 +921,11 @@ where
 
         let capacity = data.len() / Self::ENTRY_SIZE;
         if capacity == 0 {
-            return Err(MemoryError::SymbolTableFull);
+            return Err(if sealed != 0 {
+                MemoryError::WordNotFound
+            } else {
+                MemoryError::SymbolTableFull
+            });
         }
 
         let h = Self::hash_u32(symbol) as usize;
@@ -851,6 +961,68 @@ where
     }
 }
 
+// S T O R A G E
+
+/// Backing store of a [`Heap`]. Fixed-size buffers never grow, see [`Growable`] for
+/// storage that extends itself on demand.
+pub trait Storage: AsRef<[Word]> + AsMut<[Word]> {
+    /// Make room for at least `additional` more words, returning `false` if not possible.
+    /// Existing words keep their positions, so heap offsets stay valid.
+    fn grow(&mut self, _additional: usize) -> bool {
+        false
+    }
+}
+
+impl Storage for Box<[Word]> {}
+impl Storage for &mut [Word] {}
+impl<const N: usize> Storage for [Word; N] {}
+
+/// `Vec`-backed storage that doubles in size when full, up to `max_len` words.
+#[derive(Debug)]
+pub struct Growable {
+    words: Vec<Word>,
+    max_len: usize,
+}
+
+impl Growable {
+    /// Create storage of `len` words which may grow up to `max_len` words.
+    pub fn new(len: usize, max_len: usize) -> Self {
+        Self {
+            words: vec![0; len.min(max_len)],
+            max_len,
+        }
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+}
+
+impl AsRef<[Word]> for Growable {
+    fn as_ref(&self) -> &[Word] {
+        &self.words
+    }
+}
+
+impl AsMut<[Word]> for Growable {
+    fn as_mut(&mut self) -> &mut [Word] {
+        &mut self.words
+    }
+}
+
+impl Storage for Growable {
+    fn grow(&mut self, additional: usize) -> bool {
+        let len = self.words.len();
+        let required = len + additional;
+        if required > self.max_len {
+            return false;
+        }
+        self.words
+            .resize(required.max(len * 2).min(self.max_len), 0);
+        true
+    }
+}
+
 // H E A P
 
 pub struct Heap<T>(Memory<T>);
@@ -872,17 +1044,94 @@ where
     pub fn get<const N: usize>(&self, addr: Offset) -> Result<[Word; N], MemoryError> {
         self.0.get(addr)
     }
+
+    /// Number of words allocated so far, reserved header words included.
+    pub fn len(&self) -> Result<Offset, MemoryError> {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> Result<bool, MemoryError> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Total number of words the heap can hold.
+    pub fn capacity(&self) -> usize {
+        self.0 .0.as_ref().len().saturating_sub(1)
+    }
+
+    /// All allocated words, starting from address 0.
+    pub fn allocated(&self) -> Result<&[Word], MemoryError> {
+        self.0
+            .split_first()
+            .and_then(|(len, data)| data.get(..*len as usize))
+            .ok_or(MemoryError::UnexpectedError)
+    }
 }
 
 impl<T> Heap<T>
 where
-    T: AsMut<[Word]>,
+    T: AsRef<[Word]> + AsMut<[Word]>,
 {
     pub fn init(&mut self, reserve: u32) -> Result<(), MemoryError> {
         self.0.init(reserve)
     }
 
+    /// Replace heap contents with `words` and continue allocating right after them.
+    /// Words freed this way are zeroed, as allocation expects fresh memory.
+    pub fn reset(&mut self, words: &[Word]) -> Result<(), MemoryError> {
+        self.0
+            .split_first_mut()
+            .and_then(|(len, data)| {
+                let end = data.len().min(*len as usize).max(words.len());
+                data.get_mut(..end).map(|block| {
+                    let (live, freed) = block.split_at_mut(words.len());
+                    live.copy_from_slice(words);
+                    freed.fill(0);
+                    *len = words.len() as Word;
+                })
+            })
+            .ok_or(MemoryError::OutOfMemory)
+    }
+
+    pub fn get_block_mut(&mut self, addr: Offset) -> Result<&mut [Word], MemoryError> {
+        self.0.get_block_mut(addr)
+    }
+
+    pub fn put<const N: usize>(
+        &mut self,
+        addr: Offset,
+        value: [Word; N],
+    ) -> Result<(), MemoryError> {
+        self.0.put(addr, value)
+    }
+
+    pub fn get_mut<const N: usize>(&mut self, addr: Offset) -> Result<&mut [u32; N], MemoryError> {
+        self.0.get_mut(addr)
+    }
+}
+
+impl<T> Heap<T>
+where
+    T: Storage,
+{
+    /// Make sure `size` more words fit, growing the storage if it supports that.
+    fn ensure(&mut self, size: usize) -> Result<(), MemoryError> {
+        let free = self.capacity() - self.len()? as usize;
+        if size > free && !self.0 .0.grow(size - free) {
+            return Err(MemoryError::OutOfMemory);
+        }
+        Ok(())
+    }
+
+    /// Like [`Heap::reset`], but grows the storage if `words` do not fit.
+    pub fn load(&mut self, words: &[Word]) -> Result<(), MemoryError> {
+        let len = self.len()? as usize;
+        self.ensure(words.len().saturating_sub(len))?;
+        self.reset(words)
+    }
+
     pub fn alloc<const N: usize>(&mut self, words: [u32; N]) -> Result<Offset, MemoryError> {
+        self.ensure(N)?;
         self.0.alloc(words)
     }
 
@@ -890,32 +1139,63 @@ where
         &mut self,
         size: Offset,
     ) -> Result<(Offset, &mut [Word]), MemoryError> {
+        self.ensure(size as usize + 1)?;
         self.0
             .alloc_empty_block(size)
             .ok_or(MemoryError::OutOfMemory)
     }
 
     pub fn alloc_block(&mut self, values: &[Word]) -> Result<Offset, MemoryError> {
+        self.ensure(values.len() + 1)?;
         self.0.alloc_block(values)
     }
 
-    pub fn get_block_mut(&mut self, addr: Offset) -> Result<&mut [Word], MemoryError> {
-        self.0.get_block_mut(addr)
-    }
-
-    pub fn put<const N: usize>(
+    /// Replace `remove` words of the block at `addr`, starting at `at`, with `insert`.
+    ///
+    /// The block changes in place while it has room. Otherwise it moves to a growable
+    /// allocation with room to spare and leaves a forward behind, so `addr` stays valid.
+    pub fn splice(
         &mut self,
         addr: Offset,
-        value: [Word; N],
+        at: usize,
+        remove: usize,
+        insert: &[Word],
     ) -> Result<(), MemoryError> {
-        self.0.put(addr, value)
-    }
+        let addr = self.0.resolve(addr)?;
+        let [header] = self.0.get(addr)?;
+        let len = Header::len(header);
+        let end = at
+            .checked_add(remove)
+            .filter(|end| *end <= len)
+            .ok_or(MemoryError::OutOfBounds)?;
+        let new_len = len - remove + insert.len();
+        let room = if header & Header::GROWABLE != 0 {
+            Header::capacity(len)
+        } else {
+            len
+        };
 
-    pub fn get_mut<const N: usize>(&mut self, addr: Offset) -> Result<&mut [u32; N], MemoryError> {
-        self.0.get_mut(addr)
-    }
+        if new_len <= room {
+            let block = self.0.get_words_mut(addr, room + 1)?;
+            let (header, data) = block
+                .split_first_mut()
+                .ok_or(MemoryError::UnexpectedError)?;
+            data.copy_within(end..len, at + insert.len());
+            data[at..at + insert.len()].copy_from_slice(insert);
+            *header = (*header & Header::GROWABLE) | new_len as Word;
+            return Ok(());
+        }
 
-    //
+        let block = self.0.get_block(addr)?;
+        let mut words = Vec::with_capacity(new_len);
+        words.extend_from_slice(&block[..at]);
+        words.extend_from_slice(insert);
+        words.extend_from_slice(&block[end..]);
+        let (new_addr, data) = self.alloc_empty_block(Header::capacity(new_len) as Offset)?;
+        data[..new_len].copy_from_slice(&words);
+        self.0.put(new_addr, [Header::GROWABLE | new_len as Word])?;
+        self.0.put(addr, [Header::FORWARD | new_addr])
+    }
 
     pub fn alloc_context(&mut self, size: Offset) -> Result<Offset, MemoryError> {
         let (addr, data) =
@@ -1176,4 +1456,49 @@ mod tests {
             "Inserting into table without enough space should return None"
         );
     }
+
+    #[test]
+    fn test_growable_heap() -> Result<(), MemoryError> {
+        let mut heap = Heap::new(Growable::new(8, 64));
+        heap.init(0)?;
+
+        let first = heap.alloc_block(&[1, 2, 3])?;
+        let second = heap.alloc_block(&[4, 5, 6, 7, 8, 9])?;
+        assert!(heap.capacity() > 8);
+
+        // offsets allocated before growing stay valid
+        assert_eq!(heap.get_block(first)?, &[1, 2, 3]);
+        assert_eq!(heap.get_block(second)?, &[4, 5, 6, 7, 8, 9]);
+
+        // but the heap never grows past its maximum
+        assert!(matches!(
+            heap.alloc_empty_block(64),
+            Err(MemoryError::OutOfMemory)
+        ));
+        assert!(heap.capacity() <= 63);
+        Ok(())
+    }
+
+    #[test]
+    fn test_splice() -> Result<(), MemoryError> {
+        let mut heap = Heap::new(Growable::new(64, 256));
+        heap.init(0)?;
+
+        let block = heap.alloc_block(&[1, 2, 3])?;
+        let next = heap.alloc_block(&[9])?;
+        heap.splice(block, 1, 1, &[])?;
+        assert_eq!(heap.get_block(block)?, &[1, 3]);
+
+        // no room left, the block moves and its offset forwards to the new place
+        heap.splice(block, 2, 0, &[4, 5])?;
+        assert_eq!(heap.get_block(block)?, &[1, 3, 4, 5]);
+        assert_eq!(heap.get_block(next)?, &[9]);
+
+        // the moved block grows in place up to its capacity
+        let len = heap.len()?;
+        heap.splice(block, 0, 0, &[0, 0, 0, 0])?;
+        assert_eq!(heap.get_block(block)?, &[0, 0, 0, 0, 1, 3, 4, 5]);
+        assert_eq!(heap.len()?, len);
+        Ok(())
+    }
 }
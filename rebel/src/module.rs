@@ -13,12 +13,7 @@ pub struct FuncDesc<T, B> {
     pub arity: u32,
 }
 
-pub type Hash = [u8; 32];
-
-pub trait BlobStore {
-    fn get(&self, hash: &Hash) -> Result<&[u8], CoreError>;
-    fn put(&mut self, data: &[u8]) -> Result<Hash, CoreError>;
-}
+pub use crate::blob::{BlobStore, Hash};
 
 /// Module struct that serves as the main interface to the RebelDB VM
 pub struct Module<T, B> {
@@ -0,0 +1,429 @@
+// RebelDB™ © 2025 Huly Labs • https://hulylabs.com • SPDX-License-Identifier: MIT
+
+//! Conversions between VM values and the Rust types taken and returned by natives
+//! defined with [`crate::native`].
+
+use crate::core::{CoreError, Exec, MemValue, VmValue};
+use crate::mem::{Offset, Storage, SymbolId, Word};
+use crate::value::Value;
+
+/// Type of a native function argument.
+pub trait FromArg: Sized {
+    /// Tags the argument accepts, any value if empty.
+    const TYPES: &'static [Word];
+
+    fn from_arg<T: Storage>(exec: &Exec<T>, value: MemValue) -> Result<Self, CoreError>;
+}
+
+/// Result of a native function, pushed on the stack when it returns.
+pub trait IntoNative {
+    fn push_into<T: Storage>(self, exec: &mut Exec<T>) -> Result<(), CoreError>;
+}
+
+/// Block argument, by reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block(pub Offset);
+
+/// Context argument, by reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Object(pub Offset);
+
+/// Block, string or binary argument, by reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Series {
+    Block(Offset),
+    String(Offset),
+    Binary(Offset),
+}
+
+/// File system path argument, a file or a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePath(pub String);
+
+/// Host argument, a url or a string such as `user@host:port`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Host(pub String);
+
+/// Date argument, milliseconds since the Unix epoch in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date(pub i64);
+
+/// Time argument, a duration in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Time(pub i64);
+
+/// Integer or decimal argument. Integers are 64-bit, whether they fit in a word or not.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Number {
+    Int(i64),
+    Decimal(f64),
+}
+
+impl Number {
+    pub fn to_f64(self) -> f64 {
+        match self {
+            Number::Int(value) => value as f64,
+            Number::Decimal(value) => value,
+        }
+    }
+}
+
+/// Integers compare exactly, decimals as `f64`.
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) => a.partial_cmp(b),
+            (a, b) => a.to_f64().partial_cmp(&b.to_f64()),
+        }
+    }
+}
+
+/// Function or native argument, to be called with [`Exec::call`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Function(pub MemValue);
+
+/// Word argument, usually taken literally.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Name(pub SymbolId);
+
+impl FromArg for i32 {
+    const TYPES: &'static [Word] = &[VmValue::TAG_INT, VmValue::TAG_INT64];
+
+    fn from_arg<T: Storage>(exec: &Exec<T>, value: MemValue) -> Result<Self, CoreError> {
+        match value {
+            [VmValue::TAG_INT, data] => Ok(data as i32),
+            [VmValue::TAG_INT64, offset] => {
+                let [low, high] = exec.get_block::<2>(offset, 0)?;
+                let value = ((high as u64) << 32 | low as u64) as i64;
+                i32::try_from(value).map_err(|_| CoreError::OutOfRange(value))
+            }
+            _ => Err(CoreError::BadArguments),
+        }
+    }
+}
+
+impl FromArg for bool {
+    const TYPES: &'static [Word] = &[VmValue::TAG_BOOL];
+
+    fn from_arg<T: Storage>(_: &Exec<T>, value: MemValue) -> Result<Self, CoreError> {
+        match value {
+            [VmValue::TAG_BOOL, data] => Ok(data != 0),
+            _ => Err(CoreError::BadArguments),
+        }
+    }
+}
+
+impl FromArg for String {
+    const TYPES: &'static [Word] = &[VmValue::TAG_INLINE_STRING];
+
+    fn from_arg<T: Storage>(exec: &Exec<T>, value: MemValue) -> Result<Self, CoreError> {
+        match exec.to_value(value.try_into()?)? {
+            Value::String(string) => Ok(string.to_string()),
+            _ => Err(CoreError::BadArguments),
+        }
+    }
+}
+
+impl FromArg for FilePath {
+    const TYPES: &'static [Word] = &[VmValue::TAG_FILE, VmValue::TAG_INLINE_STRING];
+
+    fn from_arg<T: Storage>(exec: &Exec<T>, value: MemValue) -> Result<Self, CoreError> {
+        match exec.to_value(value.try_into()?)? {
+            Value::File(path) | Value::String(path) => Ok(FilePath(path.to_string())),
+            _ => Err(CoreError::BadArguments),
+        }
+    }
+}
+
+impl FromArg for Host {
+    const TYPES: &'static [Word] = &[VmValue::TAG_URL, VmValue::TAG_INLINE_STRING];
+
+    fn from_arg<T: Storage>(exec: &Exec<T>, value: MemValue) -> Result<Self, CoreError> {
+        match exec.to_value(value.try_into()?)? {
+            Value::Url(host) | Value::String(host) => Ok(Host(host.to_string())),
+            _ => Err(CoreError::BadArguments),
+        }
+    }
+}
+
+impl FromArg for Value {
+    const TYPES: &'static [Word] = &[];
+
+    fn from_arg<T: Storage>(exec: &Exec<T>, value: MemValue) -> Result<Self, CoreError> {
+        exec.mem_to_value(value)
+    }
+}
+
+impl FromArg for VmValue {
+    const TYPES: &'static [Word] = &[];
+
+    fn from_arg<T: Storage>(_: &Exec<T>, value: MemValue) -> Result<Self, CoreError> {
+        value.try_into()
+    }
+}
+
+/// Any value as it is on the stack, natives included.
+impl FromArg for MemValue {
+    const TYPES: &'static [Word] = &[];
+
+    fn from_arg<T: Storage>(_: &Exec<T>, value: MemValue) -> Result<Self, CoreError> {
+        Ok(value)
+    }
+}
+
+impl FromArg for Block {
+    const TYPES: &'static [Word] = &[VmValue::TAG_BLOCK];
+
+    fn from_arg<T: Storage>(_: &Exec<T>, value: MemValue) -> Result<Self, CoreError> {
+        match value {
+            [VmValue::TAG_BLOCK, block] => Ok(Block(block)),
+            _ => Err(CoreError::BadArguments),
+        }
+    }
+}
+
+impl FromArg for Object {
+    const TYPES: &'static [Word] = &[VmValue::TAG_CONTEXT];
+
+    fn from_arg<T: Storage>(_: &Exec<T>, value: MemValue) -> Result<Self, CoreError> {
+        match value {
+            [VmValue::TAG_CONTEXT, ctx] => Ok(Object(ctx)),
+            _ => Err(CoreError::BadArguments),
+        }
+    }
+}
+
+impl FromArg for Number {
+    const TYPES: &'static [Word] = &[VmValue::TAG_INT, VmValue::TAG_INT64, VmValue::TAG_DECIMAL];
+
+    fn from_arg<T: Storage>(exec: &Exec<T>, value: MemValue) -> Result<Self, CoreError> {
+        match value {
+            [VmValue::TAG_INT, data] => Ok(Number::Int(data as i32 as i64)),
+            [VmValue::TAG_INT64 | VmValue::TAG_DECIMAL, _] => {
+                match exec.to_value(value.try_into()?)? {
+                    Value::Int64(value) => Ok(Number::Int(value)),
+                    Value::Decimal(value) => Ok(Number::Decimal(value)),
+                    _ => Err(CoreError::InternalError),
+                }
+            }
+            _ => Err(CoreError::BadArguments),
+        }
+    }
+}
+
+impl FromArg for Date {
+    const TYPES: &'static [Word] = &[VmValue::TAG_DATE];
+
+    fn from_arg<T: Storage>(exec: &Exec<T>, value: MemValue) -> Result<Self, CoreError> {
+        match exec.to_value(value.try_into()?)? {
+            Value::Date(millis) => Ok(Date(millis)),
+            _ => Err(CoreError::BadArguments),
+        }
+    }
+}
+
+impl FromArg for Time {
+    const TYPES: &'static [Word] = &[VmValue::TAG_TIME];
+
+    fn from_arg<T: Storage>(exec: &Exec<T>, value: MemValue) -> Result<Self, CoreError> {
+        match exec.to_value(value.try_into()?)? {
+            Value::Time(millis) => Ok(Time(millis)),
+            _ => Err(CoreError::BadArguments),
+        }
+    }
+}
+
+impl FromArg for Series {
+    const TYPES: &'static [Word] = &[
+        VmValue::TAG_BLOCK,
+        VmValue::TAG_INLINE_STRING,
+        VmValue::TAG_BINARY,
+    ];
+
+    fn from_arg<T: Storage>(_: &Exec<T>, value: MemValue) -> Result<Self, CoreError> {
+        match value {
+            [VmValue::TAG_BLOCK, block] => Ok(Series::Block(block)),
+            [VmValue::TAG_INLINE_STRING, string] => Ok(Series::String(string)),
+            [VmValue::TAG_BINARY, binary] => Ok(Series::Binary(binary)),
+            _ => Err(CoreError::BadArguments),
+        }
+    }
+}
+
+impl FromArg for Function {
+    const TYPES: &'static [Word] = &[VmValue::TAG_FUNC, VmValue::TAG_NATIVE_FN];
+
+    fn from_arg<T: Storage>(_: &Exec<T>, value: MemValue) -> Result<Self, CoreError> {
+        match value {
+            [VmValue::TAG_FUNC | VmValue::TAG_NATIVE_FN, _] => Ok(Function(value)),
+            _ => Err(CoreError::BadArguments),
+        }
+    }
+}
+
+impl FromArg for Name {
+    const TYPES: &'static [Word] = &[VmValue::TAG_WORD];
+
+    fn from_arg<T: Storage>(_: &Exec<T>, value: MemValue) -> Result<Self, CoreError> {
+        match value {
+            [VmValue::TAG_WORD, symbol] => Ok(Name(symbol)),
+            _ => Err(CoreError::BadArguments),
+        }
+    }
+}
+
+impl IntoNative for () {
+    fn push_into<T: Storage>(self, _: &mut Exec<T>) -> Result<(), CoreError> {
+        Ok(())
+    }
+}
+
+impl IntoNative for i32 {
+    fn push_into<T: Storage>(self, exec: &mut Exec<T>) -> Result<(), CoreError> {
+        exec.push_vm_value(VmValue::Int(self)).map_err(Into::into)
+    }
+}
+
+impl IntoNative for bool {
+    fn push_into<T: Storage>(self, exec: &mut Exec<T>) -> Result<(), CoreError> {
+        exec.push_vm_value(VmValue::Bool(self)).map_err(Into::into)
+    }
+}
+
+impl IntoNative for String {
+    fn push_into<T: Storage>(self, exec: &mut Exec<T>) -> Result<(), CoreError> {
+        let string = exec.alloc_string(&self)?;
+        exec.push_vm_value(VmValue::String(string))
+            .map_err(Into::into)
+    }
+}
+
+impl IntoNative for Value {
+    fn push_into<T: Storage>(self, exec: &mut Exec<T>) -> Result<(), CoreError> {
+        exec.push_value(self)
+    }
+}
+
+impl IntoNative for VmValue {
+    fn push_into<T: Storage>(self, exec: &mut Exec<T>) -> Result<(), CoreError> {
+        exec.push_vm_value(self).map_err(Into::into)
+    }
+}
+
+impl IntoNative for MemValue {
+    fn push_into<T: Storage>(self, exec: &mut Exec<T>) -> Result<(), CoreError> {
+        exec.push(self).map_err(Into::into)
+    }
+}
+
+impl IntoNative for Block {
+    fn push_into<T: Storage>(self, exec: &mut Exec<T>) -> Result<(), CoreError> {
+        exec.push_vm_value(VmValue::Block(self.0))
+            .map_err(Into::into)
+    }
+}
+
+impl IntoNative for Object {
+    fn push_into<T: Storage>(self, exec: &mut Exec<T>) -> Result<(), CoreError> {
+        exec.push_vm_value(VmValue::Context(self.0))
+            .map_err(Into::into)
+    }
+}
+
+impl IntoNative for Number {
+    fn push_into<T: Storage>(self, exec: &mut Exec<T>) -> Result<(), CoreError> {
+        match self {
+            Number::Int(value) => Value::integer(value).push_into(exec),
+            Number::Decimal(value) => Value::Decimal(value).push_into(exec),
+        }
+    }
+}
+
+impl IntoNative for Date {
+    fn push_into<T: Storage>(self, exec: &mut Exec<T>) -> Result<(), CoreError> {
+        Value::Date(self.0).push_into(exec)
+    }
+}
+
+impl IntoNative for Time {
+    fn push_into<T: Storage>(self, exec: &mut Exec<T>) -> Result<(), CoreError> {
+        Value::Time(self.0).push_into(exec)
+    }
+}
+
+impl IntoNative for Series {
+    fn push_into<T: Storage>(self, exec: &mut Exec<T>) -> Result<(), CoreError> {
+        let value = match self {
+            Series::Block(block) => VmValue::Block(block),
+            Series::String(string) => VmValue::String(string),
+            Series::Binary(binary) => VmValue::Binary(binary),
+        };
+        exec.push_vm_value(value).map_err(Into::into)
+    }
+}
+
+/// `None` is pushed as `none`.
+impl<R: IntoNative> IntoNative for Option<R> {
+    fn push_into<T: Storage>(self, exec: &mut Exec<T>) -> Result<(), CoreError> {
+        match self {
+            Some(value) => value.push_into(exec),
+            None => exec.push_vm_value(VmValue::None).map_err(Into::into),
+        }
+    }
+}
+
+impl<R, E> IntoNative for Result<R, E>
+where
+    R: IntoNative,
+    E: Into<CoreError>,
+{
+    fn push_into<T: Storage>(self, exec: &mut Exec<T>) -> Result<(), CoreError> {
+        self.map_err(Into::into)?.push_into(exec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Module;
+
+    #[crate::native]
+    fn sum_of(a: i32, b: i32) -> Result<i32, CoreError> {
+        a.checked_add(b).ok_or(CoreError::BadArguments)
+    }
+
+    #[crate::native(name = "name?")]
+    fn name_of<T: Storage>(exec: &mut Exec<T>, #[literal] word: Name) -> Result<String, CoreError> {
+        Ok(exec.get_symbol(word.0)?.to_string())
+    }
+
+    #[test]
+    fn test_native_macro() -> Result<(), CoreError> {
+        let mut module = Module::init(vec![0; 0x10000].into_boxed_slice())?;
+        sum_of::register(&mut module)?;
+        name_of::register(&mut module)?;
+
+        let mut eval = |code: &str| -> Result<Value, CoreError> {
+            let block = module.parse(code)?;
+            let result = module.eval(block)?;
+            module.to_value(result)
+        };
+        assert_eq!(eval("sum-of 1 2")?, Value::Int(3));
+        assert_eq!(eval("name? foo")?, Value::String("foo".into()));
+        assert!(matches!(
+            eval("sum-of 1 \"2\""),
+            Err(CoreError::InvalidArgument { param, .. }) if param == "b"
+        ));
+        assert!(matches!(
+            eval("sum-of 3000000000 1"),
+            Err(CoreError::OutOfRange(3000000000))
+        ));
+
+        // a boxed integer that fits is taken as well
+        let code = Value::block([Value::word("sum-of"), Value::Int64(2), Value::Int(1)]);
+        let block = module.alloc_value(&code)?;
+        let result = module.eval(block)?;
+        assert_eq!(module.to_value(result)?, Value::Int(3));
+        Ok(())
+    }
+}
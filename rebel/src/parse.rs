@@ -1,5 +1,8 @@
 // RebelDB™ © 2025 Huly Labs • https://hulylabs.com • SPDX-License-Identifier: MIT
 
+use crate::date::{parse_date, parse_time};
+use crate::encoding::{decode_base64, decode_hex};
+use crate::url::parse_url;
 use std::str::CharIndices;
 use thiserror::Error;
 
@@ -11,6 +14,16 @@ pub enum ParserError<E> {
     UnexpectedChar(char),
     #[error("integer overflow")]
     IntegerOverflow,
+    #[error("invalid decimal")]
+    InvalidDecimal,
+    #[error("invalid binary")]
+    InvalidBinary,
+    #[error("invalid date")]
+    InvalidDate,
+    #[error("invalid time")]
+    InvalidTime,
+    #[error("invalid url")]
+    InvalidUrl,
     #[error("unexpected error")]
     UnexpectedError,
     #[error("collector error: `{0}`")]
@@ -24,6 +37,8 @@ pub enum WordKind {
     Word,
     SetWord,
     GetWord,
+    LitWord,
+    Refinement,
 }
 
 pub trait Collector {
@@ -31,12 +46,22 @@ pub trait Collector {
 
     fn string(&mut self, string: &str) -> Result<(), Self::Error>;
     fn word(&mut self, kind: WordKind, word: &str) -> Result<(), Self::Error>;
-    fn integer(&mut self, value: i32) -> Result<(), Self::Error>;
+    fn integer(&mut self, value: i64) -> Result<(), Self::Error>;
+    fn decimal(&mut self, value: f64) -> Result<(), Self::Error>;
+    fn binary(&mut self, bytes: &[u8]) -> Result<(), Self::Error>;
+    /// Date, in milliseconds since the Unix epoch in UTC.
+    fn date(&mut self, millis: i64) -> Result<(), Self::Error>;
+    /// Time, a duration in milliseconds.
+    fn time(&mut self, millis: i64) -> Result<(), Self::Error>;
+    /// File, a path such as `src/main.rs` written `%src/main.rs`.
+    fn file(&mut self, path: &str) -> Result<(), Self::Error>;
+    fn url(&mut self, url: &str) -> Result<(), Self::Error>;
     fn begin_block(&mut self) -> Result<(), Self::Error>;
     fn end_block(&mut self) -> Result<(), Self::Error>;
 
     fn begin_path(&mut self) -> Result<(), Self::Error>;
-    fn end_path(&mut self) -> Result<(), Self::Error>;
+    /// End a path, `kind` tells whether it is a path, a set-path or a get-path.
+    fn end_path(&mut self, kind: WordKind) -> Result<(), Self::Error>;
 }
 
 pub struct Parser<'a, C>
@@ -47,6 +72,7 @@ where
     cursor: CharIndices<'a>,
     collector: &'a mut C,
     in_path: bool,
+    path_kind: WordKind,
 }
 
 impl<'a, C> Parser<'a, C>
@@ -59,6 +85,7 @@ where
             collector,
             cursor: input.char_indices(),
             in_path: false,
+            path_kind: WordKind::Word,
         }
     }
 
@@ -91,12 +118,21 @@ where
         None
     }
 
-    fn parse_string(&mut self, pos: usize) -> Result<Option<char>, ParserError<C::Error>> {
-        let _start_pos = pos + 1; // Skip the opening quote
+    fn parse_string(&mut self) -> Result<Option<char>, ParserError<C::Error>> {
+        let string = self.read_string()?;
+        self.collector
+            .string(&string)
+            .map(|()| None)
+            .map_err(ParserError::CollectorError)
+    }
+
+    /// Characters up to the closing quote, with escapes resolved. The cursor is right
+    /// after the opening quote.
+    fn read_string(&mut self) -> Result<String, ParserError<C::Error>> {
         let mut result = String::new();
         let mut escaped = false;
 
-        while let Some((_, char)) = self.cursor.next() {
+        for (_, char) in self.cursor.by_ref() {
             if escaped {
                 // Handle escape sequences
                 let escaped_char = match char {
@@ -113,11 +149,7 @@ where
                 escaped = true;
             } else if char == '"' {
                 // End of string
-                return self
-                    .collector
-                    .string(&result)
-                    .map(|()| None)
-                    .map_err(ParserError::CollectorError);
+                return Ok(result);
             } else {
                 result.push(char);
             }
@@ -130,24 +162,39 @@ where
     fn collect_word(
         &mut self,
         symbol: &str,
-        kind: WordKind,
+        mut kind: WordKind,
         consumed: Option<char>,
     ) -> Result<Option<char>, C::Error> {
         if let Some('/') = consumed {
-            if self.in_path == false {
+            if !self.in_path {
                 self.in_path = true;
                 self.collector.begin_path()?;
+                // `:a/b` is a get-path starting with the word `a`
+                if kind == WordKind::GetWord {
+                    self.path_kind = WordKind::GetWord;
+                    kind = WordKind::Word;
+                }
             }
         }
+        // `a/b:` is a set-path ending with the word `b`
+        if self.in_path && kind == WordKind::SetWord {
+            self.path_kind = WordKind::SetWord;
+            kind = WordKind::Word;
+        }
         self.collector.word(kind, symbol).map(|_| consumed)
     }
 
-    fn parse_word(&mut self, start_pos: usize) -> Result<Option<char>, ParserError<C::Error>> {
-        let mut kind = WordKind::Word;
-
+    fn parse_word(
+        &mut self,
+        start_pos: usize,
+        mut kind: WordKind,
+    ) -> Result<Option<char>, ParserError<C::Error>> {
         let consumed = loop {
             match self.cursor.next() {
                 Some((pos, char)) => match char {
+                    ':' if kind == WordKind::Word && self.input[pos..].starts_with("://") => {
+                        return self.parse_url(start_pos);
+                    }
                     ':' => {
                         if pos == start_pos {
                             kind = WordKind::GetWord;
@@ -157,7 +204,7 @@ where
                         }
                     }
                     ']' | '/' => break Some(char),
-                    c if c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '?' => {}
+                    c if c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '?' | '!') => {}
                     c if c.is_ascii_whitespace() => break Some(char),
                     _ => return Err(ParserError::UnexpectedChar(char)),
                 },
@@ -178,60 +225,207 @@ where
             .map_err(ParserError::CollectorError)
     }
 
+    /// Operator words are made of `+ - * / = < >`, e.g. `+` or `<>`.
+    fn parse_operator(&mut self, start_pos: usize) -> Result<Option<char>, ParserError<C::Error>> {
+        let consumed = loop {
+            match self.cursor.next() {
+                Some((_, char)) => match char {
+                    c if Self::is_operator_char(c) => {}
+                    c if c == ']' || c.is_ascii_whitespace() => break Some(char),
+                    _ => return Err(ParserError::UnexpectedChar(char)),
+                },
+                None => break None,
+            }
+        };
+
+        let pos = self.cursor.offset() - if consumed.is_some() { 1 } else { 0 };
+        let symbol = self
+            .input
+            .get(start_pos..pos)
+            .ok_or(ParserError::UnexpectedError)?;
+        self.collect_word(symbol, WordKind::Word, consumed)
+            .map_err(ParserError::CollectorError)
+    }
+
+    fn is_operator_char(c: char) -> bool {
+        matches!(c, '+' | '-' | '*' | '/' | '=' | '<' | '>')
+    }
+
+    /// Character following the current one, without consuming it.
+    fn peek_char(&self) -> Option<char> {
+        self.cursor.clone().next().map(|(_, char)| char)
+    }
+
+    /// Whether the input at `pos` starts with a digit, possibly after a decimal point.
+    fn digits_at(&self, pos: usize) -> bool {
+        let rest = self.input.get(pos..).unwrap_or_default();
+        let rest = rest.strip_prefix('.').unwrap_or(rest);
+        rest.starts_with(|c: char| c.is_ascii_digit())
+    }
+
+    /// Integers, decimals with a fraction or an exponent such as `1.5`, `.5` or `1e10`, dates
+    /// such as `2025-03-01T12:00:00Z` and times such as `1:30`.
     fn parse_number(&mut self, char: char) -> Result<Option<char>, ParserError<C::Error>> {
-        let mut value: i32 = 0;
-        let mut is_negative = false;
+        let start = self.cursor.offset() - char.len_utf8();
+        let mut end = self.input.len();
         let mut has_digits = false;
+        let mut is_decimal = false;
+        let mut is_date = false;
+        let mut is_time = false;
+        let mut prev = char;
         let mut consumed = None;
 
         match char {
-            '+' => {}
-            '-' => {
-                is_negative = true;
-            }
-            c if c.is_ascii_digit() => {
-                value = c.to_digit(10).ok_or(ParserError::UnexpectedError)? as i32;
-                has_digits = true;
-            }
+            '+' | '-' => {}
+            '.' => is_decimal = true,
+            c if c.is_ascii_digit() => has_digits = true,
             _ => return Err(ParserError::UnexpectedChar(char)),
         }
 
-        for (_, char) in self.cursor.by_ref() {
+        while let Some((pos, char)) = self.cursor.next() {
             match char {
-                c if c.is_ascii_digit() => {
-                    has_digits = true;
-                    let digit = c.to_digit(10).ok_or(ParserError::UnexpectedError)? as i32;
-                    value = value
-                        .checked_mul(10)
-                        .and_then(|v| v.checked_add(digit))
-                        .ok_or(ParserError::IntegerOverflow)?;
+                c if c.is_ascii_digit() => has_digits = true,
+                '.' | 'e' | 'E' if has_digits => is_decimal = true,
+                // fraction without an integer part, e.g. `-.5`
+                '.' if matches!(prev, '+' | '-') => is_decimal = true,
+                // sign of the exponent
+                '+' | '-' if matches!(prev, 'e' | 'E') => {}
+                '-' if has_digits => is_date = true,
+                'T' | 'Z' | '+' if is_date => {}
+                ':' if has_digits && self.peek_char().is_some_and(|c| c.is_ascii_digit()) => {
+                    is_time = true
+                }
+                ']' | '/' | ':' if self.in_path => {
+                    consumed = Some(char);
+                    end = pos;
+                    break;
                 }
                 ']' => {
                     consumed = Some(char);
+                    end = pos;
+                    break;
+                }
+                _ => {
+                    end = pos;
                     break;
                 }
-                _ => break,
             }
+            prev = char;
+        }
+        if consumed == Some(':') {
+            self.path_kind = WordKind::SetWord;
         }
         if !has_digits {
             return Err(ParserError::EndOfInput);
         }
-        if is_negative {
-            value = value.checked_neg().ok_or(ParserError::IntegerOverflow)?;
+        let text = self
+            .input
+            .get(start..end)
+            .ok_or(ParserError::UnexpectedError)?;
+        if is_date {
+            let value = parse_date(text).ok_or(ParserError::InvalidDate)?;
+            self.collector.date(value)
+        } else if is_time {
+            let value = parse_time(text).ok_or(ParserError::InvalidTime)?;
+            self.collector.time(value)
+        } else if is_decimal {
+            let value = text
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .ok_or(ParserError::InvalidDecimal)?;
+            self.collector.decimal(value)
+        } else {
+            let value = text.parse().map_err(|_| ParserError::IntegerOverflow)?;
+            self.collector.integer(value)
         }
+        .map(|_| consumed)
+        .map_err(ParserError::CollectorError)
+    }
+
+    /// Binary in hex or base64 digits between braces, with whitespace allowed anywhere.
+    /// The cursor is right after the `#` of `#{`, `16#{` or `64#{`.
+    fn parse_binary(&mut self, base: u32) -> Result<Option<char>, ParserError<C::Error>> {
+        self.cursor.next(); // the opening brace
+        let start = self.cursor.offset();
+        let end = loop {
+            match self.cursor.next() {
+                Some((pos, '}')) => break pos,
+                Some(_) => {}
+                None => return Err(ParserError::EndOfInput),
+            }
+        };
+        let digits = self
+            .input
+            .get(start..end)
+            .ok_or(ParserError::UnexpectedError)?;
+        let bytes = match base {
+            64 => decode_base64(digits),
+            _ => decode_hex(digits),
+        }
+        .ok_or(ParserError::InvalidBinary)?;
+        self.collector
+            .binary(&bytes)
+            .map(|()| None)
+            .map_err(ParserError::CollectorError)
+    }
+
+    /// File such as `%src/main.rs` or `%"my file.txt"`, the cursor is right after the `%`.
+    fn parse_file(&mut self) -> Result<Option<char>, ParserError<C::Error>> {
+        if self.peek_char() == Some('"') {
+            self.cursor.next();
+            let path = self.read_string()?;
+            return self
+                .collector
+                .file(&path)
+                .map(|()| None)
+                .map_err(ParserError::CollectorError);
+        }
+        let start = self.cursor.offset();
+        let (end, consumed) = self.read_until_delimiter();
+        let path = self
+            .input
+            .get(start..end)
+            .ok_or(ParserError::UnexpectedError)?;
         self.collector
-            .integer(value)
-            .map(|_| consumed)
+            .file(path)
+            .map(|()| consumed)
             .map_err(ParserError::CollectorError)
     }
 
+    /// Url such as `ssh://user@host:22/path`, the cursor is on the `:` after the scheme.
+    fn parse_url(&mut self, start_pos: usize) -> Result<Option<char>, ParserError<C::Error>> {
+        let (end, consumed) = self.read_until_delimiter();
+        let url = self
+            .input
+            .get(start_pos..end)
+            .ok_or(ParserError::UnexpectedError)?;
+        parse_url(url).ok_or(ParserError::InvalidUrl)?;
+        self.collector
+            .url(url)
+            .map(|()| consumed)
+            .map_err(ParserError::CollectorError)
+    }
+
+    /// Skip to whitespace or the end of a block, returning where the value ends and the
+    /// delimiter consumed, if any.
+    fn read_until_delimiter(&mut self) -> (usize, Option<char>) {
+        for (pos, char) in self.cursor.by_ref() {
+            if char == ']' || char.is_ascii_whitespace() {
+                return (pos, Some(char));
+            }
+        }
+        (self.input.len(), None)
+    }
+
     fn process_block_end(&mut self, consumed: Option<char>) -> Result<(), C::Error> {
         match consumed {
             Some('/') => {}
             _ => {
                 if self.in_path {
                     self.in_path = false;
-                    self.collector.end_path()?;
+                    let kind = std::mem::replace(&mut self.path_kind, WordKind::Word);
+                    self.collector.end_path(kind)?;
                 }
             }
         }
@@ -250,9 +444,29 @@ where
                     .map(|()| None)
                     .map_err(ParserError::CollectorError)?,
                 ']' => Some(char),
-                '"' => self.parse_string(pos)?,
-                c if c.is_ascii_alphabetic() => self.parse_word(pos)?,
-                c if c.is_ascii_digit() || c == '+' || c == '-' => self.parse_number(c)?,
+                '"' => self.parse_string()?,
+                '%' => self.parse_file()?,
+                '#' if self.peek_char() == Some('{') => self.parse_binary(16)?,
+                '1' if self.input[pos..].starts_with("16#{") => {
+                    self.cursor.nth(1);
+                    self.parse_binary(16)?
+                }
+                '6' if self.input[pos..].starts_with("64#{") => {
+                    self.cursor.nth(1);
+                    self.parse_binary(64)?
+                }
+                c if c.is_ascii_alphabetic() => self.parse_word(pos, WordKind::Word)?,
+                ':' => self.parse_word(pos + 1, WordKind::GetWord)?,
+                '\'' => self.parse_word(pos + 1, WordKind::LitWord)?,
+                '+' | '-' if self.digits_at(pos + 1) => self.parse_number(char)?,
+                '.' if self.peek_char().is_some_and(|c| c.is_ascii_digit()) => {
+                    self.parse_number(char)?
+                }
+                '/' if self.peek_char().is_some_and(|c| c.is_ascii_alphabetic()) => {
+                    self.parse_word(pos + 1, WordKind::Refinement)?
+                }
+                c if c.is_ascii_digit() => self.parse_number(c)?,
+                c if Self::is_operator_char(c) => self.parse_operator(pos)?,
                 _ => return Err(ParserError::UnexpectedChar(char)),
             };
             self.process_block_end(consumed)
@@ -264,13 +478,19 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::{Collector, Parser, WordKind};
+    use super::{Collector, Parser, ParserError, WordKind};
 
-    #[derive(PartialEq, Debug)]
+    #[derive(PartialEq, Debug, Default)]
     struct TestCollector {
         pub strings: Vec<String>,
         pub words: Vec<(WordKind, String)>,
-        pub integers: Vec<i32>,
+        pub integers: Vec<i64>,
+        pub decimals: Vec<f64>,
+        pub binaries: Vec<Vec<u8>>,
+        pub dates: Vec<i64>,
+        pub times: Vec<i64>,
+        pub files: Vec<String>,
+        pub urls: Vec<String>,
     }
 
     impl Collector for TestCollector {
@@ -286,11 +506,41 @@ mod tests {
             Ok(())
         }
 
-        fn integer(&mut self, value: i32) -> Result<(), Self::Error> {
+        fn integer(&mut self, value: i64) -> Result<(), Self::Error> {
             self.integers.push(value);
             Ok(())
         }
 
+        fn decimal(&mut self, value: f64) -> Result<(), Self::Error> {
+            self.decimals.push(value);
+            Ok(())
+        }
+
+        fn binary(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+            self.binaries.push(bytes.to_vec());
+            Ok(())
+        }
+
+        fn date(&mut self, millis: i64) -> Result<(), Self::Error> {
+            self.dates.push(millis);
+            Ok(())
+        }
+
+        fn time(&mut self, millis: i64) -> Result<(), Self::Error> {
+            self.times.push(millis);
+            Ok(())
+        }
+
+        fn file(&mut self, path: &str) -> Result<(), Self::Error> {
+            self.files.push(path.to_string());
+            Ok(())
+        }
+
+        fn url(&mut self, url: &str) -> Result<(), Self::Error> {
+            self.urls.push(url.to_string());
+            Ok(())
+        }
+
         fn begin_block(&mut self) -> Result<(), Self::Error> {
             Ok(())
         }
@@ -303,7 +553,7 @@ mod tests {
             Ok(())
         }
 
-        fn end_path(&mut self) -> Result<(), Self::Error> {
+        fn end_path(&mut self, _kind: WordKind) -> Result<(), Self::Error> {
             Ok(())
         }
     }
@@ -319,11 +569,7 @@ mod tests {
                 word2
             "#;
 
-        let mut collector = TestCollector {
-            strings: vec![],
-            words: vec![],
-            integers: vec![],
-        };
+        let mut collector = TestCollector::default();
 
         let mut parser = Parser::new(input, &mut collector);
         parser.parse().unwrap();
@@ -350,11 +596,7 @@ mod tests {
             "Mixed: \t\r\n\"\\"
         "#;
 
-        let mut collector = TestCollector {
-            strings: vec![],
-            words: vec![],
-            integers: vec![],
-        };
+        let mut collector = TestCollector::default();
 
         let mut parser = Parser::new(input, &mut collector);
         parser.parse().unwrap();
@@ -376,11 +618,7 @@ mod tests {
     fn test_string_with_escaped_quotes() {
         let input = r#""This string has \"escaped quotes\"" "#;
 
-        let mut collector = TestCollector {
-            strings: vec![],
-            words: vec![],
-            integers: vec![],
-        };
+        let mut collector = TestCollector::default();
 
         let mut parser = Parser::new(input, &mut collector);
         parser.parse().unwrap();
@@ -395,15 +633,145 @@ mod tests {
     fn test_string_with_escaped_newlines() {
         let input = r#""Line1\nLine2\nLine3""#;
 
-        let mut collector = TestCollector {
-            strings: vec![],
-            words: vec![],
-            integers: vec![],
-        };
+        let mut collector = TestCollector::default();
 
         let mut parser = Parser::new(input, &mut collector);
         parser.parse().unwrap();
 
         assert_eq!(collector.strings, vec!["Line1\nLine2\nLine3"]);
     }
+
+    #[test]
+    fn test_refinements_and_type_words() {
+        let input = "f: func [a [integer! block!] /only b /local c]";
+
+        let mut collector = TestCollector::default();
+
+        let mut parser = Parser::new(input, &mut collector);
+        parser.parse().unwrap();
+
+        assert_eq!(
+            collector.words,
+            vec![
+                (WordKind::SetWord, "f".to_string()),
+                (WordKind::Word, "func".to_string()),
+                (WordKind::Word, "a".to_string()),
+                (WordKind::Word, "integer!".to_string()),
+                (WordKind::Word, "block!".to_string()),
+                (WordKind::Refinement, "only".to_string()),
+                (WordKind::Word, "b".to_string()),
+                (WordKind::Refinement, "local".to_string()),
+                (WordKind::Word, "c".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_operators() {
+        let input = "a + -1 * b / 2 <> [c >= -5] <= x/y";
+
+        let mut collector = TestCollector::default();
+
+        let mut parser = Parser::new(input, &mut collector);
+        parser.parse().unwrap();
+
+        let words: Vec<_> = collector.words.iter().map(|(_, w)| w.as_str()).collect();
+        assert_eq!(
+            words,
+            vec!["a", "+", "*", "b", "/", "<>", "c", ">=", "<=", "x", "y"]
+        );
+        assert_eq!(collector.integers, vec![-1, 2, -5]);
+    }
+
+    #[test]
+    fn test_decimals() {
+        let input = "1.5 -0.25 [1e10] 2.5E-3 .5 -.75 [+.5] +3 7";
+
+        let mut collector = TestCollector::default();
+
+        let mut parser = Parser::new(input, &mut collector);
+        parser.parse().unwrap();
+
+        assert_eq!(
+            collector.decimals,
+            vec![1.5, -0.25, 1e10, 0.0025, 0.5, -0.75, 0.5]
+        );
+        assert_eq!(collector.integers, vec![3, 7]);
+
+        let mut parser = Parser::new("1.2.3", &mut collector);
+        assert!(matches!(parser.parse(), Err(ParserError::InvalidDecimal)));
+
+        // out of range of a decimal rather than infinite
+        for input in ["1e400", "-1e400"] {
+            let mut parser = Parser::new(input, &mut collector);
+            assert!(matches!(parser.parse(), Err(ParserError::InvalidDecimal)));
+        }
+    }
+
+    #[test]
+    fn test_binaries() {
+        let input = "#{DEADBEEF} [16#{de ad} 64#{3q2+7w==}] #{} 16";
+
+        let mut collector = TestCollector::default();
+
+        let mut parser = Parser::new(input, &mut collector);
+        parser.parse().unwrap();
+
+        assert_eq!(
+            collector.binaries,
+            vec![
+                vec![0xDE, 0xAD, 0xBE, 0xEF],
+                vec![0xDE, 0xAD],
+                vec![0xDE, 0xAD, 0xBE, 0xEF],
+                vec![],
+            ]
+        );
+        assert_eq!(collector.integers, vec![16]);
+
+        let mut parser = Parser::new("#{ABC}", &mut collector);
+        assert!(matches!(parser.parse(), Err(ParserError::InvalidBinary)));
+        let mut parser = Parser::new("#{AB", &mut collector);
+        assert!(matches!(parser.parse(), Err(ParserError::EndOfInput)));
+    }
+
+    #[test]
+    fn test_dates_and_times() {
+        let input = "2025-03-01T12:00:00Z [1970-01-02 -1:30] 0:00:01.5 3 a/1: 4";
+
+        let mut collector = TestCollector::default();
+
+        let mut parser = Parser::new(input, &mut collector);
+        parser.parse().unwrap();
+
+        assert_eq!(collector.dates, vec![1740830400000, 86400000]);
+        assert_eq!(collector.times, vec![-5400000, 1500]);
+        assert_eq!(collector.integers, vec![3, 1, 4]);
+        assert!(collector.decimals.is_empty());
+
+        let mut parser = Parser::new("2025-02-30", &mut collector);
+        assert!(matches!(parser.parse(), Err(ParserError::InvalidDate)));
+        let mut parser = Parser::new("1:75", &mut collector);
+        assert!(matches!(parser.parse(), Err(ParserError::InvalidTime)));
+    }
+
+    #[test]
+    fn test_files_and_urls() {
+        let input =
+            "%src/main.rs [%\"my file.txt\"] ssh://user@host:22/path [http://example.com/] a/b";
+
+        let mut collector = TestCollector::default();
+
+        let mut parser = Parser::new(input, &mut collector);
+        parser.parse().unwrap();
+
+        assert_eq!(collector.files, vec!["src/main.rs", "my file.txt"]);
+        assert_eq!(
+            collector.urls,
+            vec!["ssh://user@host:22/path", "http://example.com/"]
+        );
+        assert_eq!(collector.words.len(), 2);
+
+        let mut parser = Parser::new("ssh://host:port", &mut collector);
+        assert!(matches!(parser.parse(), Err(ParserError::InvalidUrl)));
+    }
 }
@@ -65,13 +65,27 @@ pub struct BinTag;
 impl BinTag {
     pub const NONE: u8 = VmValue::TAG_NONE as u8;
     pub const INT: u8 = VmValue::TAG_INT as u8;
+    pub const DECIMAL: u8 = VmValue::TAG_DECIMAL as u8;
+    pub const INT64: u8 = VmValue::TAG_INT64 as u8;
+    pub const DATE: u8 = VmValue::TAG_DATE as u8;
+    pub const TIME: u8 = VmValue::TAG_TIME as u8;
     pub const BLOCK: u8 = VmValue::TAG_BLOCK as u8;
+    pub const PATH: u8 = VmValue::TAG_PATH as u8;
+    pub const SET_PATH: u8 = VmValue::TAG_SET_PATH as u8;
+    pub const GET_PATH: u8 = VmValue::TAG_GET_PATH as u8;
     pub const CONTEXT: u8 = VmValue::TAG_CONTEXT as u8;
     pub const INLINE_STRING: u8 = VmValue::TAG_INLINE_STRING as u8;
+    pub const BINARY: u8 = VmValue::TAG_BINARY as u8;
+    pub const FILE: u8 = VmValue::TAG_FILE as u8;
+    pub const URL: u8 = VmValue::TAG_URL as u8;
     pub const WORD: u8 = VmValue::TAG_WORD as u8;
     pub const SET_WORD: u8 = VmValue::TAG_SET_WORD as u8;
     pub const GET_WORD: u8 = VmValue::TAG_GET_WORD as u8;
+    pub const LIT_WORD: u8 = VmValue::TAG_LIT_WORD as u8;
     pub const BOOL: u8 = VmValue::TAG_BOOL as u8;
+    pub const ERROR: u8 = VmValue::TAG_ERROR as u8;
+    pub const REFINEMENT: u8 = VmValue::TAG_REFINEMENT as u8;
+    pub const FUNC: u8 = VmValue::TAG_FUNC as u8;
 }
 
 // ============================================================================
@@ -89,12 +103,33 @@ pub trait Serializer {
     /// Handle serialization of integer value
     fn integer(&mut self, value: i32) -> Result<(), Self::Error>;
 
+    /// Handle serialization of integer value that doesn't fit in an i32
+    fn int64(&mut self, value: i64) -> Result<(), Self::Error>;
+
+    /// Handle serialization of decimal value
+    fn decimal(&mut self, value: f64) -> Result<(), Self::Error>;
+
+    /// Handle serialization of date value, in milliseconds since the Unix epoch
+    fn date(&mut self, value: i64) -> Result<(), Self::Error>;
+
+    /// Handle serialization of time value, in milliseconds
+    fn time(&mut self, value: i64) -> Result<(), Self::Error>;
+
     /// Handle serialization of boolean value
     fn bool(&mut self, value: bool) -> Result<(), Self::Error>;
 
     /// Handle serialization of string value
     fn string(&mut self, value: &str) -> Result<(), Self::Error>;
 
+    /// Handle serialization of binary value
+    fn binary(&mut self, value: &[u8]) -> Result<(), Self::Error>;
+
+    /// Handle serialization of file value
+    fn file(&mut self, value: &str) -> Result<(), Self::Error>;
+
+    /// Handle serialization of url value
+    fn url(&mut self, value: &str) -> Result<(), Self::Error>;
+
     /// Handle serialization of word value
     fn word(&mut self, value: &str) -> Result<(), Self::Error>;
 
@@ -104,12 +139,30 @@ pub trait Serializer {
     /// Handle serialization of get-word value
     fn get_word(&mut self, value: &str) -> Result<(), Self::Error>;
 
+    /// Handle serialization of lit-word value
+    fn lit_word(&mut self, value: &str) -> Result<(), Self::Error>;
+
+    /// Handle serialization of refinement value
+    fn refinement(&mut self, value: &str) -> Result<(), Self::Error>;
+
     /// Begin serializing a block
     fn begin_block(&mut self, len: usize) -> Result<(), Self::Error>;
 
     /// End serializing a block
     fn end_block(&mut self) -> Result<(), Self::Error>;
 
+    /// Begin serializing a path; its segments follow as values
+    fn begin_path(&mut self, len: usize) -> Result<(), Self::Error>;
+
+    /// Begin serializing a set-path; its segments follow as values
+    fn begin_set_path(&mut self, len: usize) -> Result<(), Self::Error>;
+
+    /// Begin serializing a get-path; its segments follow as values
+    fn begin_get_path(&mut self, len: usize) -> Result<(), Self::Error>;
+
+    /// End serializing a path of any kind
+    fn end_path(&mut self) -> Result<(), Self::Error>;
+
     /// Begin serializing a context
     fn begin_context(&mut self, len: usize) -> Result<(), Self::Error>;
 
@@ -118,6 +171,18 @@ pub trait Serializer {
 
     /// End serializing a context
     fn end_context(&mut self) -> Result<(), Self::Error>;
+
+    /// Begin serializing an error; its fields follow as context keys and values
+    fn begin_error(&mut self, len: usize) -> Result<(), Self::Error>;
+
+    /// End serializing an error
+    fn end_error(&mut self) -> Result<(), Self::Error>;
+
+    /// Begin serializing a function; its spec and body follow as blocks
+    fn begin_func(&mut self) -> Result<(), Self::Error>;
+
+    /// End serializing a function
+    fn end_func(&mut self) -> Result<(), Self::Error>;
 }
 
 /// Extension trait for Value to add serialization capabilities
@@ -131,11 +196,20 @@ impl ValueSerialize for Value {
         match self {
             Value::None => serializer.none(),
             Value::Int(n) => serializer.integer(*n),
+            Value::Int64(n) => serializer.int64(*n),
+            Value::Decimal(n) => serializer.decimal(*n),
+            Value::Date(millis) => serializer.date(*millis),
+            Value::Time(millis) => serializer.time(*millis),
             Value::Bool(b) => serializer.bool(*b),
             Value::String(s) => serializer.string(s),
+            Value::Binary(bytes) => serializer.binary(bytes),
+            Value::File(path) => serializer.file(path),
+            Value::Url(url) => serializer.url(url),
             Value::Word(w) => serializer.word(w),
             Value::SetWord(w) => serializer.set_word(w),
             Value::GetWord(w) => serializer.get_word(w),
+            Value::LitWord(w) => serializer.lit_word(w),
+            Value::Refinement(w) => serializer.refinement(w),
             Value::Block(block) => {
                 serializer.begin_block(block.len())?;
                 for item in block.iter() {
@@ -151,7 +225,46 @@ impl ValueSerialize for Value {
                 }
                 serializer.end_context()
             }
-            Value::Path(_) => unimplemented!("Path serialization not supported"),
+            Value::Error(pairs) => {
+                serializer.begin_error(pairs.len())?;
+                for (key, value) in pairs.iter() {
+                    serializer.context_key(key)?;
+                    value.serialize(serializer)?;
+                }
+                serializer.end_error()
+            }
+            Value::Path(path) => {
+                serializer.begin_path(path.len())?;
+                for segment in path.iter() {
+                    segment.serialize(serializer)?;
+                }
+                serializer.end_path()
+            }
+            Value::SetPath(path) => {
+                serializer.begin_set_path(path.len())?;
+                for segment in path.iter() {
+                    segment.serialize(serializer)?;
+                }
+                serializer.end_path()
+            }
+            Value::GetPath(path) => {
+                serializer.begin_get_path(path.len())?;
+                for segment in path.iter() {
+                    segment.serialize(serializer)?;
+                }
+                serializer.end_path()
+            }
+            Value::Func(spec, body) => {
+                serializer.begin_func()?;
+                for block in [spec, body] {
+                    serializer.begin_block(block.len())?;
+                    for item in block.iter() {
+                        item.serialize(serializer)?;
+                    }
+                    serializer.end_block()?;
+                }
+                serializer.end_func()
+            }
         }
     }
 }
@@ -180,6 +293,7 @@ pub enum BinarySerializerError {
 /// - BinTag::WORD: Word (length + UTF-8 bytes)
 /// - BinTag::SET_WORD: SetWord (length + UTF-8 bytes)
 /// - BinTag::BLOCK: Block (length + contents)
+/// - BinTag::CONTEXT, BinTag::ERROR: Context or Error (length + key-value pairs)
 pub struct BinarySerializer<W: Write> {
     writer: W,
 }
@@ -200,6 +314,16 @@ impl<W: Write> BinarySerializer<W> {
         Ok(())
     }
 
+    /// Write a variable-length encoded i64, see [`encoding::encode_i64`]
+    fn write_i64(&mut self, value: i64) -> Result<(), BinarySerializerError> {
+        let mut buffer = [0u8; 9];
+        let len = encoding::encode_i64(value, &mut buffer).ok_or_else(|| {
+            BinarySerializerError::SerializeError("Failed to encode integer".into())
+        })?;
+        self.writer.write_all(&buffer[..len])?;
+        Ok(())
+    }
+
     /// Write a string with its length prefix
     fn write_string(&mut self, s: &str) -> Result<(), BinarySerializerError> {
         // Write the string length
@@ -230,6 +354,27 @@ impl<W: Write> Serializer for BinarySerializer<W> {
         self.write_varint(value)
     }
 
+    fn int64(&mut self, value: i64) -> Result<(), Self::Error> {
+        self.writer.write_all(&[BinTag::INT64])?;
+        self.write_i64(value)
+    }
+
+    fn decimal(&mut self, value: f64) -> Result<(), Self::Error> {
+        self.writer.write_all(&[BinTag::DECIMAL])?;
+        self.writer.write_all(&value.to_le_bytes())?;
+        Ok(())
+    }
+
+    fn date(&mut self, value: i64) -> Result<(), Self::Error> {
+        self.writer.write_all(&[BinTag::DATE])?;
+        self.write_i64(value)
+    }
+
+    fn time(&mut self, value: i64) -> Result<(), Self::Error> {
+        self.writer.write_all(&[BinTag::TIME])?;
+        self.write_i64(value)
+    }
+
     fn bool(&mut self, value: bool) -> Result<(), Self::Error> {
         self.writer
             .write_all(&[BinTag::BOOL, if value { 1 } else { 0 }])
@@ -243,6 +388,23 @@ impl<W: Write> Serializer for BinarySerializer<W> {
         self.write_string(value)
     }
 
+    fn binary(&mut self, value: &[u8]) -> Result<(), Self::Error> {
+        self.writer.write_all(&[BinTag::BINARY])?;
+        self.write_varint(value.len() as i32)?;
+        self.writer.write_all(value)?;
+        Ok(())
+    }
+
+    fn file(&mut self, value: &str) -> Result<(), Self::Error> {
+        self.writer.write_all(&[BinTag::FILE])?;
+        self.write_string(value)
+    }
+
+    fn url(&mut self, value: &str) -> Result<(), Self::Error> {
+        self.writer.write_all(&[BinTag::URL])?;
+        self.write_string(value)
+    }
+
     fn word(&mut self, value: &str) -> Result<(), Self::Error> {
         // Write tag
         self.writer.write_all(&[BinTag::WORD])?;
@@ -262,6 +424,16 @@ impl<W: Write> Serializer for BinarySerializer<W> {
         self.write_string(value)
     }
 
+    fn lit_word(&mut self, value: &str) -> Result<(), Self::Error> {
+        self.writer.write_all(&[BinTag::LIT_WORD])?;
+        self.write_string(value)
+    }
+
+    fn refinement(&mut self, value: &str) -> Result<(), Self::Error> {
+        self.writer.write_all(&[BinTag::REFINEMENT])?;
+        self.write_string(value)
+    }
+
     fn begin_block(&mut self, len: usize) -> Result<(), Self::Error> {
         // Write tag
         self.writer.write_all(&[BinTag::BLOCK])?;
@@ -274,6 +446,26 @@ impl<W: Write> Serializer for BinarySerializer<W> {
         Ok(())
     }
 
+    fn begin_path(&mut self, len: usize) -> Result<(), Self::Error> {
+        // Same layout as a block, only the tag differs
+        self.writer.write_all(&[BinTag::PATH])?;
+        self.write_varint(len as i32)
+    }
+
+    fn begin_set_path(&mut self, len: usize) -> Result<(), Self::Error> {
+        self.writer.write_all(&[BinTag::SET_PATH])?;
+        self.write_varint(len as i32)
+    }
+
+    fn begin_get_path(&mut self, len: usize) -> Result<(), Self::Error> {
+        self.writer.write_all(&[BinTag::GET_PATH])?;
+        self.write_varint(len as i32)
+    }
+
+    fn end_path(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
     fn begin_context(&mut self, len: usize) -> Result<(), Self::Error> {
         // Write tag
         self.writer.write_all(&[BinTag::CONTEXT])?;
@@ -290,6 +482,24 @@ impl<W: Write> Serializer for BinarySerializer<W> {
         // No additional data needed for end_context in binary format
         Ok(())
     }
+
+    fn begin_error(&mut self, len: usize) -> Result<(), Self::Error> {
+        // Same layout as a context, only the tag differs
+        self.writer.write_all(&[BinTag::ERROR])?;
+        self.write_varint(len as i32)
+    }
+
+    fn end_error(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn begin_func(&mut self) -> Result<(), Self::Error> {
+        self.writer.write_all(&[BinTag::FUNC]).map_err(Into::into)
+    }
+
+    fn end_func(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
 }
 
 /// Serialize a Value to a Vec<u8>
@@ -383,6 +593,19 @@ impl<R: Read> BinaryDeserializer<R> {
         Ok(value)
     }
 
+    /// Read a variable-length encoded i64, see [`encoding::decode_i64`]
+    fn read_i64(&mut self) -> Result<i64, BinaryDeserializerError> {
+        let first_byte = self.read_byte()?;
+        let len = encoding::encoded_i64_len(first_byte)
+            .ok_or(BinaryDeserializerError::InvalidTag(first_byte))?;
+        let mut buffer = [0u8; 9];
+        buffer[0] = first_byte;
+        self.reader.read_exact(&mut buffer[1..len])?;
+        let (value, _) = encoding::decode_i64(&buffer[..len])
+            .ok_or(BinaryDeserializerError::InvalidIntegerEncoding)?;
+        Ok(value)
+    }
+
     /// Read a string with its length prefix
     fn read_string(&mut self) -> Result<String, BinaryDeserializerError> {
         // Read the length
@@ -399,6 +622,53 @@ impl<R: Read> BinaryDeserializer<R> {
         String::from_utf8(buffer).map_err(|_| BinaryDeserializerError::InvalidUtf8)
     }
 
+    /// Read the key-value pairs of a context or an error
+    fn read_pairs(&mut self) -> Result<Box<[(SmolStr, Value)]>, BinaryDeserializerError> {
+        let len = self.read_varint()?;
+        if len < 0 {
+            return Err(BinaryDeserializerError::NegativeLength);
+        }
+
+        // Read each key-value pair in the context
+        let mut pairs = Vec::with_capacity(len as usize);
+        for _ in 0..len {
+            // Read key
+            let key_str = self.read_string()?;
+            let key = SmolStr::new(key_str);
+
+            // Read value
+            let value = self.read_value()?;
+
+            pairs.push((key, value));
+        }
+
+        Ok(pairs.into_boxed_slice())
+    }
+
+    /// Read the values of a block or a path
+    fn read_values(&mut self) -> Result<Box<[Value]>, BinaryDeserializerError> {
+        let len = self.read_varint()?;
+        if len < 0 {
+            return Err(BinaryDeserializerError::NegativeLength);
+        }
+
+        // Read each value in the block
+        let mut values = Vec::with_capacity(len as usize);
+        for _ in 0..len {
+            values.push(self.read_value()?);
+        }
+
+        Ok(values.into_boxed_slice())
+    }
+
+    /// Read a block, the only value a function's spec and body can be
+    fn read_block(&mut self) -> Result<Box<[Value]>, BinaryDeserializerError> {
+        match self.read_byte()? {
+            BinTag::BLOCK => self.read_values(),
+            tag => Err(BinaryDeserializerError::InvalidTag(tag)),
+        }
+    }
+
     /// Read a single value from the reader
     pub fn read_value(&mut self) -> Result<Value, BinaryDeserializerError> {
         let tag = self.read_byte()?;
@@ -411,11 +681,34 @@ impl<R: Read> BinaryDeserializer<R> {
                 Ok(Value::Int(value))
             }
 
+            BinTag::INT64 => Ok(Value::integer(self.read_i64()?)),
+            BinTag::DATE => Ok(Value::Date(self.read_i64()?)),
+            BinTag::TIME => Ok(Value::Time(self.read_i64()?)),
+
+            BinTag::DECIMAL => {
+                let mut bytes = [0u8; 8];
+                self.reader.read_exact(&mut bytes)?;
+                Ok(Value::Decimal(f64::from_le_bytes(bytes)))
+            }
+
             BinTag::INLINE_STRING => {
                 let value = self.read_string()?;
                 Ok(Value::String(SmolStr::new(value)))
             }
 
+            BinTag::FILE => Ok(Value::File(SmolStr::new(self.read_string()?))),
+            BinTag::URL => Ok(Value::Url(SmolStr::new(self.read_string()?))),
+
+            BinTag::BINARY => {
+                let len = self.read_varint()?;
+                if len < 0 {
+                    return Err(BinaryDeserializerError::NegativeLength);
+                }
+                let mut bytes = vec![0u8; len as usize];
+                self.reader.read_exact(&mut bytes)?;
+                Ok(Value::Binary(bytes.into()))
+            }
+
             BinTag::WORD => {
                 let value = self.read_string()?;
                 Ok(Value::Word(SmolStr::new(value)))
@@ -431,41 +724,28 @@ impl<R: Read> BinaryDeserializer<R> {
                 Ok(Value::GetWord(SmolStr::new(value)))
             }
 
-            BinTag::BLOCK => {
-                let len = self.read_varint()?;
-                if len < 0 {
-                    return Err(BinaryDeserializerError::NegativeLength);
-                }
-
-                // Read each value in the block
-                let mut values = Vec::with_capacity(len as usize);
-                for _ in 0..len {
-                    values.push(self.read_value()?);
-                }
-
-                Ok(Value::Block(values.into_boxed_slice()))
+            BinTag::LIT_WORD => {
+                let value = self.read_string()?;
+                Ok(Value::LitWord(SmolStr::new(value)))
             }
 
-            BinTag::CONTEXT => {
-                let len = self.read_varint()?;
-                if len < 0 {
-                    return Err(BinaryDeserializerError::NegativeLength);
-                }
+            BinTag::REFINEMENT => {
+                let value = self.read_string()?;
+                Ok(Value::Refinement(SmolStr::new(value)))
+            }
 
-                // Read each key-value pair in the context
-                let mut pairs = Vec::with_capacity(len as usize);
-                for _ in 0..len {
-                    // Read key
-                    let key_str = self.read_string()?;
-                    let key = SmolStr::new(key_str);
+            BinTag::BLOCK => self.read_values().map(Value::Block),
+            BinTag::PATH => self.read_values().map(Value::Path),
+            BinTag::SET_PATH => self.read_values().map(Value::SetPath),
+            BinTag::GET_PATH => self.read_values().map(Value::GetPath),
 
-                    // Read value
-                    let value = self.read_value()?;
+            BinTag::CONTEXT => self.read_pairs().map(Value::Context),
+            BinTag::ERROR => self.read_pairs().map(Value::Error),
 
-                    pairs.push((key, value));
-                }
-
-                Ok(Value::Context(pairs.into_boxed_slice()))
+            BinTag::FUNC => {
+                let spec = self.read_block()?;
+                let body = self.read_block()?;
+                Ok(Value::Func(spec, body))
             }
 
             _ => Err(BinaryDeserializerError::InvalidTag(tag)),
@@ -844,6 +1124,25 @@ mod tests {
         assert_eq!(outer_context, roundtrip);
     }
 
+    #[test]
+    fn test_error_roundtrip() {
+        let error = Value::Error(Box::new([
+            (SmolStr::new("kind"), Value::Word("bad-arguments".into())),
+            (
+                SmolStr::new("message"),
+                Value::String("bad arguments".into()),
+            ),
+            (SmolStr::new("word"), Value::Word("add".into())),
+        ]));
+
+        let bytes = to_bytes(&error).unwrap();
+        assert_eq!(bytes[0], BinTag::ERROR);
+        assert_eq!(bytes[1], 3);
+
+        let roundtrip = from_bytes(&bytes).unwrap();
+        assert_eq!(error, roundtrip);
+    }
+
     #[test]
     fn test_deserialize_invalid_tag() {
         let bytes = [100]; // Invalid tag
@@ -941,4 +1240,75 @@ mod tests {
         // Verify
         assert_eq!(value, deserialized);
     }
+
+    #[test]
+    fn test_refinement_roundtrip() {
+        let value = Value::Refinement("local".into());
+        let bytes = to_bytes(&value).unwrap();
+        assert_eq!(bytes[0], BinTag::REFINEMENT);
+        assert_eq!(from_bytes(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn test_int64_roundtrip() {
+        let value = Value::block([Value::Int64(i64::MIN), Value::Int64(1 << 40)]);
+        let bytes = to_bytes(&value).unwrap();
+        assert_eq!(bytes[2], BinTag::INT64);
+        assert_eq!(from_bytes(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn test_decimal_roundtrip() {
+        let value = Value::block([Value::Decimal(-0.25), Value::Decimal(1e10)]);
+        let bytes = to_bytes(&value).unwrap();
+        assert_eq!(bytes[2], BinTag::DECIMAL);
+        assert_eq!(from_bytes(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn test_binary_roundtrip() {
+        let value = Value::block([Value::binary([0xDE, 0xAD, 0xBE, 0xEF]), Value::binary([])]);
+        let bytes = to_bytes(&value).unwrap();
+        assert_eq!(bytes[2], BinTag::BINARY);
+        assert_eq!(from_bytes(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn test_date_time_roundtrip() {
+        let value = Value::block([Value::Date(1740830400000), Value::Time(-5400000)]);
+        let bytes = to_bytes(&value).unwrap();
+        assert_eq!(bytes[2], BinTag::DATE);
+        assert_eq!(from_bytes(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn test_path_roundtrip() {
+        let value = parse("[a/b/1 c/d: :e/f]").unwrap();
+        let bytes = to_bytes(&value).unwrap();
+        assert_eq!(from_bytes(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn test_file_url_roundtrip() {
+        let value = Value::block([Value::file("src/main.rs"), Value::url("ssh://host:22")]);
+        let bytes = to_bytes(&value).unwrap();
+        assert_eq!(bytes[2], BinTag::FILE);
+        assert_eq!(from_bytes(&bytes).unwrap(), value);
+    }
+    #[test]
+    fn test_func_roundtrip() {
+        let spec = [Value::word("x"), Value::block([Value::word("integer!")])];
+        let body = [Value::word("x"), Value::word("+"), Value::int(1)];
+        let value = Value::Func(spec.into(), body.into());
+        let bytes = to_bytes(&value).unwrap();
+        assert_eq!(bytes[..2], [BinTag::FUNC, BinTag::BLOCK]);
+        assert_eq!(from_bytes(&bytes).unwrap(), value);
+
+        // the spec and body must be blocks
+        let bytes = [BinTag::FUNC, BinTag::INT, 1, BinTag::BLOCK, 0];
+        assert!(matches!(
+            from_bytes(&bytes),
+            Err(BinaryDeserializerError::InvalidTag(BinTag::INT))
+        ));
+    }
 }
@@ -0,0 +1,957 @@
+// RebelDB™ © 2025 Huly Labs • https://hulylabs.com • SPDX-License-Identifier: MIT
+
+//! Natives working on series: blocks of values, strings of characters and binaries of
+//! bytes.
+//!
+//! Series have no position of their own, so natives returning part of a series, such as
+//! `skip` and `find`, return a copy of that part. Natives modifying a series change it
+//! in place and return it. Elements of strings are one character strings, elements of
+//! binaries are integers from 0 to 255.
+
+use crate::boot::equal_values;
+use crate::core::{CoreError, Exec, MemValue, Module, NativeFn, VmValue};
+use crate::encoding::{decode_base64, decode_hex, encode_base64, encode_hex};
+use crate::mem::{Offset, Storage, Word};
+use crate::native;
+use crate::native::{Block, Function, Name, Series};
+use crate::value::Value;
+use smol_str::SmolStr;
+
+fn string_chars<T: Storage>(exec: &Exec<T>, string: Word) -> Result<Vec<char>, CoreError> {
+    match exec.to_value(VmValue::String(string))? {
+        Value::String(string) => Ok(string.chars().collect()),
+        _ => Err(CoreError::InternalError),
+    }
+}
+
+fn set_string_chars<T: Storage>(
+    exec: &mut Exec<T>,
+    string: Word,
+    chars: &[char],
+) -> Result<(), CoreError> {
+    let value: String = chars.iter().collect();
+    exec.set_string(string, &value).map_err(Into::into)
+}
+
+fn block_values<T: Storage>(exec: &Exec<T>, block: Word) -> Result<Vec<MemValue>, CoreError> {
+    Ok(exec
+        .get_block_data(block)?
+        .chunks_exact(2)
+        .map(|pair| [pair[0], pair[1]])
+        .collect())
+}
+
+fn set_block_values<T: Storage>(
+    exec: &mut Exec<T>,
+    block: Word,
+    values: &[MemValue],
+) -> Result<(), CoreError> {
+    let len = exec.get_block_len(block)?;
+    exec.splice(block, 0, len, values.as_flattened())
+        .map_err(Into::into)
+}
+
+/// Bytes of a value added to a binary: the bytes of a binary, the UTF-8 bytes of a
+/// string, an integer as one byte, or a block of such integers.
+fn value_bytes<T: Storage>(exec: &Exec<T>, value: VmValue) -> Result<Vec<u8>, CoreError> {
+    match exec.to_value(value)? {
+        Value::Binary(bytes) => Ok(bytes.into_vec()),
+        Value::String(string) => Ok(string.as_bytes().to_vec()),
+        Value::Block(values) => values.iter().map(byte_value).collect(),
+        value => byte_value(&value).map(|byte| vec![byte]),
+    }
+}
+
+fn byte_value(value: &Value) -> Result<u8, CoreError> {
+    match value {
+        Value::Int(n) => u8::try_from(*n).map_err(|_| CoreError::BadArguments),
+        _ => Err(CoreError::BadArguments),
+    }
+}
+
+fn series_len<T: Storage>(exec: &Exec<T>, series: Series) -> Result<usize, CoreError> {
+    match series {
+        Series::Block(block) => Ok(exec.get_block_len(block)? / 2),
+        Series::String(string) => Ok(string_chars(exec, string)?.len()),
+        Series::Binary(binary) => Ok(exec.get_binary(binary)?.len()),
+    }
+}
+
+/// Element at `index`, starting at 1, or none past either end of the series.
+fn pick_at<T: Storage>(exec: &mut Exec<T>, series: Series, index: i32) -> Result<(), CoreError> {
+    let value = match (index.checked_sub(1), series) {
+        (Some(index @ 0..), Series::Block(block)) => exec
+            .get_block::<2>(block, index as Word * 2)
+            .unwrap_or([VmValue::TAG_NONE, 0]),
+        (Some(index @ 0..), Series::String(string)) => {
+            match string_chars(exec, string)?.get(index as usize) {
+                Some(char) => {
+                    let string = exec.alloc_string(char.encode_utf8(&mut [0; 4]))?;
+                    [VmValue::TAG_INLINE_STRING, string]
+                }
+                None => [VmValue::TAG_NONE, 0],
+            }
+        }
+        (Some(index @ 0..), Series::Binary(binary)) => {
+            match exec.get_binary(binary)?.get(index as usize) {
+                Some(byte) => [VmValue::TAG_INT, *byte as Word],
+                None => [VmValue::TAG_NONE, 0],
+            }
+        }
+        _ => [VmValue::TAG_NONE, 0],
+    };
+    exec.push(value).map_err(Into::into)
+}
+
+/// Insert `value` before the element at `at`, counting from 0. Blocks get the values of
+/// a block rather than the block itself, strings get the value formed, binaries get the
+/// bytes of the value.
+fn insert_at<T: Storage>(
+    exec: &mut Exec<T>,
+    series: Series,
+    at: usize,
+    value: VmValue,
+) -> Result<(), CoreError> {
+    match series {
+        Series::Block(block) => {
+            let values = match value {
+                VmValue::Block(values) => exec.get_block_data(values)?.to_vec(),
+                value => value.vm_repr().to_vec(),
+            };
+            exec.splice(block, at * 2, 0, &values).map_err(Into::into)
+        }
+        Series::String(string) => {
+            let text = exec.to_value(value)?.form();
+            let mut chars = string_chars(exec, string)?;
+            chars.splice(at..at, text.chars());
+            set_string_chars(exec, string, &chars)
+        }
+        Series::Binary(binary) => {
+            let inserted = value_bytes(exec, value)?;
+            let mut bytes = exec.get_binary(binary)?;
+            bytes.splice(at..at, inserted);
+            exec.set_binary(binary, &bytes).map_err(Into::into)
+        }
+    }
+}
+
+/// New series with the elements from `start` up to `end`, counting from 0.
+fn copy_range<T: Storage>(
+    exec: &mut Exec<T>,
+    series: Series,
+    start: usize,
+    end: usize,
+) -> Result<Series, CoreError> {
+    match series {
+        Series::Block(block) => {
+            let values = exec.get_block_data(block)?;
+            let part = values.get(start * 2..end * 2).unwrap_or_default().to_vec();
+            Ok(Series::Block(exec.alloc_block(&part)?))
+        }
+        Series::String(string) => {
+            let chars = string_chars(exec, string)?;
+            let part: String = chars.get(start..end).unwrap_or_default().iter().collect();
+            Ok(Series::String(exec.alloc_string(&part)?))
+        }
+        Series::Binary(binary) => {
+            let bytes = exec.get_binary(binary)?;
+            let part = bytes.get(start..end).unwrap_or_default();
+            Ok(Series::Binary(exec.alloc_binary(part)?))
+        }
+    }
+}
+
+#[native]
+fn first<T: Storage>(exec: &mut Exec<T>, series: Series) -> Result<(), CoreError> {
+    pick_at(exec, series, 1)
+}
+
+#[native]
+fn last<T: Storage>(exec: &mut Exec<T>, series: Series) -> Result<(), CoreError> {
+    let len = series_len(exec, series)?;
+    pick_at(exec, series, len as i32)
+}
+
+#[native]
+fn pick<T: Storage>(exec: &mut Exec<T>, series: Series, index: i32) -> Result<(), CoreError> {
+    pick_at(exec, series, index)
+}
+
+/// Replace the element at `index`, starting at 1, and return the new value. Elements of
+/// strings can only be replaced by one character strings, bytes by integers up to 255.
+#[native]
+fn poke<T: Storage>(
+    exec: &mut Exec<T>,
+    series: Series,
+    index: i32,
+    value: VmValue,
+) -> Result<VmValue, CoreError> {
+    let position = match index.checked_sub(1) {
+        Some(position @ 0..) if (position as usize) < series_len(exec, series)? => {
+            position as usize
+        }
+        _ => return Err(CoreError::BoundsCheckFailed),
+    };
+    match series {
+        Series::Block(block) => exec.splice(block, position * 2, 2, &value.vm_repr())?,
+        Series::String(string) => {
+            let char = match exec.to_value(value)? {
+                Value::String(text) if text.chars().count() == 1 => text.chars().next(),
+                _ => None,
+            };
+            let mut chars = string_chars(exec, string)?;
+            chars[position] = char.ok_or(CoreError::BadArguments)?;
+            set_string_chars(exec, string, &chars)?;
+        }
+        Series::Binary(binary) => {
+            let mut bytes = exec.get_binary(binary)?;
+            bytes[position] = byte_value(&exec.to_value(value)?)?;
+            exec.set_binary(binary, &bytes)?;
+        }
+    }
+    Ok(value)
+}
+
+#[native(name = "length?")]
+fn length<T: Storage>(exec: &mut Exec<T>, series: Series) -> Result<i32, CoreError> {
+    series_len(exec, series).map(|len| len as i32)
+}
+
+#[native(name = "empty?")]
+fn empty<T: Storage>(exec: &mut Exec<T>, series: Series) -> Result<bool, CoreError> {
+    series_len(exec, series).map(|len| len == 0)
+}
+
+#[native]
+fn append<T: Storage>(
+    exec: &mut Exec<T>,
+    series: Series,
+    value: VmValue,
+) -> Result<Series, CoreError> {
+    let len = series_len(exec, series)?;
+    insert_at(exec, series, len, value)?;
+    Ok(series)
+}
+
+#[native]
+fn insert<T: Storage>(
+    exec: &mut Exec<T>,
+    series: Series,
+    value: VmValue,
+) -> Result<Series, CoreError> {
+    insert_at(exec, series, 0, value)?;
+    Ok(series)
+}
+
+/// Remove the first element, if any.
+#[native]
+fn remove<T: Storage>(exec: &mut Exec<T>, series: Series) -> Result<Series, CoreError> {
+    match series {
+        Series::Block(block) => {
+            let len = exec.get_block_len(block)?;
+            exec.splice(block, 0, len.min(2), &[])?;
+        }
+        Series::String(string) => {
+            let chars = string_chars(exec, string)?;
+            set_string_chars(exec, string, chars.get(1..).unwrap_or_default())?;
+        }
+        Series::Binary(binary) => {
+            let bytes = exec.get_binary(binary)?;
+            exec.set_binary(binary, bytes.get(1..).unwrap_or_default())?;
+        }
+    }
+    Ok(series)
+}
+
+#[native]
+fn copy<T: Storage>(exec: &mut Exec<T>, series: Series) -> Result<Series, CoreError> {
+    let len = series_len(exec, series)?;
+    copy_range(exec, series, 0, len)
+}
+
+/// Copy of the first `length` elements.
+#[native(name = "copy/part")]
+fn copy_part<T: Storage>(
+    exec: &mut Exec<T>,
+    series: Series,
+    length: i32,
+) -> Result<Series, CoreError> {
+    let length = usize::try_from(length).map_err(|_| CoreError::BadArguments)?;
+    let len = series_len(exec, series)?;
+    copy_range(exec, series, 0, length.min(len))
+}
+
+/// Copy of the series without its first `offset` elements.
+#[native]
+fn skip<T: Storage>(exec: &mut Exec<T>, series: Series, offset: i32) -> Result<Series, CoreError> {
+    let len = series_len(exec, series)?;
+    let start = (offset.max(0) as usize).min(len);
+    copy_range(exec, series, start, len)
+}
+
+/// Copy of the series from the first element equal to `value`, or from the first match
+/// of `value` formed in a string, or of its bytes in a binary. None if there is no match.
+#[native]
+fn find<T: Storage>(
+    exec: &mut Exec<T>,
+    series: Series,
+    value: VmValue,
+) -> Result<Option<Series>, CoreError> {
+    let start = match series {
+        Series::Block(block) => {
+            let mut found = None;
+            for (index, item) in block_values(exec, block)?.into_iter().enumerate() {
+                if equal_values(exec, VmValue::try_from(item)?, value)? {
+                    found = Some(index);
+                    break;
+                }
+            }
+            found
+        }
+        Series::String(string) => {
+            let chars = string_chars(exec, string)?;
+            let pattern: Vec<char> = exec.to_value(value)?.form().chars().collect();
+            (0..=chars.len().saturating_sub(pattern.len()))
+                .find(|&start| chars[start..].starts_with(&pattern))
+        }
+        Series::Binary(binary) => {
+            let bytes = exec.get_binary(binary)?;
+            let pattern = value_bytes(exec, value)?;
+            (0..=bytes.len().saturating_sub(pattern.len()))
+                .find(|&start| bytes[start..].starts_with(&pattern))
+        }
+    };
+    match start {
+        Some(start) => {
+            let len = series_len(exec, series)?;
+            copy_range(exec, series, start, len).map(Some)
+        }
+        None => Ok(None),
+    }
+}
+
+#[native]
+fn reverse<T: Storage>(exec: &mut Exec<T>, series: Series) -> Result<Series, CoreError> {
+    match series {
+        Series::Block(block) => {
+            let mut values = block_values(exec, block)?;
+            values.reverse();
+            set_block_values(exec, block, &values)?;
+        }
+        Series::String(string) => {
+            let mut chars = string_chars(exec, string)?;
+            chars.reverse();
+            set_string_chars(exec, string, &chars)?;
+        }
+        Series::Binary(binary) => {
+            let mut bytes = exec.get_binary(binary)?;
+            bytes.reverse();
+            exec.set_binary(binary, &bytes)?;
+        }
+    }
+    Ok(series)
+}
+
+/// Order of block elements in `sort`: by type, then integers and logic values by value,
+/// strings and words by text. Other values of the same type keep their order.
+fn sort_key<T: Storage>(
+    exec: &Exec<T>,
+    value: MemValue,
+) -> Result<(Word, i32, SmolStr), CoreError> {
+    let [tag, data] = value;
+    match tag {
+        VmValue::TAG_INT | VmValue::TAG_BOOL => Ok((tag, data as i32, SmolStr::default())),
+        VmValue::TAG_INLINE_STRING => match exec.to_value(VmValue::String(data))? {
+            Value::String(string) => Ok((tag, 0, string)),
+            _ => Err(CoreError::InternalError),
+        },
+        VmValue::TAG_WORD
+        | VmValue::TAG_SET_WORD
+        | VmValue::TAG_GET_WORD
+        | VmValue::TAG_LIT_WORD
+        | VmValue::TAG_REFINEMENT => Ok((tag, 0, exec.get_symbol(data)?)),
+        _ => Ok((tag, 0, SmolStr::default())),
+    }
+}
+
+/// Sort in place, in ascending order.
+#[native]
+fn sort<T: Storage>(exec: &mut Exec<T>, series: Series) -> Result<Series, CoreError> {
+    match series {
+        Series::Block(block) => {
+            let mut keyed = Vec::new();
+            for value in block_values(exec, block)? {
+                keyed.push((sort_key(exec, value)?, value));
+            }
+            keyed.sort_by(|(a, _), (b, _)| a.cmp(b));
+            let values: Vec<MemValue> = keyed.into_iter().map(|(_, value)| value).collect();
+            set_block_values(exec, block, &values)?;
+        }
+        Series::String(string) => {
+            let mut chars = string_chars(exec, string)?;
+            chars.sort();
+            set_string_chars(exec, string, &chars)?;
+        }
+        Series::Binary(binary) => {
+            let mut bytes = exec.get_binary(binary)?;
+            bytes.sort();
+            exec.set_binary(binary, &bytes)?;
+        }
+    }
+    Ok(series)
+}
+
+// C O N V E R S I O N
+
+/// Binary with the bytes of a string, encoded as UTF-8, of a binary, or of a block of
+/// integers up to 255.
+#[native(name = "to-binary")]
+fn to_binary<T: Storage>(exec: &mut Exec<T>, value: VmValue) -> Result<VmValue, CoreError> {
+    let bytes = value_bytes(exec, value)?;
+    Ok(VmValue::Binary(exec.alloc_binary(&bytes)?))
+}
+
+/// Binary decoded from text in `encoding`: `utf-8`, `latin-1`, `hex` or `base64`.
+#[native(name = "to-binary/as")]
+fn to_binary_as<T: Storage>(
+    exec: &mut Exec<T>,
+    text: String,
+    encoding: Name,
+) -> Result<VmValue, CoreError> {
+    let bytes = match exec.get_symbol(encoding.0)?.as_str() {
+        "utf-8" => Some(text.into_bytes()),
+        "latin-1" => text.chars().map(|c| u8::try_from(c).ok()).collect(),
+        "hex" => decode_hex(&text),
+        "base64" => decode_base64(&text),
+        _ => None,
+    }
+    .ok_or(CoreError::BadArguments)?;
+    Ok(VmValue::Binary(exec.alloc_binary(&bytes)?))
+}
+
+/// Text of a binary decoded as UTF-8, or any other value formed.
+#[native(name = "to-string")]
+fn to_string<T: Storage>(exec: &mut Exec<T>, value: VmValue) -> Result<String, CoreError> {
+    match exec.to_value(value)? {
+        Value::Binary(bytes) => String::from_utf8(bytes.into_vec()).map_err(Into::into),
+        value => Ok(value.form()),
+    }
+}
+
+/// Text of the bytes of a value, see `to-binary`, in `encoding`: `utf-8`, `latin-1`,
+/// `hex` or `base64`.
+#[native(name = "to-string/as")]
+fn to_string_as<T: Storage>(
+    exec: &mut Exec<T>,
+    value: VmValue,
+    encoding: Name,
+) -> Result<String, CoreError> {
+    let bytes = value_bytes(exec, value)?;
+    match exec.get_symbol(encoding.0)?.as_str() {
+        "utf-8" => String::from_utf8(bytes).map_err(Into::into),
+        "latin-1" => Ok(bytes.into_iter().map(char::from).collect()),
+        "hex" => Ok(encode_hex(&bytes)),
+        "base64" => Ok(encode_base64(&bytes)),
+        _ => Err(CoreError::BadArguments),
+    }
+}
+
+// H I G H E R   O R D E R
+
+// Natives calling back into functions keep the state of the iteration on the stack while
+// the function runs, `[func data index extra]`, and continue in a native of their own.
+
+/// Function of one argument, `word`, evaluating `body`.
+fn body_func<T: Storage>(
+    exec: &mut Exec<T>,
+    word: Name,
+    body: Block,
+) -> Result<MemValue, CoreError> {
+    let spec = exec.alloc_block(&[VmValue::TAG_WORD, word.0])?;
+    Ok([VmValue::TAG_FUNC, exec.alloc_func(spec, body.0)?])
+}
+
+/// Save the state of an iteration over `data`, at `index` words in, and call `func`.
+fn call_each<T: Storage>(
+    exec: &mut Exec<T>,
+    func: MemValue,
+    data: Offset,
+    index: Offset,
+    extra: MemValue,
+    args: &[MemValue],
+    resume: NativeFn<T>,
+) -> Result<(), CoreError> {
+    exec.push(func)?;
+    exec.push([VmValue::TAG_BLOCK, data])?;
+    exec.push([VmValue::TAG_INT, index])?;
+    exec.push(extra)?;
+    exec.call(func, args, resume)
+}
+
+/// Result of the last call, then the state saved by [`call_each`].
+fn pop_each<T: Storage>(
+    exec: &mut Exec<T>,
+) -> Result<(MemValue, MemValue, Offset, Offset, MemValue), CoreError> {
+    let result = exec.pop::<2>()?;
+    let [func_tag, func, _, data, _, index, tag, extra] = exec.pop::<8>()?;
+    Ok((result, [func_tag, func], data, index, [tag, extra]))
+}
+
+/// Evaluate `body` for each value of `data` with `word` set to it, and collect the
+/// results in a new block.
+#[native(name = "map-each")]
+fn map_each<T: Storage>(
+    exec: &mut Exec<T>,
+    #[literal] word: Name,
+    data: Block,
+    body: Block,
+) -> Result<(), CoreError> {
+    let func = body_func(exec, word, body)?;
+    let out = [VmValue::TAG_BLOCK, exec.alloc_block(&[])?];
+    match exec.get_block::<2>(data.0, 0) {
+        Ok(value) => call_each(exec, func, data.0, 0, out, &[value], map_each_next),
+        Err(_) => exec.push(out).map_err(Into::into),
+    }
+}
+
+fn map_each_next<T: Storage>(exec: &mut Exec<T>) -> Result<(), CoreError> {
+    let (result, func, data, index, out) = pop_each(exec)?;
+    let len = exec.get_block_len(out[1])?;
+    exec.splice(out[1], len, 0, &result)?;
+    match exec.get_block::<2>(data, index + 2) {
+        Ok(value) => call_each(exec, func, data, index + 2, out, &[value], map_each_next),
+        Err(_) => exec.push(out).map_err(Into::into),
+    }
+}
+
+/// New block with the values of `data` for which `body` is true, evaluated with `word`
+/// set to the value.
+#[native]
+fn filter<T: Storage>(
+    exec: &mut Exec<T>,
+    #[literal] word: Name,
+    data: Block,
+    body: Block,
+) -> Result<(), CoreError> {
+    let func = body_func(exec, word, body)?;
+    let out = [VmValue::TAG_BLOCK, exec.alloc_block(&[])?];
+    match exec.get_block::<2>(data.0, 0) {
+        Ok(value) => call_each(exec, func, data.0, 0, out, &[value], filter_next),
+        Err(_) => exec.push(out).map_err(Into::into),
+    }
+}
+
+fn filter_next<T: Storage>(exec: &mut Exec<T>) -> Result<(), CoreError> {
+    let (result, func, data, index, out) = pop_each(exec)?;
+    if VmValue::try_from(result)?.is_truthy() {
+        let value = exec.get_block::<2>(data, index)?;
+        let len = exec.get_block_len(out[1])?;
+        exec.splice(out[1], len, 0, &value)?;
+    }
+    match exec.get_block::<2>(data, index + 2) {
+        Ok(value) => call_each(exec, func, data, index + 2, out, &[value], filter_next),
+        Err(_) => exec.push(out).map_err(Into::into),
+    }
+}
+
+/// Combine the values of `data` from left to right, calling `func` with the result so
+/// far, starting with `init`, and the next value.
+#[native]
+fn fold<T: Storage>(
+    exec: &mut Exec<T>,
+    data: Block,
+    init: VmValue,
+    func: Function,
+) -> Result<(), CoreError> {
+    let acc = init.vm_repr();
+    match exec.get_block::<2>(data.0, 0) {
+        Ok(value) => call_each(exec, func.0, data.0, 0, acc, &[acc, value], fold_next),
+        Err(_) => exec.push(acc).map_err(Into::into),
+    }
+}
+
+fn fold_next<T: Storage>(exec: &mut Exec<T>) -> Result<(), CoreError> {
+    let (acc, func, data, index, _) = pop_each(exec)?;
+    match exec.get_block::<2>(data, index + 2) {
+        Ok(value) => call_each(exec, func, data, index + 2, acc, &[acc, value], fold_next),
+        Err(_) => exec.push(acc).map_err(Into::into),
+    }
+}
+
+/// First true result of `body` evaluated for the values of `data` in turn, with `word`
+/// set to the value, or none. Stops at the first true result.
+#[native(name = "any-of")]
+fn any_of<T: Storage>(
+    exec: &mut Exec<T>,
+    #[literal] word: Name,
+    data: Block,
+    body: Block,
+) -> Result<(), CoreError> {
+    let func = body_func(exec, word, body)?;
+    let none = [VmValue::TAG_NONE, 0];
+    match exec.get_block::<2>(data.0, 0) {
+        Ok(value) => call_each(exec, func, data.0, 0, none, &[value], any_of_next),
+        Err(_) => exec.push(none).map_err(Into::into),
+    }
+}
+
+fn any_of_next<T: Storage>(exec: &mut Exec<T>) -> Result<(), CoreError> {
+    let (result, func, data, index, none) = pop_each(exec)?;
+    if VmValue::try_from(result)?.is_truthy() {
+        return exec.push(result).map_err(Into::into);
+    }
+    match exec.get_block::<2>(data, index + 2) {
+        Ok(value) => call_each(exec, func, data, index + 2, none, &[value], any_of_next),
+        Err(_) => exec.push(none).map_err(Into::into),
+    }
+}
+
+/// Last result of `body` evaluated for the values of `data` in turn, with `word` set to
+/// the value, if all of them are true, none otherwise. Stops at the first false result,
+/// and returns true if `data` is empty.
+#[native(name = "all-of")]
+fn all_of<T: Storage>(
+    exec: &mut Exec<T>,
+    #[literal] word: Name,
+    data: Block,
+    body: Block,
+) -> Result<(), CoreError> {
+    let func = body_func(exec, word, body)?;
+    let last = [VmValue::TAG_BOOL, 1];
+    match exec.get_block::<2>(data.0, 0) {
+        Ok(value) => call_each(exec, func, data.0, 0, last, &[value], all_of_next),
+        Err(_) => exec.push(last).map_err(Into::into),
+    }
+}
+
+fn all_of_next<T: Storage>(exec: &mut Exec<T>) -> Result<(), CoreError> {
+    let (result, func, data, index, _) = pop_each(exec)?;
+    if !VmValue::try_from(result)?.is_truthy() {
+        return exec.push([VmValue::TAG_NONE, 0]).map_err(Into::into);
+    }
+    match exec.get_block::<2>(data, index + 2) {
+        Ok(value) => call_each(exec, func, data, index + 2, result, &[value], all_of_next),
+        Err(_) => exec.push(result).map_err(Into::into),
+    }
+}
+
+/// Sort a block in place with `compare`, called with two values and returning true, or a
+/// negative integer, if the first one goes before the second. Values are inserted one by
+/// one into a sorted copy, at the position found by binary search, so the sort is stable.
+#[native(name = "sort/compare")]
+fn sort_compare<T: Storage>(
+    exec: &mut Exec<T>,
+    data: Block,
+    compare: Function,
+) -> Result<(), CoreError> {
+    let first = match exec.get_block::<2>(data.0, 0) {
+        Ok(first) => first,
+        Err(_) => return exec.push([VmValue::TAG_BLOCK, data.0]).map_err(Into::into),
+    };
+    let sorted = exec.alloc_block(&first)?;
+    insert_next(exec, compare.0, data.0, sorted, 1, 0, 1)
+}
+
+/// Compare the value at `index` of `data` with the middle of the range of `sorted`
+/// where it goes, `lo..hi`, keeping `[compare data sorted index lo hi]` on the stack.
+/// Once the range is empty, insert it there and go on with the next value.
+fn insert_next<T: Storage>(
+    exec: &mut Exec<T>,
+    compare: MemValue,
+    data: Offset,
+    sorted: Offset,
+    mut index: Word,
+    mut lo: Word,
+    mut hi: Word,
+) -> Result<(), CoreError> {
+    loop {
+        let value = match exec.get_block::<2>(data, index * 2) {
+            Ok(value) => value,
+            Err(_) => {
+                let values = exec.get_block_data(sorted)?.to_vec();
+                let len = exec.get_block_len(data)?;
+                exec.splice(data, 0, len, &values)?;
+                return exec.push([VmValue::TAG_BLOCK, data]).map_err(Into::into);
+            }
+        };
+        if lo < hi {
+            let mid = (lo + hi) / 2;
+            let other = exec.get_block::<2>(sorted, mid * 2)?;
+            exec.push(compare)?;
+            exec.push([VmValue::TAG_BLOCK, data])?;
+            exec.push([VmValue::TAG_BLOCK, sorted])?;
+            exec.push([VmValue::TAG_INT, index])?;
+            exec.push([VmValue::TAG_INT, lo])?;
+            exec.push([VmValue::TAG_INT, hi])?;
+            return exec.call(compare, &[value, other], sort_compare_next);
+        }
+        exec.splice(sorted, lo as usize * 2, 0, &value)?;
+        index += 1;
+        lo = 0;
+        hi = index;
+    }
+}
+
+fn sort_compare_next<T: Storage>(exec: &mut Exec<T>) -> Result<(), CoreError> {
+    let result = exec.pop::<2>()?;
+    let [_, data, _, sorted, _, index, _, lo, _, hi] = exec.pop::<10>()?;
+    let compare = exec.pop::<2>()?;
+    let mid = (lo + hi) / 2;
+    let before = match result {
+        [VmValue::TAG_INT, order] => (order as i32) < 0,
+        result => VmValue::try_from(result)?.is_truthy(),
+    };
+    if before {
+        insert_next(exec, compare, data, sorted, index, lo, mid)
+    } else {
+        insert_next(exec, compare, data, sorted, index, mid + 1, hi)
+    }
+}
+
+pub fn series_package<T>(module: &mut Module<T>) -> Result<(), CoreError>
+where
+    T: Storage,
+{
+    first::register(module)?;
+    last::register(module)?;
+    pick::register(module)?;
+    poke::register(module)?;
+    length::register(module)?;
+    empty::register(module)?;
+    append::register(module)?;
+    insert::register(module)?;
+    remove::register(module)?;
+    copy::register(module)?;
+    copy_part::register(module)?;
+    skip::register(module)?;
+    find::register(module)?;
+    reverse::register(module)?;
+    sort::register(module)?;
+    sort_compare::register(module)?;
+    to_binary::register(module)?;
+    to_binary_as::register(module)?;
+    to_string::register(module)?;
+    to_string_as::register(module)?;
+    map_each::register(module)?;
+    filter::register(module)?;
+    fold::register(module)?;
+    any_of::register(module)?;
+    all_of::register(module)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::Growable;
+
+    fn eval(module: &mut Module<Growable>, code: &str) -> Result<Value, CoreError> {
+        let block = module.parse(code)?;
+        let result = module.eval(block)?;
+        module.to_value(result)
+    }
+
+    fn int_block<const N: usize>(values: [i32; N]) -> Value {
+        Value::block(values.map(Value::int))
+    }
+
+    #[test]
+    fn test_block_series() -> Result<(), CoreError> {
+        let mut module = Module::init(Growable::new(0x10000, 0x100000))?;
+        eval(&mut module, "data: [3 1 2]")?;
+        let cases = [
+            ("first data", Value::int(3)),
+            ("last data", Value::int(2)),
+            ("pick data 2", Value::int(1)),
+            ("pick data 4", Value::None),
+            ("length? data", Value::int(3)),
+            ("empty? []", Value::Bool(true)),
+            ("append data 4", int_block([3, 1, 2, 4])),
+            ("append data [5 6]", int_block([3, 1, 2, 4, 5, 6])),
+            ("insert data 0", int_block([0, 3, 1, 2, 4, 5, 6])),
+            ("remove data", int_block([3, 1, 2, 4, 5, 6])),
+            ("poke data 1 7", Value::int(7)),
+            ("copy/part data 2", int_block([7, 1])),
+            ("skip data 4", int_block([5, 6])),
+            ("find data 4", int_block([4, 5, 6])),
+            ("find data 9", Value::None),
+            ("reverse copy data", int_block([6, 5, 4, 2, 1, 7])),
+            ("sort data", int_block([1, 2, 4, 5, 6, 7])),
+            ("data", int_block([1, 2, 4, 5, 6, 7])),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(eval(&mut module, code)?, expected, "{code}");
+        }
+
+        let result = eval(&mut module, "poke data 7 0");
+        assert!(matches!(result, Err(CoreError::BoundsCheckFailed)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_string_series() -> Result<(), CoreError> {
+        let mut module = Module::init(Growable::new(0x10000, 0x100000))?;
+        eval(&mut module, "text: \"hello\"")?;
+        let cases = [
+            ("first text", "h"),
+            ("last text", "o"),
+            ("append text \" world\"", "hello world"),
+            ("insert text 1", "1hello world"),
+            ("remove text", "hello world"),
+            ("poke text 1 \"j\" text", "jello world"),
+            ("copy/part text 4", "jell"),
+            ("skip text 6", "world"),
+            ("find text \"lo\"", "lo world"),
+            ("reverse copy \"abc\"", "cba"),
+            ("sort \"cab\"", "abc"),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(
+                eval(&mut module, code)?,
+                Value::String(expected.into()),
+                "{code}"
+            );
+        }
+        assert_eq!(eval(&mut module, "length? text")?, Value::int(11));
+        Ok(())
+    }
+
+    #[test]
+    fn test_binary_series() -> Result<(), CoreError> {
+        let mut module = Module::init(Growable::new(0x10000, 0x100000))?;
+        eval(&mut module, "data: #{DEADBEEF}")?;
+        let cases = [
+            ("first data", Value::int(0xDE)),
+            ("pick data 4", Value::int(0xEF)),
+            ("pick data 5", Value::None),
+            ("length? data", Value::int(4)),
+            ("empty? #{}", Value::Bool(true)),
+            ("copy/part data 2", Value::binary([0xDE, 0xAD])),
+            ("skip data 3", Value::binary([0xEF])),
+            ("find data #{BEEF}", Value::binary([0xBE, 0xEF])),
+            ("append data 1", Value::binary([0xDE, 0xAD, 0xBE, 0xEF, 1])),
+            ("remove data", Value::binary([0xAD, 0xBE, 0xEF, 1])),
+            ("poke data 1 0 data", Value::binary([0, 0xBE, 0xEF, 1])),
+            ("sort data", Value::binary([0, 1, 0xBE, 0xEF])),
+            ("data = #{0001BEEF}", Value::Bool(true)),
+            ("mold reduce [data]", Value::string("[#{0001BEEF}]")),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(eval(&mut module, code)?, expected, "{code}");
+        }
+
+        let result = eval(&mut module, "poke data 1 256");
+        assert!(matches!(result, Err(CoreError::BadArguments)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_binary_conversion() -> Result<(), CoreError> {
+        let mut module = Module::init(Growable::new(0x10000, 0x100000))?;
+        let cases = [
+            ("to-binary \"hi\"", Value::binary(*b"hi")),
+            ("to-binary [1 2 255]", Value::binary([1, 2, 255])),
+            (
+                "to-binary/as \"3q2+7w==\" 'base64",
+                Value::binary([0xDE, 0xAD, 0xBE, 0xEF]),
+            ),
+            ("to-binary/as \"dead\" 'hex", Value::binary([0xDE, 0xAD])),
+            ("to-binary/as \"é\" 'latin-1", Value::binary([0xE9])),
+            ("to-string #{6869}", Value::string("hi")),
+            ("to-string 42", Value::string("42")),
+            ("to-string/as #{DEADBEEF} 'hex", Value::string("DEADBEEF")),
+            (
+                "to-string/as #{DEADBEEF} 'base64",
+                Value::string("3q2+7w=="),
+            ),
+            ("to-string/as #{E9} 'latin-1", Value::string("é")),
+            ("to-string/as \"hi\" 'base64", Value::string("aGk=")),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(eval(&mut module, code)?, expected, "{code}");
+        }
+
+        let result = eval(&mut module, "to-string #{FF}");
+        assert!(matches!(result, Err(CoreError::Utf8Error(_))));
+        let result = eval(&mut module, "to-binary [256]");
+        assert!(matches!(result, Err(CoreError::BadArguments)));
+        let result = eval(&mut module, "to-string/as #{FF} 'ebcdic");
+        assert!(matches!(result, Err(CoreError::BadArguments)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_higher_order() -> Result<(), CoreError> {
+        let mut module = Module::init(Growable::new(0x10000, 0x100000))?;
+        eval(&mut module, "data: [3 1 4 1 5] twice: func [n] [n * 2]")?;
+        let cases = [
+            ("map-each x data [twice x]", int_block([6, 2, 8, 2, 10])),
+            ("map-each x [] [twice x]", int_block([])),
+            (
+                "f: func [n] [map-each x [1 2] [x + n]] f 10",
+                int_block([11, 12]),
+            ),
+            (
+                "map-each x [1 2] [map-each y [1 2] [x * 10 + y]]",
+                Value::block([int_block([11, 12]), int_block([21, 22])]),
+            ),
+            ("filter x data [x > 2]", int_block([3, 4, 5])),
+            ("fold data 0 :add", Value::int(14)),
+            ("fold data 1 func [a b] [a * b]", Value::int(60)),
+            ("fold [] 7 :add", Value::int(7)),
+            ("any-of x data [if x > 3 [x]]", Value::int(4)),
+            ("any-of x data [x > 5]", Value::None),
+            ("all-of x data [x > 0]", Value::Bool(true)),
+            ("all-of x data [x > 1]", Value::None),
+            ("all-of x [] [x > 1]", Value::Bool(true)),
+            (
+                "sort/compare copy data :lesser?",
+                int_block([1, 1, 3, 4, 5]),
+            ),
+            (
+                "sort/compare data func [a b] [b - a]",
+                int_block([5, 4, 3, 1, 1]),
+            ),
+            ("data", int_block([5, 4, 3, 1, 1])),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(eval(&mut module, code)?, expected, "{code}");
+        }
+
+        // stable: pairs with equal keys keep their order
+        let sorted = eval(
+            &mut module,
+            "sort/compare [[2 a] [1 b] [2 c] [1 d]] func [a b] [lt first a first b]",
+        )?;
+        assert_eq!(
+            sorted,
+            Value::block([
+                Value::block([Value::int(1), Value::word("b")]),
+                Value::block([Value::int(1), Value::word("d")]),
+                Value::block([Value::int(2), Value::word("a")]),
+                Value::block([Value::int(2), Value::word("c")]),
+            ])
+        );
+
+        let result = eval(&mut module, "map-each x [1 0] [10 / x]");
+        assert!(matches!(result, Err(CoreError::ZeroDivide)));
+        assert_eq!(eval(&mut module, "length? data")?, Value::int(5));
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_grows_in_place() -> Result<(), CoreError> {
+        let mut module = Module::init(Growable::new(0x10000, 0x100000))?;
+        eval(&mut module, "data: [] other: []")?;
+        eval(&mut module, "repeat i 100 [append data i append other i]")?;
+        assert_eq!(eval(&mut module, "length? data")?, Value::int(100));
+
+        // both blocks moved to grow, collection keeps them reachable from the old offsets
+        module.collect()?;
+        assert_eq!(eval(&mut module, "last data")?, Value::int(100));
+        assert_eq!(eval(&mut module, "pick other 50")?, Value::int(50));
+        eval(&mut module, "append data 101")?;
+        assert_eq!(eval(&mut module, "length? data")?, Value::int(101));
+        Ok(())
+    }
+}
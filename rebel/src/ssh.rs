@@ -1,62 +1,36 @@
 // RebelDB™ © 2025 Huly Labs • https://hulylabs.com • SPDX-License-Identifier: MIT
 
-use crate::core::{CoreError, Exec, Module};
-use crate::mem::Word;
+use crate::core::{CoreError, Exec, Module, VmValue};
+use crate::mem::Storage;
+use crate::native;
+use crate::native::{Host, Object};
+use crate::url::parse_url;
 use crate::value::Value;
 use ssh2::Session;
 use std::io::Read;
 use std::net::TcpStream;
 use std::path::Path;
 
-/// Helper function to extract a string argument from the stack
-fn get_string_arg<T>(module: &mut Exec<T>) -> Result<String, CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
-    let value = module.pop_to_value()?;
-    match value {
-        Value::String(s) => Ok(s.to_string()),
-        _ => Err(CoreError::BadArguments),
-    }
-}
-
-/// Parse a host string in the format "user@hostname:port"
+/// Parse a host string in the format "user@hostname:port" or an `ssh://` url
 /// Returns (username, hostname, port)
 /// If username is not provided, the current user is used
 /// If port is not provided, the default SSH port (22) is used
 fn parse_host(host: &str) -> Result<(String, String, u16), CoreError> {
-    // Default values
-    let username;
-    let hostname;
-    let port;
-
-    // First, split by '@' to get username and host part
-    let host_part;
-    if let Some(pos) = host.find('@') {
-        username = host[..pos].to_string();
-        host_part = &host[pos + 1..];
-    } else {
-        username = whoami::username();
-        host_part = host;
-    }
-
-    // Then, split host part by ':' to get hostname and port
-    if let Some(pos) = host_part.find(':') {
-        hostname = host_part[..pos].to_string();
-        port = host_part[pos + 1..]
-            .parse()
-            .map_err(|_| CoreError::BadArguments)?;
+    let host = if host.contains("://") {
+        host.to_string()
     } else {
-        hostname = host_part.to_string();
-        port = 22; // Default SSH port
-    }
-
-    Ok((username, hostname, port))
+        format!("ssh://{host}")
+    };
+    let url = parse_url(&host)
+        .filter(|url| url.scheme == "ssh" && !url.host.is_empty())
+        .ok_or(CoreError::BadArguments)?;
+    let username = url.user.map_or_else(whoami::username, str::to_string);
+    Ok((username, url.host.to_string(), url.port.unwrap_or(22)))
 }
 
 /// Execute a command on a remote host via SSH with options
 /// Arguments:
-/// - host: String in the format "user@hostname:port"
+/// - host: Url such as ssh://user@hostname:port, or a string "user@hostname:port"
 /// - command: String command to execute
 /// - options: Context with optional parameters:
 ///   - password: String password for authentication
@@ -67,54 +41,44 @@ fn parse_host(host: &str) -> Result<(String, String, u16), CoreError> {
 /// - stderr: String error output from the command
 /// - exit_code: Integer exit code
 /// - success: Boolean indicating if the command succeeded (exit code 0)
-fn ssh_with_options<T>(module: &mut Exec<T>) -> Result<(), CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
-    // Get arguments from the stack
-    let options = module.pop_to_value()?;
-    let command = get_string_arg(module)?;
-    let host = get_string_arg(module)?;
+#[native(name = "ssh-with-options")]
+fn ssh_with_options<T: Storage>(
+    module: &mut Exec<T>,
+    host: Host,
+    command: String,
+    options: Object,
+) -> Result<Value, CoreError> {
+    let options = module.to_value(VmValue::Context(options.0))?;
 
     // Call the implementation with options
-    ssh_with_options_impl(host, command, options, module)
+    ssh_with_options_impl(host.0, command, options)
 }
 
 /// Execute a command on a remote host via SSH
 /// Arguments:
-/// - host: String in the format "user@hostname:port"
+/// - host: Url such as ssh://user@hostname:port, or a string "user@hostname:port"
 /// - command: String command to execute
 /// Returns a context with:
 /// - stdout: String output from the command
 /// - stderr: String error output from the command
 /// - exit_code: Integer exit code
 /// - success: Boolean indicating if the command succeeded (exit code 0)
-fn ssh<T>(module: &mut Exec<T>) -> Result<(), CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
-    // Get arguments from the stack
-    let command = get_string_arg(module)?;
-    let host = get_string_arg(module)?;
-
+#[native]
+fn ssh(host: Host, command: String) -> Result<Value, CoreError> {
     // Create an empty options context
     let options = Value::object().build();
 
     // Call the implementation with options
-    ssh_with_options_impl(host, command, options, module)
+    ssh_with_options_impl(host.0, command, options)
 }
 
 /// Implementation of SSH with options
 /// This is the common implementation used by both ssh and ssh-with-options functions
-fn ssh_with_options_impl<T>(
+fn ssh_with_options_impl(
     host: String,
     command: String,
     options: Value,
-    module: &mut Exec<T>,
-) -> Result<(), CoreError>
-where
-    T: AsRef<[Word]> + AsMut<[Word]>,
-{
+) -> Result<Value, CoreError> {
     // Extract options from the context
     let password = if let Value::Context(pairs) = &options {
         pairs.iter().find_map(|(key, value)| {
@@ -182,8 +146,8 @@ where
     }
 
     // 3. Try password authentication if provided and still not authenticated
-    if !sess.authenticated() && password.is_some() {
-        let _ = sess.userauth_password(&username, &password.unwrap());
+    if let Some(password) = password.filter(|_| !sess.authenticated()) {
+        let _ = sess.userauth_password(&username, &password);
     }
 
     // 4. If still not authenticated, return an error
@@ -226,24 +190,23 @@ where
     let result = Value::object()
         .insert("stdout", stdout)
         .insert("stderr", stderr)
-        .insert("exit_code", exit_code as i32)
+        .insert("exit_code", exit_code)
         .insert("success", exit_code == 0)
         .build();
 
-    // Push result onto the stack
-    module.push_value(result)
+    Ok(result)
 }
 
 /// Register SSH functions
 pub fn ssh_package<T>(module: &mut Module<T>) -> Result<(), CoreError>
 where
-    T: AsMut<[Word]> + AsRef<[Word]>,
+    T: Storage,
 {
     // Register the basic SSH function (host, command)
-    module.add_native_fn("ssh", ssh, 2)?;
+    ssh::register(module)?;
 
     // Register the advanced SSH function with options (host, command, options)
-    module.add_native_fn("ssh-with-options", ssh_with_options, 3)?;
+    ssh_with_options::register(module)?;
 
     Ok(())
 }
@@ -267,7 +230,7 @@ mod tests {
     }
 
     // Helper function to create an execution context from a module
-    fn setup_exec(module: &mut Module<Box<[Word]>>) -> Exec<Box<[Word]>> {
+    fn setup_exec(module: &mut Module<Box<[Word]>>) -> Exec<'_, Box<[Word]>> {
         // Create a dummy block for the execution context
         let dummy_block = module
             .alloc_value(&Value::block(vec![]))
@@ -311,6 +274,16 @@ mod tests {
         assert_eq!(result.2, 2222);
     }
 
+    #[test]
+    fn test_parse_host_url() {
+        let result = parse_host("ssh://user@example.com:2222/home").unwrap();
+        assert_eq!(result.0, "user");
+        assert_eq!(result.1, "example.com");
+        assert_eq!(result.2, 2222);
+        assert!(parse_host("http://example.com").is_err());
+        assert!(parse_host("example.com:ssh").is_err());
+    }
+
     // Integration tests for SSH functionality
     // These tests require a running SSH server on localhost:2222
     // with username "testuser" and password "password"
@@ -331,7 +304,7 @@ mod tests {
         exec.push_value(options).expect("Failed to push options");
 
         // Call the SSH function
-        ssh_with_options(&mut exec).expect("Failed to call SSH function");
+        ssh_with_options::native(&mut exec).expect("Failed to call SSH function");
 
         // Get the result
         let result = exec.pop_to_value().expect("Failed to get result");
@@ -367,7 +340,7 @@ mod tests {
 
             // Verify success is true
             if let Some(Value::Bool(success)) = result_map.get("success") {
-                assert_eq!(*success, true, "success should be true");
+                assert!(*success, "success should be true");
             } else {
                 panic!("success field missing or not a boolean");
             }
@@ -391,7 +364,7 @@ mod tests {
             .expect("Failed to push command");
         exec.push_value(options.clone())
             .expect("Failed to push options");
-        ssh_with_options(&mut exec).expect("Failed to create test file");
+        ssh_with_options::native(&mut exec).expect("Failed to create test file");
         exec.pop_to_value().expect("Failed to get result"); // Discard result
 
         // 2. Read the file content - push arguments in reverse order (host, command, options)
@@ -402,7 +375,7 @@ mod tests {
             .expect("Failed to push command");
         exec.push_value(options.clone())
             .expect("Failed to push options");
-        ssh_with_options(&mut exec).expect("Failed to read test file");
+        ssh_with_options::native(&mut exec).expect("Failed to read test file");
 
         // Get the result
         let read_result = exec.pop_to_value().expect("Failed to get result");
@@ -429,7 +402,7 @@ mod tests {
         exec.push_value(Value::string(delete_file_cmd))
             .expect("Failed to push command");
         exec.push_value(options).expect("Failed to push options");
-        ssh_with_options(&mut exec).expect("Failed to delete test file");
+        ssh_with_options::native(&mut exec).expect("Failed to delete test file");
 
         // Get the result
         let delete_result = exec.pop_to_value().expect("Failed to get result");
@@ -463,7 +436,7 @@ mod tests {
         exec.push_value(Value::string(commands))
             .expect("Failed to push command");
         exec.push_value(options).expect("Failed to push options");
-        ssh_with_options(&mut exec).expect("Failed to execute multiple commands");
+        ssh_with_options::native(&mut exec).expect("Failed to execute multiple commands");
 
         // Get the result
         let result = exec.pop_to_value().expect("Failed to get result");
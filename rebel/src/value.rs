@@ -1,5 +1,7 @@
 // RebelDB™ © 2025 Huly Labs • https://hulylabs.com • SPDX-License-Identifier: MIT
 
+use crate::date::{form_date, form_time};
+use crate::encoding::encode_hex;
 use smol_str::SmolStr;
 use std::convert::From;
 use std::fmt;
@@ -8,14 +10,33 @@ use std::fmt;
 pub enum Value {
     None,
     Int(i32),
+    /// Integer that doesn't fit in an `Int`.
+    Int64(i64),
+    Decimal(f64),
+    /// Milliseconds since the Unix epoch, in UTC.
+    Date(i64),
+    /// Duration in milliseconds.
+    Time(i64),
     Bool(bool),
     Block(Box<[Value]>),
     String(SmolStr),
+    Binary(Box<[u8]>),
+    /// Path in the file system, `%src/main.rs`.
+    File(SmolStr),
+    /// Url such as `ssh://user@host:22`.
+    Url(SmolStr),
     Word(SmolStr),
     SetWord(SmolStr),
     GetWord(SmolStr),
+    LitWord(SmolStr),
+    Refinement(SmolStr),
     Context(Box<[(SmolStr, Value)]>),
     Path(Box<[Value]>),
+    SetPath(Box<[Value]>),
+    GetPath(Box<[Value]>),
+    Error(Box<[(SmolStr, Value)]>),
+    /// Function with its spec and body blocks, molded as `func spec body`.
+    Func(Box<[Value]>, Box<[Value]>),
 }
 
 impl fmt::Display for Value {
@@ -24,11 +45,112 @@ impl fmt::Display for Value {
     }
 }
 
+fn form_pairs(prefix: &str, pairs: &[(SmolStr, Value)]) -> String {
+    let mut result = prefix.to_string();
+    let mut first = true;
+    for (key, value) in pairs.iter() {
+        if !first {
+            result.push(' ');
+        }
+        first = false;
+        result.push_str(key);
+        result.push(':');
+        result.push_str(&value.form());
+    }
+    result.push(']');
+    result
+}
+
+fn mold_block(items: &[Value]) -> String {
+    let items: Vec<String> = items.iter().map(Value::mold).collect();
+    format!("[{}]", items.join(" "))
+}
+
+/// Values of the pairs are evaluated when `make` loads them back, so words are quoted.
+fn mold_pairs(prefix: &str, pairs: &[(SmolStr, Value)]) -> String {
+    let mut result = prefix.to_string();
+    for (i, (key, value)) in pairs.iter().enumerate() {
+        if i > 0 {
+            result.push(' ');
+        }
+        result.push_str(key);
+        result.push_str(": ");
+        if let Value::Word(word) = value {
+            result.push('\'');
+            result.push_str(word);
+        } else {
+            result.push_str(&value.mold());
+        }
+    }
+    result.push(']');
+    result
+}
+
+fn mold_string(string: &str) -> String {
+    let mut result = String::with_capacity(string.len() + 2);
+    result.push('"');
+    for char in string.chars() {
+        match char {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            char => result.push(char),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Files are quoted if they have characters ending a file literal, e.g. `%"my file"`.
+fn mold_file(path: &str) -> String {
+    if path.contains(|c: char| c.is_ascii_whitespace() || matches!(c, '"' | '[' | ']')) {
+        format!("%{}", mold_string(path))
+    } else {
+        format!("%{path}")
+    }
+}
+
+/// Decimals keep a fraction or an exponent, so that they load back as decimals. Very
+/// large and very small ones are written with an exponent rather than all their digits.
+fn form_decimal(value: f64) -> String {
+    let magnitude = value.abs();
+    let result = if magnitude >= 1e16 || (magnitude != 0.0 && magnitude < 1e-6) {
+        format!("{value:e}")
+    } else {
+        value.to_string()
+    };
+    if value.is_finite() && !result.contains(['.', 'e']) {
+        result + ".0"
+    } else {
+        result
+    }
+}
+
+fn form_path(prefix: &str, path: &[Value], suffix: &str) -> String {
+    let mut result = prefix.to_string();
+    let mut first = true;
+    for segment in path.iter() {
+        if !first {
+            result.push('/');
+        }
+        first = false;
+        result.push_str(&segment.form());
+    }
+    result.push_str(suffix);
+    result
+}
+
 impl Value {
     pub fn form(&self) -> String {
         match self {
             Value::None => "none".into(),
             Value::Int(n) => n.to_string(),
+            Value::Int64(n) => n.to_string(),
+            Value::Decimal(n) => form_decimal(*n),
+            Value::Date(millis) => form_date(*millis),
+            Value::Time(millis) => form_time(*millis),
             Value::Bool(b) => {
                 if *b {
                     "true".into()
@@ -37,6 +159,8 @@ impl Value {
                 }
             }
             Value::String(s) => s.to_string(),
+            Value::Binary(bytes) => format!("#{{{}}}", encode_hex(bytes)),
+            Value::File(path) | Value::Url(path) => path.to_string(),
             Value::Word(w) => w.to_string(),
             Value::SetWord(w) => {
                 let mut result = w.to_string();
@@ -45,48 +169,51 @@ impl Value {
             }
             Value::GetWord(w) => {
                 let mut result = ":".to_string();
-                result.push_str(&w);
+                result.push_str(w);
                 result
             }
-            Value::Block(block) => {
-                let mut result = String::new();
-                let mut first = true;
-                for item in block.iter() {
-                    if !first {
-                        result.push(' ');
-                    }
-                    first = false;
-                    result.push_str(&item.form());
-                }
+            Value::LitWord(w) => {
+                let mut result = "'".to_string();
+                result.push_str(w);
                 result
             }
-            Value::Context(pairs) => {
-                let mut result = "make object! [".to_string();
-                let mut first = true;
-                for (key, value) in pairs.iter() {
-                    if !first {
-                        result.push(' ');
-                    }
-                    first = false;
-                    result.push_str(&key);
-                    result.push(':');
-                    result.push_str(&value.form());
-                }
-                result.push(']');
+            Value::Refinement(w) => {
+                let mut result = "/".to_string();
+                result.push_str(w);
                 result
             }
-            Value::Path(path) => {
+            Value::Block(block) => {
                 let mut result = String::new();
                 let mut first = true;
-                for segment in path.iter() {
+                for item in block.iter() {
                     if !first {
-                        result.push('/');
+                        result.push(' ');
                     }
                     first = false;
-                    result.push_str(&segment.form());
+                    result.push_str(&item.form());
                 }
                 result
             }
+            Value::Context(pairs) => form_pairs("make object! [", pairs),
+            Value::Error(pairs) => form_pairs("make error! [", pairs),
+            Value::Path(path) => form_path("", path, ""),
+            Value::SetPath(path) => form_path("", path, ":"),
+            Value::GetPath(path) => form_path(":", path, ""),
+            Value::Func(_, _) => self.mold(),
+        }
+    }
+
+    /// Source text of the value, which loads back as the same value: unlike [`Value::form`]
+    /// it quotes and escapes strings and keeps the brackets of blocks.
+    pub fn mold(&self) -> String {
+        match self {
+            Value::String(string) => mold_string(string),
+            Value::File(path) => mold_file(path),
+            Value::Block(block) => mold_block(block),
+            Value::Context(pairs) => mold_pairs("make object! [", pairs),
+            Value::Error(pairs) => mold_pairs("make error! [", pairs),
+            Value::Func(spec, body) => format!("func {} {}", mold_block(spec), mold_block(body)),
+            _ => self.form(),
         }
     }
 
@@ -104,11 +231,39 @@ impl Value {
         Value::Int(value)
     }
 
+    /// Create an integer value, an Int64 only if it doesn't fit in an Int
+    pub fn integer(value: i64) -> Self {
+        match i32::try_from(value) {
+            Ok(value) => Value::Int(value),
+            Err(_) => Value::Int64(value),
+        }
+    }
+
+    /// Create a Decimal value
+    pub fn decimal(value: f64) -> Self {
+        Value::Decimal(value)
+    }
+
     /// Create a String value
     pub fn string<S: Into<SmolStr>>(value: S) -> Self {
         Value::String(value.into())
     }
 
+    /// Create a Binary value
+    pub fn binary<B: Into<Box<[u8]>>>(value: B) -> Self {
+        Value::Binary(value.into())
+    }
+
+    /// Create a File value
+    pub fn file<S: Into<SmolStr>>(value: S) -> Self {
+        Value::File(value.into())
+    }
+
+    /// Create a Url value
+    pub fn url<S: Into<SmolStr>>(value: S) -> Self {
+        Value::Url(value.into())
+    }
+
     /// Create a Word value
     pub fn word<S: Into<SmolStr>>(value: S) -> Self {
         Value::Word(value.into())
@@ -188,12 +343,14 @@ impl Value {
         matches!(self, Value::Context(_))
     }
 
+    /// Check if value is Error
+    pub fn is_error(&self) -> bool {
+        matches!(self, Value::Error(_))
+    }
+
     /// Check if value represents a boolean (Int with value 0 or 1)
     pub fn is_boolean(&self) -> bool {
-        match self {
-            Value::Int(0 | 1) => true,
-            _ => false,
-        }
+        matches!(self, Value::Int(0 | 1))
     }
 
     /// Check if value is truthy (anything except None, Int(0), or empty Block/Context)
@@ -288,10 +445,10 @@ impl Value {
     // CONTEXT OPERATIONS
     //==================================================================
 
-    /// Get a value from a Context using a string key
+    /// Get a value from a Context (or an Error's fields) using a string key
     pub fn get<K: AsRef<str>>(&self, key: K) -> Option<&Value> {
         match self {
-            Value::Context(pairs) => {
+            Value::Context(pairs) | Value::Error(pairs) => {
                 let key_ref = key.as_ref();
                 pairs
                     .iter()
@@ -458,7 +615,7 @@ impl Value {
     {
         match self {
             Value::Block(block) => {
-                let mapped = block.to_vec().into_iter().map(f).collect::<Vec<_>>();
+                let mapped = block.into_vec().into_iter().map(f).collect::<Vec<_>>();
                 Value::Block(mapped.into_boxed_slice())
             }
             // If not a block, return as-is
@@ -473,7 +630,7 @@ impl Value {
     {
         match self {
             Value::Block(block) => {
-                let filtered = block.to_vec().into_iter().filter(f).collect::<Vec<_>>();
+                let filtered = block.into_vec().into_iter().filter(f).collect::<Vec<_>>();
                 Value::Block(filtered.into_boxed_slice())
             }
             // If not a block, return as-is
@@ -555,8 +712,7 @@ impl Value {
                     Value::Context(pairs_vec.into_boxed_slice())
                 } else {
                     // Create new context
-                    let mut pairs = Vec::new();
-                    pairs.push((keys[0].clone().into(), value));
+                    let pairs = vec![(keys[0].clone().into(), value)];
                     Value::Context(pairs.into_boxed_slice())
                 }
             } else {
@@ -588,6 +744,38 @@ impl Value {
         set_path_inner(self, &path_vec, value)
     }
 
+    //==================================================================
+    // COMPARISON
+    //==================================================================
+
+    /// Structural equality: blocks and paths are equal when their items are, contexts
+    /// and errors when they have the same words with equal values, in any order.
+    /// Integers equal decimals of the same value.
+    pub fn equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Block(a), Value::Block(b))
+            | (Value::Path(a), Value::Path(b))
+            | (Value::SetPath(a), Value::SetPath(b))
+            | (Value::GetPath(a), Value::GetPath(b)) => {
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| a.equals(b))
+            }
+            (Value::Int(a), Value::Decimal(b)) | (Value::Decimal(b), Value::Int(a)) => {
+                *a as f64 == *b
+            }
+            (Value::Int64(a), Value::Decimal(b)) | (Value::Decimal(b), Value::Int64(a)) => {
+                *a as f64 == *b
+            }
+            (Value::Context(a), Value::Context(b)) | (Value::Error(a), Value::Error(b)) => {
+                a.len() == b.len()
+                    && a.iter().all(|(key, value)| {
+                        b.iter()
+                            .any(|(other_key, other)| key == other_key && value.equals(other))
+                    })
+            }
+            _ => self == other,
+        }
+    }
+
     //==================================================================
     // CONVERSION UTILITIES
     //==================================================================
@@ -597,14 +785,24 @@ impl Value {
         match self {
             Value::None => Value::String("none".into()),
             Value::Int(n) => Value::String(n.to_string().into()),
+            Value::Int64(n) => Value::String(n.to_string().into()),
+            Value::Decimal(n) => Value::String(form_decimal(*n).into()),
+            Value::Date(_) | Value::Time(_) => Value::String(self.form().into()),
             Value::Bool(b) => Value::String(if *b { "true" } else { "false" }.into()),
             Value::String(s) => Value::String(s.clone()),
+            Value::Binary(_) => Value::String(self.form().into()),
+            Value::File(path) | Value::Url(path) => Value::String(path.clone()),
             Value::Word(w) => Value::String(w.clone()),
             Value::SetWord(w) => Value::String(format!("{}:", w).into()),
             Value::GetWord(w) => Value::String(format!("{}:", w).into()),
+            Value::LitWord(w) => Value::String(format!("'{}", w).into()),
+            Value::Refinement(w) => Value::String(format!("/{}", w).into()),
             Value::Block(_) => Value::String(format!("{}", self).into()),
             Value::Context(_) => Value::String(format!("{}", self).into()),
-            Value::Path(_) => Value::String(format!("{}", self).into()),
+            Value::Path(_) | Value::SetPath(_) | Value::GetPath(_) => {
+                Value::String(format!("{}", self).into())
+            }
+            Value::Error(_) | Value::Func(_, _) => Value::String(format!("{}", self).into()),
         }
     }
 
@@ -612,9 +810,11 @@ impl Value {
     pub fn to_int_value(&self) -> Value {
         match self {
             Value::Int(n) => Value::Int(*n),
+            Value::Int64(n) => Value::Int64(*n),
+            Value::Decimal(n) if n.is_finite() => Value::integer(*n as i64),
             Value::String(s) => {
-                if let Ok(n) = s.parse::<i32>() {
-                    Value::Int(n)
+                if let Ok(n) = s.parse::<i64>() {
+                    Value::integer(n)
                 } else {
                     Value::None
                 }
@@ -640,6 +840,12 @@ pub struct ContextBuilder {
     pairs: Vec<(SmolStr, Value)>,
 }
 
+impl Default for ContextBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ContextBuilder {
     /// Create a new empty ContextBuilder
     pub fn new() -> Self {
@@ -663,6 +869,12 @@ pub struct BlockBuilder {
     values: Vec<Value>,
 }
 
+impl Default for BlockBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BlockBuilder {
     /// Create a new empty BlockBuilder
     pub fn new() -> Self {
@@ -701,6 +913,12 @@ impl From<i32> for Value {
     }
 }
 
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::integer(value)
+    }
+}
+
 impl From<bool> for Value {
     fn from(value: bool) -> Self {
         Value::Bool(value)
@@ -919,9 +1137,7 @@ macro_rules! rebel {
     }};
     (@parse_context [$($pairs:expr),*] $key:ident => $val:expr) => {{
         let new_pair = (stringify!($key).into(), Value::from($val));
-        let mut v = vec![$($pairs),*];
-        v.push(new_pair);
-        v
+        vec![$($pairs,)* new_pair]
     }};
 
     (@parse_context [$($pairs:expr),*] $key:literal => $val:expr , $($rest:tt)*) => {{
@@ -942,9 +1158,7 @@ macro_rules! rebel {
             s => s.into()
         };
         let new_pair = (key_str, Value::from($val));
-        let mut v = vec![$($pairs),*];
-        v.push(new_pair);
-        v
+        vec![$($pairs,)* new_pair]
     }};
 }
 
@@ -1037,6 +1251,17 @@ mod tests {
         assert_eq!(config.get("timeout"), Some(&Value::Int(5000)));
     }
 
+    #[test]
+    fn test_equals() {
+        let a = Value::context([("x", Value::int(1)), ("y", Value::block([Value::int(2)]))]);
+        let b = Value::context([("y", Value::block([Value::int(2)])), ("x", Value::int(1))]);
+        assert!(a.equals(&b));
+        assert!(!a.equals(&Value::context([("x", Value::int(1))])));
+        assert!(Value::block([a.clone()]).equals(&Value::block([b])));
+        assert!(!Value::block([Value::int(1)]).equals(&Value::path([Value::int(1)])));
+        assert!(!Value::word("x").equals(&Value::set_word("x")));
+    }
+
     #[test]
     fn test_type_checking() {
         let int_val = Value::int(42);
@@ -1704,7 +1929,7 @@ mod tests {
         let name = "Alice";
         let age = 30;
         let is_active = true;
-        let tags = vec!["user", "premium"];
+        let tags = ["user", "premium"];
 
         //==============================================================
         // 1. DIRECT VARIABLE USAGE
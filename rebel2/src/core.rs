@@ -490,10 +490,7 @@ mod tests {
     }
 
     fn create_external_blob() -> Blob {
-        let mut hash = [0u8; HASH_SIZE];
-        for i in 0..HASH_SIZE {
-            hash[i] = (i as u8) % 255;
-        }
+        let hash: [u8; HASH_SIZE] = std::array::from_fn(|i| (i as u8) % 255);
         Blob::External(hash)
     }
 
@@ -1,6 +1,6 @@
 // RebelDB™ © 2025 Huly Labs • https://hulylabs.com • SPDX-License-Identifier: MIT
 
-use crate::core::{Blob, BlobStore, Block, CoreError, Value, Values, WordKind};
+use crate::core::{BlobStore, Block, CoreError, Value, WordKind};
 use crate::parse::Collector;
 use smol_str::SmolStr;
 use std::collections::HashMap;
@@ -60,7 +60,7 @@ enum Op {
     CallNative(usize),
 }
 
-struct Process<'a> {
+pub struct Process<'a> {
     runtime: &'a mut Runtime,
     // block: Block,
     // ip: usize,
@@ -71,7 +71,7 @@ struct Process<'a> {
 }
 
 impl<'a> Process<'a> {
-    fn new(runtime: &'a mut Runtime, block: Block) -> Result<Self, CoreError> {
+    fn new(runtime: &'a mut Runtime, _block: Block) -> Result<Self, CoreError> {
         Ok(Process {
             runtime,
             // block,
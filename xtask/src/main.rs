@@ -0,0 +1,22 @@
+// RebelDB™ © 2025 Huly Labs • https://hulylabs.com • SPDX-License-Identifier: MIT
+
+//! Development tasks, run with `cargo xtask <task>`:
+//! - `stdlib-image` writes `rebel/src/stdlib.img`, the heap image of the standard library
+//!   that `Module::init` loads instead of evaluating `stdlib.rebel`. Run it after changing
+//!   the standard library, the core natives or the heap layout.
+
+use anyhow::{bail, Result};
+use rebel::boot::stdlib_image;
+use std::path::Path;
+
+fn main() -> Result<()> {
+    match std::env::args().nth(1).as_deref() {
+        Some("stdlib-image") => {
+            let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../rebel/src/stdlib.img");
+            std::fs::write(&path, stdlib_image()?)?;
+            println!("Wrote {}", path.display());
+            Ok(())
+        }
+        _ => bail!("usage: cargo xtask stdlib-image"),
+    }
+}